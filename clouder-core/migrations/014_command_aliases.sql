@@ -0,0 +1,8 @@
+-- 014: Per-guild command aliases
+CREATE TABLE IF NOT EXISTS command_aliases (
+	guild_id TEXT NOT NULL,
+	alias TEXT NOT NULL,
+	command TEXT NOT NULL,
+	created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY (guild_id, alias)
+);
@@ -0,0 +1,18 @@
+-- 015: Scheduled announcements
+
+CREATE TABLE IF NOT EXISTS scheduled_messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id TEXT NOT NULL,
+    channel_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    cron_spec TEXT,
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    next_run_at INTEGER,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_messages_guild ON scheduled_messages (guild_id);
+CREATE INDEX IF NOT EXISTS idx_scheduled_messages_due ON scheduled_messages (enabled, next_run_at);
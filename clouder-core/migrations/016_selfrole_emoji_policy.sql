@@ -0,0 +1,7 @@
+-- 016: Per-guild emoji allow/deny policy for self-role panels
+CREATE TABLE IF NOT EXISTS selfrole_emoji_policies (
+	guild_id TEXT PRIMARY KEY,
+	mode TEXT NOT NULL CHECK (mode IN ('allow', 'deny')),
+	emojis TEXT NOT NULL DEFAULT '',
+	updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
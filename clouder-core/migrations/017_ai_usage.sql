@@ -0,0 +1,11 @@
+-- 017: Per-guild AI token usage log
+CREATE TABLE IF NOT EXISTS ai_usage (
+	id INTEGER PRIMARY KEY AUTOINCREMENT,
+	guild_id TEXT NOT NULL,
+	user_id TEXT NOT NULL,
+	prompt_tokens INTEGER NOT NULL DEFAULT 0,
+	completion_tokens INTEGER NOT NULL DEFAULT 0,
+	created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_usage_guild_created ON ai_usage(guild_id, created_at);
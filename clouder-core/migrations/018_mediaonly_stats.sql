@@ -0,0 +1,11 @@
+-- 018: Daily per-channel media-only deletion counts
+CREATE TABLE IF NOT EXISTS mediaonly_stats (
+	guild_id TEXT NOT NULL,
+	channel_id TEXT NOT NULL,
+	user_id TEXT NOT NULL,
+	day TEXT NOT NULL,
+	deletions INTEGER NOT NULL DEFAULT 0,
+	PRIMARY KEY (guild_id, channel_id, day, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mediaonly_stats_day ON mediaonly_stats(day);
@@ -0,0 +1,4 @@
+-- 019: Mark self-role panels attached to an existing message. Attached panels
+-- keep the message's own content; only its buttons are managed by the bot.
+
+ALTER TABLE selfrole_configs ADD COLUMN attached BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,18 @@
+-- 020: Welcome/goodbye message rotation. A guild can keep several variants per
+-- kind and the rotation mode picks one per join/leave. 'off' keeps the single
+-- configured message. The counters drive round-robin and survive config saves.
+
+ALTER TABLE welcome_goodbye_configs ADD COLUMN welcome_rotation TEXT NOT NULL DEFAULT 'off' CHECK (welcome_rotation IN ('off', 'random', 'round_robin'));
+ALTER TABLE welcome_goodbye_configs ADD COLUMN goodbye_rotation TEXT NOT NULL DEFAULT 'off' CHECK (goodbye_rotation IN ('off', 'random', 'round_robin'));
+ALTER TABLE welcome_goodbye_configs ADD COLUMN welcome_rotation_counter INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE welcome_goodbye_configs ADD COLUMN goodbye_rotation_counter INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS welcome_goodbye_variants (
+	id INTEGER PRIMARY KEY AUTOINCREMENT,
+	guild_id TEXT NOT NULL,
+	kind TEXT NOT NULL CHECK (kind IN ('welcome', 'goodbye')),
+	content TEXT NOT NULL,
+	created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_welcome_goodbye_variants_guild ON welcome_goodbye_variants(guild_id, kind);
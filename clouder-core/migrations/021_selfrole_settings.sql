@@ -0,0 +1,6 @@
+-- 021: Per-guild settings shared by all self-role panels
+CREATE TABLE IF NOT EXISTS selfrole_settings (
+	guild_id TEXT PRIMARY KEY,
+	show_updated BOOLEAN NOT NULL DEFAULT FALSE,
+	updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
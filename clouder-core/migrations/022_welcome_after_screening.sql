@@ -0,0 +1,4 @@
+-- 022: Welcome after membership screening. When set, members who join a
+-- server with screening are welcomed once they pass it instead of on join.
+
+ALTER TABLE welcome_goodbye_configs ADD COLUMN welcome_after_screening BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- 023: Which name fills the {username} placeholder in welcome/goodbye messages.
+-- 'global_name' and 'nickname' fall back to the username when unset.
+
+ALTER TABLE welcome_goodbye_configs ADD COLUMN name_placeholder_source TEXT NOT NULL DEFAULT 'username' CHECK (name_placeholder_source IN ('username', 'global_name', 'nickname'));
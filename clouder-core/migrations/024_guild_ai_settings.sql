@@ -0,0 +1,7 @@
+-- 024: Per-guild AI reply settings. allowed_channel_ids is comma-separated
+-- and an empty list lets the bot answer in every channel.
+CREATE TABLE IF NOT EXISTS guild_ai_settings (
+	guild_id TEXT PRIMARY KEY,
+	allowed_channel_ids TEXT NOT NULL DEFAULT '',
+	updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
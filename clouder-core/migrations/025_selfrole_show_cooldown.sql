@@ -0,0 +1,3 @@
+-- 025: Opt-in cooldown line in self-role panel footers
+
+ALTER TABLE selfrole_settings ADD COLUMN show_cooldown BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- 026: Per-channel spacing for AI replies. 0 turns it off.
+
+ALTER TABLE guild_ai_settings ADD COLUMN channel_cooldown_secs INTEGER NOT NULL DEFAULT 0;
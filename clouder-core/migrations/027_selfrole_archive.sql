@@ -0,0 +1,4 @@
+-- 027: Archive self-role panels when the bot leaves a guild instead of losing them.
+
+ALTER TABLE selfrole_configs ADD COLUMN archived_at DATETIME;
+CREATE INDEX idx_selfrole_configs_archived_at ON selfrole_configs(archived_at);
@@ -0,0 +1,3 @@
+-- 028: How a self-role click is confirmed: an ephemeral reply or a brief reaction on the panel
+
+ALTER TABLE selfrole_settings ADD COLUMN feedback_mode TEXT NOT NULL DEFAULT 'reply';
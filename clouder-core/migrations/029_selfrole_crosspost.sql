@@ -0,0 +1,3 @@
+-- 029: Opt-in auto-publish of self-role panels posted in announcement channels
+
+ALTER TABLE selfrole_settings ADD COLUMN crosspost BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- 030: Per-panel click cooldown, and a per-guild default for new panels. 5 matches the old fixed cooldown.
+
+ALTER TABLE selfrole_configs ADD COLUMN cooldown_secs INTEGER NOT NULL DEFAULT 5;
+ALTER TABLE selfrole_settings ADD COLUMN default_cooldown_secs INTEGER NOT NULL DEFAULT 5;
@@ -0,0 +1,9 @@
+-- 031: Members who joined while screening was pending, so their held-back welcome
+-- doesn't depend on the old member state being cached when they pass.
+
+CREATE TABLE IF NOT EXISTS pending_welcomes (
+    guild_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    joined_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (guild_id, user_id)
+);
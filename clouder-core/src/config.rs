@@ -4,6 +4,7 @@ use serenity::all::Http;
 use sqlx::SqlitePool;
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{error, info, warn};
 
 // default color for embeds when none is configured; exposed publicly so tests and
@@ -12,6 +13,8 @@ pub const DEFAULT_EMBED_COLOR: u32 = 0xFFFFFF; // white
 
 const DEFAULT_API_BASE: &str = "http://127.0.0.1:8080";
 const DEFAULT_BIND_ADDR: &str = "127.0.0.1:3000";
+const DEFAULT_WEB_MAX_CONCURRENT_REQUESTS: usize = 256;
+const DEFAULT_WEB_REQUEST_TIMEOUT_SECS: u64 = 30;
 const DEFAULT_DATABASE_URL: &str = "data/db.sqlite";
 const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434/v1";
 const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
@@ -20,6 +23,14 @@ const DEFAULT_OPENAI_MODEL: &str = "gpt-3.5-turbo";
 const DEFAULT_LLM_TEMPERATURE: f32 = 0.7;
 const DEFAULT_LLM_MAX_TOKENS: u32 = 1000;
 const DEFAULT_LLM_TIMEOUT_SECONDS: u64 = 30;
+const DEFAULT_LLM_USAGE_RETENTION_DAYS: i64 = 90;
+/// Seconds a user waits between AI prompts.
+pub const LLM_COOLDOWN_SECS: u64 = 10;
+const DEFAULT_SELFROLE_MAX_PANELS_PER_CHANNEL: i64 = 10;
+const DEFAULT_SELFROLE_COOLDOWN_CLEANUP_BATCH_SIZE: i64 = 0;
+const DEFAULT_SELFROLE_ARCHIVE_RETENTION_DAYS: i64 = 30;
+const DEFAULT_MEDIAONLY_STATS_RETENTION_DAYS: i64 = 90;
+const DEFAULT_MEDIAONLY_MAX_CHANNELS: i64 = 50;
 
 // Minimum byte length for any cryptographic secret loaded from env. 32 bytes
 // (256 bits) is the standard "comfortably above brute-force" threshold and
@@ -35,6 +46,16 @@ pub struct Config {
     pub github_token: Option<String>,
     pub scheduler_interval: u64,
     pub default_timezone: String,
+    pub selfrole_max_panels_per_channel: i64,
+    /// Expired cooldowns deleted per statement by the cleanup task; 0 deletes
+    /// them all in one statement.
+    pub selfrole_cooldown_cleanup_batch_size: i64,
+    /// Days a left guild's self-role panels are kept for a rejoin; 0 deletes them on leave.
+    pub selfrole_archive_retention_days: i64,
+    /// Days of media-only deletion stats to keep; 0 turns tracking off.
+    pub mediaonly_stats_retention_days: i64,
+    /// Media-only channels one guild may configure; the bot owner is exempt.
+    pub mediaonly_max_channels: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +69,10 @@ pub struct DiscordConfig {
 pub struct WebConfig {
     pub api_base: String,
     pub bind_addr: String,
+    /// Requests in flight beyond this are shed with `503 Service Unavailable`.
+    pub max_concurrent_requests: usize,
+    /// Requests running longer than this are answered with `408 Request Timeout`.
+    pub request_timeout_secs: u64,
     pub oauth: OAuthConfig,
     pub embed: EmbedConfig,
     pub session_secret: String,
@@ -99,6 +124,24 @@ pub struct LlmConfig {
     pub allowed_users: Vec<u64>,
     pub dm_allowed_users: Vec<u64>,
     pub no_cooldown_users: Vec<u64>,
+    /// Seconds a user waits between retry button presses, tracked apart from prompts.
+    pub retry_cooldown_secs: u64,
+    /// Tokens a single guild may spend per calendar month (UTC); `None` is unlimited.
+    pub guild_monthly_token_budget: Option<u64>,
+    /// Whether the dashboard usage page lists the guild's heaviest users.
+    pub usage_show_top_users: bool,
+    /// Days of per-request AI usage to keep; 0 keeps it forever. The current
+    /// budget month is always kept.
+    pub usage_retention_days: i64,
+    /// Tell the model the server, channel, and asker's display name. Off by default for privacy.
+    pub include_context: bool,
+}
+
+impl LlmConfig {
+    /// How long a user waits between presses of the retry button.
+    pub fn retry_cooldown(&self) -> Duration {
+        Duration::from_secs(self.retry_cooldown_secs)
+    }
 }
 
 fn require_env(key: &str) -> Result<String, anyhow::Error> {
@@ -174,6 +217,16 @@ impl Config {
 
         let api_base = optional_env("API_BASE", DEFAULT_API_BASE);
         let bind_addr = optional_env("WEB_BIND_ADDR", DEFAULT_BIND_ADDR);
+        let web_max_concurrent_requests = env::var("WEB_MAX_CONCURRENT_REQUESTS")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_WEB_MAX_CONCURRENT_REQUESTS);
+        let web_request_timeout_secs = env::var("WEB_REQUEST_TIMEOUT_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_WEB_REQUEST_TIMEOUT_SECS);
         let database_url = optional_env("DATABASE_URL", DEFAULT_DATABASE_URL);
 
         let embed_default_color = match env::var("EMBED_DEFAULT_COLOR") {
@@ -286,6 +339,26 @@ impl Config {
         let llm_allowed_users = parse_user_ids("LLM_ALLOWED_USERS");
         let llm_dm_allowed_users = parse_user_ids("LLM_DM_ALLOWED_USERS");
         let llm_no_cooldown_users = parse_user_ids("LLM_NO_COOLDOWN_USERS");
+        let llm_retry_cooldown_secs = env::var("LLM_RETRY_COOLDOWN_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(LLM_COOLDOWN_SECS);
+
+        let llm_guild_monthly_token_budget = env::var("LLM_GUILD_MONTHLY_TOKEN_BUDGET")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|&n| n > 0);
+        let llm_usage_show_top_users = env::var("LLM_USAGE_SHOW_TOP_USERS")
+            .map(|s| s != "false" && s != "0")
+            .unwrap_or(true);
+        let llm_usage_retention_days = env::var("LLM_USAGE_RETENTION_DAYS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|n| *n >= 0)
+            .unwrap_or(DEFAULT_LLM_USAGE_RETENTION_DAYS);
+        let llm_include_context = env::var("LLM_INCLUDE_CONTEXT")
+            .map(|s| s == "true" || s == "1")
+            .unwrap_or(false);
 
         let github_token = env::var("GITHUB_TOKEN").ok().filter(|s| !s.is_empty());
 
@@ -296,6 +369,36 @@ impl Config {
 
         let default_timezone = env::var("DEFAULT_TIMEZONE").unwrap_or_else(|_| "UTC".to_string());
 
+        let selfrole_max_panels_per_channel = env::var("SELFROLE_MAX_PANELS_PER_CHANNEL")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_SELFROLE_MAX_PANELS_PER_CHANNEL);
+
+        let selfrole_cooldown_cleanup_batch_size = env::var("SELFROLE_COOLDOWN_CLEANUP_BATCH_SIZE")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|n| *n >= 0)
+            .unwrap_or(DEFAULT_SELFROLE_COOLDOWN_CLEANUP_BATCH_SIZE);
+
+        let selfrole_archive_retention_days = env::var("SELFROLE_ARCHIVE_RETENTION_DAYS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|n| *n >= 0)
+            .unwrap_or(DEFAULT_SELFROLE_ARCHIVE_RETENTION_DAYS);
+
+        let mediaonly_stats_retention_days = env::var("MEDIAONLY_STATS_RETENTION_DAYS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|n| *n >= 0)
+            .unwrap_or(DEFAULT_MEDIAONLY_STATS_RETENTION_DAYS);
+
+        let mediaonly_max_channels = env::var("MEDIAONLY_MAX_CHANNELS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_MEDIAONLY_MAX_CHANNELS);
+
         Ok(Config {
             discord: DiscordConfig {
                 token: discord_token,
@@ -305,6 +408,8 @@ impl Config {
             web: WebConfig {
                 api_base,
                 bind_addr,
+                max_concurrent_requests: web_max_concurrent_requests,
+                request_timeout_secs: web_request_timeout_secs,
                 oauth: OAuthConfig {
                     client_id: oauth_client_id,
                     client_secret: oauth_client_secret,
@@ -333,10 +438,20 @@ impl Config {
                 allowed_users: llm_allowed_users,
                 dm_allowed_users: llm_dm_allowed_users,
                 no_cooldown_users: llm_no_cooldown_users,
+                retry_cooldown_secs: llm_retry_cooldown_secs,
+                guild_monthly_token_budget: llm_guild_monthly_token_budget,
+                usage_show_top_users: llm_usage_show_top_users,
+                usage_retention_days: llm_usage_retention_days,
+                include_context: llm_include_context,
             },
             github_token,
             scheduler_interval,
             default_timezone,
+            selfrole_max_panels_per_channel,
+            selfrole_cooldown_cleanup_batch_size,
+            selfrole_archive_retention_days,
+            mediaonly_stats_retention_days,
+            mediaonly_max_channels,
         })
     }
 
@@ -350,6 +465,8 @@ impl Config {
             web: WebConfig {
                 api_base: DEFAULT_API_BASE.to_string(),
                 bind_addr: "127.0.0.1:8080".to_string(),
+                max_concurrent_requests: DEFAULT_WEB_MAX_CONCURRENT_REQUESTS,
+                request_timeout_secs: DEFAULT_WEB_REQUEST_TIMEOUT_SECS,
                 oauth: OAuthConfig {
                     client_id: "12345".to_string(),
                     client_secret: "test_client_secret".to_string(),
@@ -385,10 +502,20 @@ impl Config {
                 allowed_users: vec![],
                 dm_allowed_users: vec![],
                 no_cooldown_users: vec![],
+                retry_cooldown_secs: LLM_COOLDOWN_SECS,
+                guild_monthly_token_budget: None,
+                usage_show_top_users: true,
+                usage_retention_days: DEFAULT_LLM_USAGE_RETENTION_DAYS,
+                include_context: false,
             },
             github_token: None,
             scheduler_interval: 60,
             default_timezone: "UTC".to_string(),
+            selfrole_max_panels_per_channel: DEFAULT_SELFROLE_MAX_PANELS_PER_CHANNEL,
+            selfrole_cooldown_cleanup_batch_size: DEFAULT_SELFROLE_COOLDOWN_CLEANUP_BATCH_SIZE,
+            selfrole_archive_retention_days: DEFAULT_SELFROLE_ARCHIVE_RETENTION_DAYS,
+            mediaonly_stats_retention_days: DEFAULT_MEDIAONLY_STATS_RETENTION_DAYS,
+            mediaonly_max_channels: DEFAULT_MEDIAONLY_MAX_CHANNELS,
         }
     }
 }
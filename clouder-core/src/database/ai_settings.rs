@@ -0,0 +1,69 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+/// Per-guild options for AI replies.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct GuildAiSettings {
+    pub guild_id: String,
+    /// Comma-separated channel ids the bot answers in; empty means every channel.
+    pub allowed_channel_ids: String,
+    /// Minimum seconds between replies in one channel; 0 turns spacing off.
+    pub channel_cooldown_secs: i64,
+}
+
+impl GuildAiSettings {
+    pub async fn get(pool: &SqlitePool, guild_id: &str) -> Result<Option<Self>> {
+        Ok(sqlx::query_as::<_, Self>(
+            "SELECT guild_id, allowed_channel_ids, channel_cooldown_secs FROM guild_ai_settings WHERE guild_id = ?",
+        )
+        .bind(guild_id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn get_or_default(pool: &SqlitePool, guild_id: &str) -> Result<Self> {
+        Ok(Self::get(pool, guild_id).await?.unwrap_or_else(|| Self {
+            guild_id: guild_id.to_string(),
+            ..Default::default()
+        }))
+    }
+
+    pub async fn upsert(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO guild_ai_settings (guild_id, allowed_channel_ids, channel_cooldown_secs)
+            VALUES (?, ?, ?)
+            ON CONFLICT (guild_id) DO UPDATE SET
+                allowed_channel_ids = excluded.allowed_channel_ids,
+                channel_cooldown_secs = excluded.channel_cooldown_secs,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(&self.guild_id)
+        .bind(&self.allowed_channel_ids)
+        .bind(self.channel_cooldown_secs)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub fn channel_list(&self) -> Vec<String> {
+        self.allowed_channel_ids
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn set_channel_list(&mut self, channel_ids: &[String]) {
+        self.allowed_channel_ids = channel_ids.join(",");
+    }
+
+    /// Whether the bot may answer in `channel_id`. An empty list allows every channel.
+    pub fn allows_channel(&self, channel_id: &str) -> bool {
+        let allowed = self.channel_list();
+        allowed.is_empty() || allowed.iter().any(|id| id == channel_id)
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct AiUserUsage {
+    pub user_id: String,
+    pub requests: i64,
+    pub tokens: i64,
+}
+
+/// Aggregated LLM usage for one guild over a period.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AiUsageSummary {
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub top_users: Vec<AiUserUsage>,
+}
+
+impl AiUsageSummary {
+    pub fn total_tokens(&self) -> i64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+/// One logged LLM request, as stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct AiUsageRecord {
+    pub guild_id: String,
+    pub user_id: String,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+fn sqlite_timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Midnight UTC on the first day of the month containing `now`; budgets reset here.
+pub fn period_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+pub async fn record(
+    pool: &SqlitePool,
+    guild_id: &str,
+    user_id: &str,
+    prompt_tokens: u32,
+    completion_tokens: u32,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO ai_usage (guild_id, user_id, prompt_tokens, completion_tokens) VALUES (?, ?, ?, ?)",
+    )
+    .bind(guild_id)
+    .bind(user_id)
+    .bind(prompt_tokens as i64)
+    .bind(completion_tokens as i64)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Tokens the guild has used since `since`, for budget checks.
+pub async fn tokens_since(pool: &SqlitePool, guild_id: &str, since: DateTime<Utc>) -> Result<i64> {
+    Ok(sqlx::query_scalar(
+        "SELECT COALESCE(SUM(prompt_tokens + completion_tokens), 0) FROM ai_usage WHERE guild_id = ? AND created_at >= ?",
+    )
+    .bind(guild_id)
+    .bind(sqlite_timestamp(since))
+    .fetch_one(pool)
+    .await?)
+}
+
+/// Totals since `since` plus the `top_n` users by tokens spent (ties broken by user id).
+pub async fn summary(
+    pool: &SqlitePool,
+    guild_id: &str,
+    since: DateTime<Utc>,
+    top_n: i64,
+) -> Result<AiUsageSummary> {
+    let since = sqlite_timestamp(since);
+
+    let (requests, prompt_tokens, completion_tokens): (i64, i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), COALESCE(SUM(prompt_tokens), 0), COALESCE(SUM(completion_tokens), 0)
+        FROM ai_usage
+        WHERE guild_id = ? AND created_at >= ?
+        "#,
+    )
+    .bind(guild_id)
+    .bind(&since)
+    .fetch_one(pool)
+    .await?;
+
+    let top_users = if top_n > 0 {
+        sqlx::query_as::<_, AiUserUsage>(
+            r#"
+            SELECT user_id, COUNT(*) AS requests, SUM(prompt_tokens + completion_tokens) AS tokens
+            FROM ai_usage
+            WHERE guild_id = ? AND created_at >= ?
+            GROUP BY user_id
+            ORDER BY tokens DESC, user_id
+            LIMIT ?
+            "#,
+        )
+        .bind(guild_id)
+        .bind(&since)
+        .bind(top_n)
+        .fetch_all(pool)
+        .await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(AiUsageSummary {
+        requests,
+        prompt_tokens,
+        completion_tokens,
+        top_users,
+    })
+}
+
+pub async fn get_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<Vec<AiUsageRecord>> {
+    Ok(sqlx::query_as(
+        "SELECT guild_id, user_id, prompt_tokens, completion_tokens, created_at FROM ai_usage WHERE guild_id = ? ORDER BY id",
+    )
+    .bind(guild_id)
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn get_by_user(pool: &SqlitePool, user_id: &str) -> Result<Vec<AiUsageRecord>> {
+    Ok(sqlx::query_as(
+        "SELECT guild_id, user_id, prompt_tokens, completion_tokens, created_at FROM ai_usage WHERE user_id = ? ORDER BY id",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Drops usage logged before `before`; returns how many rows went.
+pub async fn purge_before(pool: &SqlitePool, before: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM ai_usage WHERE created_at < ?")
+        .bind(sqlite_timestamp(before))
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Cutoff for the retention purge: `retention_days` back from `now`, but never
+/// into the current budget month so `tokens_since` stays accurate.
+pub fn retention_cutoff(now: DateTime<Utc>, retention_days: i64) -> DateTime<Utc> {
+    (now - chrono::Duration::days(retention_days)).min(period_start(now))
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CommandAlias {
+    pub guild_id: String,
+    pub alias: String,
+    pub command: String,
+}
+
+impl CommandAlias {
+    pub async fn get_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(
+            "SELECT guild_id, alias, command FROM command_aliases WHERE guild_id = ? ORDER BY alias",
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Returns the command an alias points at in this guild, if any.
+    pub async fn resolve(pool: &SqlitePool, guild_id: &str, alias: &str) -> Result<Option<String>> {
+        Ok(sqlx::query_scalar(
+            "SELECT command FROM command_aliases WHERE guild_id = ? AND alias = ?",
+        )
+        .bind(guild_id)
+        .bind(alias.to_lowercase())
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn count_for_guild(pool: &SqlitePool, guild_id: &str) -> Result<i64> {
+        Ok(
+            sqlx::query_scalar("SELECT COUNT(*) FROM command_aliases WHERE guild_id = ?")
+                .bind(guild_id)
+                .fetch_one(pool)
+                .await?,
+        )
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        guild_id: &str,
+        alias: &str,
+        command: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO command_aliases (guild_id, alias, command)
+            VALUES (?, ?, ?)
+            ON CONFLICT (guild_id, alias) DO UPDATE SET command = excluded.command
+            "#,
+        )
+        .bind(guild_id)
+        .bind(alias)
+        .bind(command)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, guild_id: &str, alias: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM command_aliases WHERE guild_id = ? AND alias = ?")
+            .bind(guild_id)
+            .bind(alias.to_lowercase())
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
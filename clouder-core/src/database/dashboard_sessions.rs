@@ -62,6 +62,16 @@ impl DashboardSession {
         Ok(())
     }
 
+    pub async fn count_active_for_user(db: &SqlitePool, user_id: &str) -> Result<i64> {
+        let count = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM dashboard_sessions WHERE user_id = ? AND expires_at > unixepoch()",
+        )
+        .bind(user_id)
+        .fetch_one(db)
+        .await?;
+        Ok(count)
+    }
+
     pub async fn delete_expired(db: &SqlitePool) -> Result<u64> {
         let result = sqlx::query("DELETE FROM dashboard_sessions WHERE expires_at <= unixepoch()")
             .execute(db)
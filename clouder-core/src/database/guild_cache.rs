@@ -1,11 +1,12 @@
 use anyhow::Result;
+use serde::Serialize;
 use sqlx::SqlitePool;
 
 /// 1 hour. Long enough to avoid hammering Discord on dashboard navigation,
 /// short enough that revoked permissions stop letting writes through quickly.
 pub const GUILD_CACHE_TTL_SECONDS: i64 = 3600;
 
-#[derive(Debug, sqlx::FromRow)]
+#[derive(Debug, Serialize, sqlx::FromRow)]
 pub struct CachedGuild {
     pub user_id: String,
     pub guild_id: String,
@@ -1,10 +1,11 @@
 use anyhow::Result;
+use serde::Serialize;
 use sqlx::SqlitePool;
 
 pub const DEFAULT_TIMEZONE: &str = "UTC";
 pub const DEFAULT_COMMAND_PREFIX: &str = "!";
 
-#[derive(Debug, Clone, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
 pub struct GuildConfig {
     pub guild_id: String,
     pub timezone: String,
@@ -13,13 +14,18 @@ pub struct GuildConfig {
 }
 
 impl GuildConfig {
-    pub async fn get_or_default(db: &SqlitePool, guild_id: &str) -> Result<Self> {
-        let config = sqlx::query_as::<_, Self>(
+    /// The stored row, without falling back to defaults.
+    pub async fn get(db: &SqlitePool, guild_id: &str) -> Result<Option<Self>> {
+        Ok(sqlx::query_as::<_, Self>(
             "SELECT guild_id, timezone, command_prefix, embed_color FROM guild_configs WHERE guild_id = ?",
         )
         .bind(guild_id)
         .fetch_optional(db)
-        .await?;
+        .await?)
+    }
+
+    pub async fn get_or_default(db: &SqlitePool, guild_id: &str) -> Result<Self> {
+        let config = Self::get(db, guild_id).await?;
 
         Ok(config.unwrap_or(Self {
             guild_id: guild_id.to_string(),
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+/// Rows hanging off a guild-keyed parent: `(table, fk column, parent table)`.
+/// Deleted explicitly rather than trusting `ON DELETE CASCADE` to be enforced.
+const CHILD_TABLES: &[(&str, &str, &str)] = &[
+    ("selfrole_roles", "config_id", "selfrole_configs"),
+    ("reminder_ping_roles", "config_id", "reminder_configs"),
+    ("reminder_subscriptions", "config_id", "reminder_configs"),
+    ("reminder_logs", "config_id", "reminder_configs"),
+    (
+        "custom_reminder_ping_roles",
+        "reminder_id",
+        "custom_reminders",
+    ),
+    (
+        "custom_reminder_subscriptions",
+        "reminder_id",
+        "custom_reminders",
+    ),
+    ("custom_reminder_logs", "reminder_id", "custom_reminders"),
+];
+
+/// Every table keyed by `guild_id`. `user_guild_cache` is left alone: it
+/// belongs to dashboard users, not the guild.
+const GUILD_TABLES: &[&str] = &[
+    "selfrole_configs",
+    "selfrole_cooldowns",
+    "selfrole_labels",
+    "selfrole_emoji_policies",
+    "selfrole_settings",
+    "reminder_configs",
+    "custom_reminders",
+    "guild_configs",
+    "scheduled_messages",
+    "welcome_goodbye_configs",
+    "welcome_goodbye_variants",
+    "pending_welcomes",
+    "mediaonly_configs",
+    "mediaonly_stats",
+    "uwufy_toggles",
+    "command_aliases",
+    "ai_usage",
+    "guild_ai_settings",
+];
+
+/// Deletes everything stored for a guild in one transaction.
+/// Returns the number of rows removed.
+pub async fn delete_all(pool: &SqlitePool, guild_id: &str) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+    let mut removed = 0;
+    for (table, fk, parent) in CHILD_TABLES {
+        let sql = format!(
+            "DELETE FROM {} WHERE {} IN (SELECT id FROM {} WHERE guild_id = ?)",
+            table, fk, parent
+        );
+        removed += sqlx::query(&sql)
+            .bind(guild_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+    for table in GUILD_TABLES {
+        removed += sqlx::query(&format!("DELETE FROM {} WHERE guild_id = ?", table))
+            .bind(guild_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+    tx.commit().await?;
+    Ok(removed)
+}
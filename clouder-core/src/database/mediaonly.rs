@@ -1,6 +1,7 @@
 use crate::utils::parse_sqlite_datetime;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, SqliteConnection, SqlitePool};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaOnlyConfig {
@@ -126,6 +127,13 @@ impl MediaOnlyConfig {
         Ok(configs)
     }
 
+    pub async fn count_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM mediaonly_configs WHERE guild_id = ?")
+            .bind(guild_id)
+            .fetch_one(pool)
+            .await
+    }
+
     pub async fn upsert_with_config(
         pool: &SqlitePool,
         guild_id: &str,
@@ -134,6 +142,30 @@ impl MediaOnlyConfig {
         allow_attachments: bool,
         allow_gifs: bool,
         allow_stickers: bool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        Self::upsert_with_config_in(
+            &mut conn,
+            guild_id,
+            channel_id,
+            allow_links,
+            allow_attachments,
+            allow_gifs,
+            allow_stickers,
+        )
+        .await
+    }
+
+    /// [`Self::upsert_with_config`] on a connection the caller holds, so
+    /// several channels can be saved in one transaction.
+    pub async fn upsert_with_config_in(
+        conn: &mut SqliteConnection,
+        guild_id: &str,
+        channel_id: &str,
+        allow_links: bool,
+        allow_attachments: bool,
+        allow_gifs: bool,
+        allow_stickers: bool,
     ) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
@@ -153,7 +185,7 @@ impl MediaOnlyConfig {
         .bind(allow_attachments)
         .bind(allow_gifs)
         .bind(allow_stickers)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
         Ok(())
@@ -178,3 +210,109 @@ impl MediaOnlyConfig {
         Ok(())
     }
 }
+
+/// Deletion totals for one media-only channel over a stats window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct MediaOnlyChannelStats {
+    pub channel_id: String,
+    pub deletions: i64,
+    pub unique_offenders: i64,
+    pub last_day: String,
+}
+
+/// One day's deletions for one user in one channel, as stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct MediaOnlyStatRow {
+    pub guild_id: String,
+    pub channel_id: String,
+    pub user_id: String,
+    pub day: String,
+    pub deletions: i64,
+}
+
+pub struct MediaOnlyStats;
+
+impl MediaOnlyStats {
+    /// Counts one deleted message against `user_id` for `day`.
+    pub async fn record_deletion(
+        pool: &SqlitePool,
+        guild_id: &str,
+        channel_id: &str,
+        user_id: &str,
+        day: NaiveDate,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO mediaonly_stats (guild_id, channel_id, user_id, day, deletions)
+            VALUES (?, ?, ?, ?, 1)
+            ON CONFLICT(guild_id, channel_id, day, user_id) DO UPDATE SET
+                deletions = deletions + 1
+            "#,
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(user_id)
+        .bind(day.format("%Y-%m-%d").to_string())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Per-channel totals from `since` (inclusive), busiest channel first.
+    pub async fn get_by_guild(
+        pool: &SqlitePool,
+        guild_id: &str,
+        since: NaiveDate,
+    ) -> Result<Vec<MediaOnlyChannelStats>, sqlx::Error> {
+        sqlx::query_as::<_, MediaOnlyChannelStats>(
+            r#"
+            SELECT channel_id, SUM(deletions) AS deletions,
+                   COUNT(DISTINCT user_id) AS unique_offenders, MAX(day) AS last_day
+            FROM mediaonly_stats
+            WHERE guild_id = ? AND day >= ?
+            GROUP BY channel_id
+            ORDER BY deletions DESC, channel_id
+            "#,
+        )
+        .bind(guild_id)
+        .bind(since.format("%Y-%m-%d").to_string())
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Every stored row for a guild, for data exports.
+    pub async fn rows_by_guild(
+        pool: &SqlitePool,
+        guild_id: &str,
+    ) -> Result<Vec<MediaOnlyStatRow>, sqlx::Error> {
+        sqlx::query_as::<_, MediaOnlyStatRow>(
+            "SELECT * FROM mediaonly_stats WHERE guild_id = ? ORDER BY day, channel_id, user_id",
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Every stored row counting against a user, for data exports.
+    pub async fn rows_by_user(
+        pool: &SqlitePool,
+        user_id: &str,
+    ) -> Result<Vec<MediaOnlyStatRow>, sqlx::Error> {
+        sqlx::query_as::<_, MediaOnlyStatRow>(
+            "SELECT * FROM mediaonly_stats WHERE user_id = ? ORDER BY day, guild_id, channel_id",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Drops rows for days before `before`; returns how many went.
+    pub async fn purge_before(pool: &SqlitePool, before: NaiveDate) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM mediaonly_stats WHERE day < ?")
+            .bind(before.format("%Y-%m-%d").to_string())
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -4,13 +4,19 @@ use std::{path::Path, str::FromStr};
 
 use tracing::info;
 
+pub mod ai_settings;
+pub mod ai_usage;
+pub mod command_aliases;
 pub mod dashboard_sessions;
 pub mod dashboard_users;
 pub mod guild_cache;
 pub mod guild_configs;
+pub mod guild_data;
 pub mod mediaonly;
 pub mod reminders;
+pub mod scheduled_messages;
 pub mod selfroles;
+pub mod user_data;
 pub mod uwufy;
 pub mod welcome_goodbye;
 
@@ -62,6 +68,69 @@ async fn run_migrations(pool: &SqlitePool) -> Result<()> {
             13,
             include_str!("../../migrations/013_dashboard_users_api_key_ciphertext.sql"),
         ),
+        Migration::new(14, include_str!("../../migrations/014_command_aliases.sql")),
+        Migration::new(
+            15,
+            include_str!("../../migrations/015_scheduled_messages.sql"),
+        ),
+        Migration::new(
+            16,
+            include_str!("../../migrations/016_selfrole_emoji_policy.sql"),
+        ),
+        Migration::new(17, include_str!("../../migrations/017_ai_usage.sql")),
+        Migration::new(18, include_str!("../../migrations/018_mediaonly_stats.sql")),
+        Migration::new(
+            19,
+            include_str!("../../migrations/019_selfrole_attached.sql"),
+        ),
+        Migration::new(
+            20,
+            include_str!("../../migrations/020_welcome_rotation.sql"),
+        ),
+        Migration::new(
+            21,
+            include_str!("../../migrations/021_selfrole_settings.sql"),
+        ),
+        Migration::new(
+            22,
+            include_str!("../../migrations/022_welcome_after_screening.sql"),
+        ),
+        Migration::new(
+            23,
+            include_str!("../../migrations/023_name_placeholder_source.sql"),
+        ),
+        Migration::new(
+            24,
+            include_str!("../../migrations/024_guild_ai_settings.sql"),
+        ),
+        Migration::new(
+            25,
+            include_str!("../../migrations/025_selfrole_show_cooldown.sql"),
+        ),
+        Migration::new(
+            26,
+            include_str!("../../migrations/026_ai_channel_cooldown.sql"),
+        ),
+        Migration::new(
+            27,
+            include_str!("../../migrations/027_selfrole_archive.sql"),
+        ),
+        Migration::new(
+            28,
+            include_str!("../../migrations/028_selfrole_feedback_mode.sql"),
+        ),
+        Migration::new(
+            29,
+            include_str!("../../migrations/029_selfrole_crosspost.sql"),
+        ),
+        Migration::new(
+            30,
+            include_str!("../../migrations/030_selfrole_cooldowns.sql"),
+        ),
+        Migration::new(
+            31,
+            include_str!("../../migrations/031_pending_welcomes.sql"),
+        ),
     ];
 
     create_migration_ledger(pool).await?;
@@ -353,6 +422,7 @@ fn split_sql_statements(sql: &str) -> Vec<String> {
 
 #[cfg(test)]
 mod tests {
+    use super::selfroles::{ACTIVE_CONFIGS_BY_GUILD, DELETE_EXPIRED_COOLDOWNS, ROLES_BY_CONFIG};
     use super::{run_migrations, split_sql_statements};
     use sqlx::SqlitePool;
 
@@ -367,7 +437,40 @@ mod tests {
             .fetch_one(&pool)
             .await
             .unwrap();
-        assert_eq!(count, 13);
+        assert_eq!(count, 31);
+    }
+
+    #[tokio::test]
+    async fn selfrole_hot_queries_use_indexes() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        for (query, index) in [
+            (ACTIVE_CONFIGS_BY_GUILD, "selfrole_configs_guild_id"),
+            (ROLES_BY_CONFIG, "selfrole_roles_config"),
+            (DELETE_EXPIRED_COOLDOWNS, "selfrole_cooldowns_expires_at"),
+        ] {
+            // each takes one parameter; its value doesn't change the plan
+            let plan: Vec<String> = sqlx::query_as::<_, (i64, i64, i64, String)>(&format!(
+                "EXPLAIN QUERY PLAN {}",
+                query
+            ))
+            .bind("1")
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, _, _, detail)| detail)
+            .collect();
+            assert!(
+                plan.iter()
+                    .any(|d| d.contains("USING") && d.contains(index)),
+                "{} doesn't use {}: {:?}",
+                query,
+                index,
+                plan
+            );
+        }
     }
 
     #[tokio::test]
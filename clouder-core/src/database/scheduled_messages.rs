@@ -0,0 +1,125 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+/// A channel post that fires once (`cron_spec` is `None`) or on a cron schedule.
+/// `next_run_at` is a unix timestamp; a fired one-off is disabled with it cleared.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ScheduledMessage {
+    pub id: i64,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub content: String,
+    pub cron_spec: Option<String>,
+    pub timezone: String,
+    pub next_run_at: Option<i64>,
+    pub enabled: bool,
+    pub created_by: String,
+}
+
+const COLUMNS: &str =
+    "id, guild_id, channel_id, content, cron_spec, timezone, next_run_at, enabled, created_by";
+
+impl ScheduledMessage {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool: &SqlitePool,
+        guild_id: &str,
+        channel_id: &str,
+        content: &str,
+        cron_spec: Option<&str>,
+        timezone: &str,
+        next_run_at: i64,
+        created_by: &str,
+    ) -> Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO scheduled_messages
+                (guild_id, channel_id, content, cron_spec, timezone, next_run_at, created_by)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(content)
+        .bind(cron_spec)
+        .bind(timezone)
+        .bind(next_run_at)
+        .bind(created_by)
+        .execute(pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn get_by_id(pool: &SqlitePool, id: i64) -> Result<Option<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM scheduled_messages WHERE id = ?",
+            COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn get_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM scheduled_messages WHERE guild_id = ? ORDER BY id",
+            COLUMNS
+        ))
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn get_by_creator(pool: &SqlitePool, user_id: &str) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM scheduled_messages WHERE created_by = ? ORDER BY id",
+            COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn count_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<i64> {
+        Ok(
+            sqlx::query_scalar("SELECT COUNT(*) FROM scheduled_messages WHERE guild_id = ?")
+                .bind(guild_id)
+                .fetch_one(pool)
+                .await?,
+        )
+    }
+
+    pub async fn get_due(pool: &SqlitePool, now: i64) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM scheduled_messages
+             WHERE enabled = 1 AND next_run_at IS NOT NULL AND next_run_at <= ?
+             ORDER BY next_run_at",
+            COLUMNS
+        ))
+        .bind(now)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Moves the message to its next run, or disables it when there is none.
+    pub async fn set_next_run(pool: &SqlitePool, id: i64, next_run_at: Option<i64>) -> Result<()> {
+        sqlx::query(
+            "UPDATE scheduled_messages
+             SET next_run_at = ?, enabled = ?, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?",
+        )
+        .bind(next_run_at)
+        .bind(next_run_at.is_some())
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM scheduled_messages WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -3,6 +3,31 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 
+// Hot statements, shared with the query-plan test in `database::tests` so it
+// checks what actually runs.
+pub(crate) const ACTIVE_CONFIGS_BY_GUILD: &str = "SELECT * FROM selfrole_configs WHERE guild_id = ? AND archived_at IS NULL ORDER BY created_at DESC";
+pub(crate) const ROLES_BY_CONFIG: &str = "SELECT * FROM selfrole_roles WHERE config_id = ?";
+pub(crate) const DELETE_EXPIRED_COOLDOWNS: &str =
+    "DELETE FROM selfrole_cooldowns WHERE expires_at <= ?";
+
+/// `message_id` is already recorded for a different panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageIdTaken {
+    pub config_id: i64,
+}
+
+impl std::fmt::Display for MessageIdTaken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "message already belongs to self-role config {}",
+            self.config_id
+        )
+    }
+}
+
+impl std::error::Error for MessageIdTaken {}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct SelfRoleConfig {
     pub id: i64,
@@ -14,6 +39,10 @@ pub struct SelfRoleConfig {
     pub selection_type: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Buttons were added to an existing message rather than a panel the bot posted.
+    pub attached: bool,
+    /// Seconds a member waits between clicks on the same button; 0 for none.
+    pub cooldown_secs: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
@@ -24,7 +53,7 @@ pub struct SelfRoleRole {
     pub emoji: String,
 }
 
-#[derive(Debug, sqlx::FromRow)]
+#[derive(Debug, Serialize, sqlx::FromRow)]
 pub struct SelfRoleCooldown {
     pub user_id: String,
     pub role_id: String,
@@ -72,8 +101,20 @@ impl SelfRoleConfig {
         Ok(config)
     }
 
+    /// Records the panel's message. Setting an id this config already holds is
+    /// a no-op, so retries are safe; an id held by another config fails with
+    /// [`MessageIdTaken`], which callers can `downcast_ref` for.
     pub async fn update_message_id(&mut self, pool: &SqlitePool, message_id: &str) -> Result<()> {
-        sqlx::query(
+        match self.message_id_owner(pool, message_id).await? {
+            Some(owner) if owner == self.id => {
+                self.message_id = Some(message_id.to_string());
+                return Ok(());
+            }
+            Some(owner) => return Err(MessageIdTaken { config_id: owner }.into()),
+            None => {}
+        }
+
+        let result = sqlx::query(
             r#"
             UPDATE selfrole_configs
             SET message_id = ?, updated_at = CURRENT_TIMESTAMP
@@ -83,9 +124,52 @@ impl SelfRoleConfig {
         .bind(message_id)
         .bind(self.id)
         .execute(pool)
+        .await;
+
+        match result {
+            Ok(_) => {}
+            // lost a race with another writer between the check and the update
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                let owner = self.message_id_owner(pool, message_id).await?;
+                if owner != Some(self.id) {
+                    return Err(MessageIdTaken {
+                        config_id: owner.unwrap_or_default(),
+                    }
+                    .into());
+                }
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        self.message_id = Some(message_id.to_string());
+        Ok(())
+    }
+
+    async fn message_id_owner(&self, pool: &SqlitePool, message_id: &str) -> Result<Option<i64>> {
+        Ok(
+            sqlx::query_scalar("SELECT id FROM selfrole_configs WHERE message_id = ?")
+                .bind(message_id)
+                .fetch_optional(pool)
+                .await?,
+        )
+    }
+
+    /// Records the existing message this config's buttons were attached to.
+    pub async fn mark_attached(&mut self, pool: &SqlitePool, message_id: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE selfrole_configs
+            SET message_id = ?, attached = TRUE, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(message_id)
+        .bind(self.id)
+        .execute(pool)
         .await?;
 
         self.message_id = Some(message_id.to_string());
+        self.attached = true;
         Ok(())
     }
 
@@ -107,6 +191,42 @@ impl SelfRoleConfig {
         Ok(())
     }
 
+    /// Points the config at a panel re-posted in another channel.
+    pub async fn move_to(
+        &mut self,
+        pool: &SqlitePool,
+        channel_id: &str,
+        message_id: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE selfrole_configs
+            SET channel_id = ?, message_id = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(channel_id)
+        .bind(message_id)
+        .bind(self.id)
+        .execute(pool)
+        .await?;
+
+        self.channel_id = channel_id.to_string();
+        self.message_id = Some(message_id.to_string());
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    pub async fn set_cooldown(&mut self, pool: &SqlitePool, cooldown_secs: i64) -> Result<()> {
+        sqlx::query("UPDATE selfrole_configs SET cooldown_secs = ? WHERE id = ?")
+            .bind(cooldown_secs)
+            .bind(self.id)
+            .execute(pool)
+            .await?;
+        self.cooldown_secs = cooldown_secs;
+        Ok(())
+    }
+
     pub async fn get_by_message_id(pool: &SqlitePool, message_id: &str) -> Result<Option<Self>> {
         let config =
             sqlx::query_as::<_, Self>("SELECT * FROM selfrole_configs WHERE message_id = ?")
@@ -118,16 +238,28 @@ impl SelfRoleConfig {
     }
 
     pub async fn get_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<Vec<Self>> {
-        let configs = sqlx::query_as::<_, Self>(
-            "SELECT * FROM selfrole_configs WHERE guild_id = ? ORDER BY created_at DESC",
-        )
-        .bind(guild_id)
-        .fetch_all(pool)
-        .await?;
+        let configs = sqlx::query_as::<_, Self>(ACTIVE_CONFIGS_BY_GUILD)
+            .bind(guild_id)
+            .fetch_all(pool)
+            .await?;
 
         Ok(configs)
     }
 
+    pub async fn count_by_channel(
+        pool: &SqlitePool,
+        guild_id: &str,
+        channel_id: &str,
+    ) -> Result<i64> {
+        Ok(sqlx::query_scalar(
+            "SELECT COUNT(*) FROM selfrole_configs WHERE guild_id = ? AND channel_id = ? AND archived_at IS NULL",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .fetch_one(pool)
+        .await?)
+    }
+
     pub async fn delete(&self, pool: &SqlitePool) -> Result<()> {
         sqlx::query("DELETE FROM selfrole_configs WHERE id = ?")
             .bind(self.id)
@@ -166,12 +298,56 @@ impl SelfRoleConfig {
         Ok(())
     }
 
-    pub async fn get_roles(&self, pool: &SqlitePool) -> Result<Vec<SelfRoleRole>> {
-        let roles =
-            sqlx::query_as::<_, SelfRoleRole>("SELECT * FROM selfrole_roles WHERE config_id = ?")
+    /// Updates the panel text and swaps its roles for `roles` (`(role_id, emoji)`
+    /// pairs) in one transaction, so a failed insert leaves the old panel intact.
+    pub async fn replace_contents(
+        &mut self,
+        pool: &SqlitePool,
+        title: &str,
+        body: &str,
+        selection_type: &str,
+        roles: &[(String, String)],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE selfrole_configs
+            SET title = ?, body = ?, selection_type = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(title)
+        .bind(body)
+        .bind(selection_type)
+        .bind(self.id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM selfrole_roles WHERE config_id = ?")
+            .bind(self.id)
+            .execute(&mut *tx)
+            .await?;
+        for (role_id, emoji) in roles {
+            sqlx::query("INSERT INTO selfrole_roles (config_id, role_id, emoji) VALUES (?, ?, ?)")
                 .bind(self.id)
-                .fetch_all(pool)
+                .bind(role_id)
+                .bind(emoji)
+                .execute(&mut *tx)
                 .await?;
+        }
+        tx.commit().await?;
+
+        self.title = title.to_string();
+        self.body = body.to_string();
+        self.selection_type = selection_type.to_string();
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    pub async fn get_roles(&self, pool: &SqlitePool) -> Result<Vec<SelfRoleRole>> {
+        let roles = sqlx::query_as::<_, SelfRoleRole>(ROLES_BY_CONFIG)
+            .bind(self.id)
+            .fetch_all(pool)
+            .await?;
 
         Ok(roles)
     }
@@ -186,14 +362,76 @@ impl SelfRoleConfig {
     }
 
     pub async fn get_by_guild_id(pool: &SqlitePool, guild_id: u64) -> Result<Vec<Self>> {
-        let configs = sqlx::query_as::<_, Self>(
-            "SELECT * FROM selfrole_configs WHERE guild_id = ? ORDER BY created_at DESC",
+        let configs = sqlx::query_as::<_, Self>(ACTIVE_CONFIGS_BY_GUILD)
+            .bind(guild_id.to_string())
+            .fetch_all(pool)
+            .await?;
+
+        Ok(configs)
+    }
+
+    /// Hides a guild's panels when the bot leaves it. They stay out of every
+    /// listing until [`Self::restore_guild`] or [`Self::purge_archived_before`].
+    pub async fn archive_guild(pool: &SqlitePool, guild_id: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE selfrole_configs SET archived_at = ? WHERE guild_id = ? AND archived_at IS NULL",
         )
-        .bind(guild_id.to_string())
-        .fetch_all(pool)
+        .bind(Utc::now())
+        .bind(guild_id)
+        .execute(pool)
         .await?;
 
-        Ok(configs)
+        Ok(result.rows_affected())
+    }
+
+    /// Brings back a guild's archived panels when the bot rejoins it.
+    pub async fn restore_guild(pool: &SqlitePool, guild_id: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE selfrole_configs SET archived_at = NULL WHERE guild_id = ? AND archived_at IS NOT NULL",
+        )
+        .bind(guild_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Permanently deletes panels archived before `before`, along with their roles.
+    pub async fn purge_archived_before(pool: &SqlitePool, before: DateTime<Utc>) -> Result<u64> {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM selfrole_roles WHERE config_id IN \
+             (SELECT id FROM selfrole_configs WHERE archived_at < ?)",
+        )
+        .bind(before)
+        .execute(&mut *tx)
+        .await?;
+        let result = sqlx::query("DELETE FROM selfrole_configs WHERE archived_at < ?")
+            .bind(before)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes every panel in a guild, archived or not, along with their roles.
+    pub async fn delete_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<u64> {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM selfrole_roles WHERE config_id IN \
+             (SELECT id FROM selfrole_configs WHERE guild_id = ?)",
+        )
+        .bind(guild_id)
+        .execute(&mut *tx)
+        .await?;
+        let result = sqlx::query("DELETE FROM selfrole_configs WHERE guild_id = ?")
+            .bind(guild_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
     }
 
     pub async fn get_by_message_id_u64(pool: &SqlitePool, message_id: u64) -> Result<Option<Self>> {
@@ -242,8 +480,26 @@ impl SelfRoleRole {
 
         Ok(())
     }
+
+    /// Deletes role rows whose config no longer exists. The FK cascade covers
+    /// this when `foreign_keys` is on; this catches rows left behind when it
+    /// wasn't. Returns how many rows were removed.
+    pub async fn delete_orphaned(pool: &SqlitePool) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM selfrole_roles WHERE config_id NOT IN (SELECT id FROM selfrole_configs)",
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
 }
 
+/// Click cooldown for new panels in guilds that haven't set their own default.
+pub const SELFROLE_COOLDOWN_SECS: i64 = 5;
+/// Longest click cooldown a panel or guild default may use.
+pub const MAX_SELFROLE_COOLDOWN_SECS: i64 = 3600;
+
 impl SelfRoleCooldown {
     pub async fn create(
         pool: &SqlitePool,
@@ -292,15 +548,92 @@ impl SelfRoleCooldown {
         Ok(cooldown.is_some())
     }
 
-    pub async fn cleanup_expired(pool: &SqlitePool) -> Result<()> {
+    pub async fn get_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(
+            "SELECT user_id, role_id, guild_id, expires_at FROM selfrole_cooldowns WHERE guild_id = ?",
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn get_by_user(pool: &SqlitePool, user_id: &str) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(
+            "SELECT user_id, role_id, guild_id, expires_at FROM selfrole_cooldowns WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Clears a user's cooldowns, optionally only in one guild and/or for one
+    /// role, whether or not they've expired. Returns the rows removed.
+    pub async fn delete_for_user(
+        pool: &SqlitePool,
+        user_id: &str,
+        guild_id: Option<&str>,
+        role_id: Option<&str>,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM selfrole_cooldowns
+            WHERE user_id = ?1
+              AND (?2 IS NULL OR guild_id = ?2)
+              AND (?3 IS NULL OR role_id = ?3)
+            "#,
+        )
+        .bind(user_id)
+        .bind(guild_id)
+        .bind(role_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes every expired cooldown. Returns the rows removed.
+    pub async fn cleanup_expired(pool: &SqlitePool) -> Result<u64> {
         let now = Utc::now();
 
-        sqlx::query("DELETE FROM selfrole_cooldowns WHERE expires_at <= ?")
+        let result = sqlx::query(DELETE_EXPIRED_COOLDOWNS)
             .bind(now)
             .execute(pool)
             .await?;
 
-        Ok(())
+        Ok(result.rows_affected())
+    }
+
+    /// Like [`Self::cleanup_expired`], but deletes at most `batch_size` rows
+    /// per statement and yields between batches, so a large backlog doesn't
+    /// hold the write lock in one long delete. Returns the rows removed.
+    /// A `batch_size` of 0 or less means no batching.
+    pub async fn cleanup_expired_batched(pool: &SqlitePool, batch_size: i64) -> Result<u64> {
+        if batch_size <= 0 {
+            return Self::cleanup_expired(pool).await;
+        }
+        let now = Utc::now();
+        let mut removed = 0;
+
+        loop {
+            let deleted = sqlx::query(
+                r#"
+                DELETE FROM selfrole_cooldowns WHERE rowid IN (
+                    SELECT rowid FROM selfrole_cooldowns WHERE expires_at <= ? LIMIT ?
+                )
+                "#,
+            )
+            .bind(now)
+            .bind(batch_size)
+            .execute(pool)
+            .await?
+            .rows_affected();
+            removed += deleted;
+
+            if deleted < batch_size as u64 {
+                return Ok(removed);
+            }
+            tokio::task::yield_now().await;
+        }
     }
 }
 
@@ -366,3 +699,199 @@ impl SelfRoleLabel {
         Ok(labels.into_iter().map(|l| (l.role_id, l.name)).collect())
     }
 }
+
+/// Guild-wide rule for which emojis self-role buttons may use. `allow` means
+/// only the listed emojis are accepted; `deny` rejects the listed ones.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SelfRoleEmojiPolicy {
+    pub guild_id: String,
+    pub mode: String,
+    /// comma-separated; custom emojis are stored by id
+    pub emojis: String,
+}
+
+impl SelfRoleEmojiPolicy {
+    /// `<:name:id>` and `<a:name:id>` compare by id so renames don't matter.
+    pub fn normalize_emoji(emoji: &str) -> String {
+        let emoji = emoji.trim();
+        if emoji.starts_with('<')
+            && emoji.ends_with('>')
+            && let Some(id) = emoji.trim_end_matches('>').rsplit(':').next()
+        {
+            return id.to_string();
+        }
+        emoji.to_string()
+    }
+
+    pub fn emoji_list(&self) -> Vec<String> {
+        self.emojis
+            .split(',')
+            .map(Self::normalize_emoji)
+            .filter(|e| !e.is_empty())
+            .collect()
+    }
+
+    /// Roles without an emoji are always allowed.
+    pub fn allows(&self, emoji: &str) -> bool {
+        let emoji = Self::normalize_emoji(emoji);
+        if emoji.is_empty() {
+            return true;
+        }
+        let listed = self.emoji_list().contains(&emoji);
+        match self.mode.as_str() {
+            "allow" => listed,
+            _ => !listed,
+        }
+    }
+
+    pub async fn get(pool: &SqlitePool, guild_id: &str) -> Result<Option<Self>> {
+        Ok(sqlx::query_as::<_, Self>(
+            "SELECT guild_id, mode, emojis FROM selfrole_emoji_policies WHERE guild_id = ?",
+        )
+        .bind(guild_id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        guild_id: &str,
+        mode: &str,
+        emojis: &[String],
+    ) -> Result<()> {
+        let emojis = emojis
+            .iter()
+            .map(|e| Self::normalize_emoji(e))
+            .filter(|e| !e.is_empty())
+            .collect::<Vec<_>>()
+            .join(",");
+        sqlx::query(
+            r#"
+            INSERT INTO selfrole_emoji_policies (guild_id, mode, emojis)
+            VALUES (?, ?, ?)
+            ON CONFLICT (guild_id) DO UPDATE SET mode = excluded.mode, emojis = excluded.emojis, updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(guild_id)
+        .bind(mode)
+        .bind(emojis)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, guild_id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM selfrole_emoji_policies WHERE guild_id = ?")
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Guild-wide options applied to every self-role panel.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SelfRoleSettings {
+    pub guild_id: String,
+    /// Stamp the panel footer with when it was last posted or edited.
+    pub show_updated: bool,
+    /// Show the click cooldown in the panel footer.
+    pub show_cooldown: bool,
+    /// How a role change is confirmed; see [`SelfRoleFeedback`].
+    pub feedback_mode: String,
+    /// Publish panels posted in announcement channels to following servers.
+    pub crosspost: bool,
+    /// Click cooldown given to new panels that don't set their own.
+    pub default_cooldown_secs: i64,
+}
+
+impl Default for SelfRoleSettings {
+    fn default() -> Self {
+        Self {
+            guild_id: String::new(),
+            show_updated: false,
+            show_cooldown: false,
+            feedback_mode: SelfRoleFeedback::default().as_str().to_string(),
+            crosspost: false,
+            default_cooldown_secs: SELFROLE_COOLDOWN_SECS,
+        }
+    }
+}
+
+/// How a successful self-role click is confirmed to the member
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelfRoleFeedback {
+    /// An ephemeral message naming the role
+    #[default]
+    Reply,
+    /// A brief reaction on the panel, removed after a few seconds. Only
+    /// successful changes use it; errors still get a reply.
+    React,
+}
+
+impl SelfRoleFeedback {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "reply" => Some(SelfRoleFeedback::Reply),
+            "react" => Some(SelfRoleFeedback::React),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SelfRoleFeedback::Reply => "reply",
+            SelfRoleFeedback::React => "react",
+        }
+    }
+}
+
+impl SelfRoleSettings {
+    pub async fn get(pool: &SqlitePool, guild_id: &str) -> Result<Option<Self>> {
+        Ok(sqlx::query_as::<_, Self>(
+            "SELECT guild_id, show_updated, show_cooldown, feedback_mode, crosspost, default_cooldown_secs \
+             FROM selfrole_settings WHERE guild_id = ?",
+        )
+        .bind(guild_id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn get_or_default(pool: &SqlitePool, guild_id: &str) -> Result<Self> {
+        Ok(Self::get(pool, guild_id).await?.unwrap_or_else(|| Self {
+            guild_id: guild_id.to_string(),
+            ..Default::default()
+        }))
+    }
+
+    /// The configured feedback mode, falling back to a reply for unset or unknown values.
+    pub fn feedback(&self) -> SelfRoleFeedback {
+        SelfRoleFeedback::parse(&self.feedback_mode).unwrap_or_default()
+    }
+
+    pub async fn upsert(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO selfrole_settings
+                (guild_id, show_updated, show_cooldown, feedback_mode, crosspost, default_cooldown_secs)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (guild_id) DO UPDATE SET
+                show_updated = excluded.show_updated,
+                show_cooldown = excluded.show_cooldown,
+                feedback_mode = excluded.feedback_mode,
+                crosspost = excluded.crosspost,
+                default_cooldown_secs = excluded.default_cooldown_secs,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(&self.guild_id)
+        .bind(self.show_updated)
+        .bind(self.show_cooldown)
+        .bind(self.feedback().as_str())
+        .bind(self.crosspost)
+        .bind(self.default_cooldown_secs)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
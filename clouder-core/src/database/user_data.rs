@@ -0,0 +1,34 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+/// Every table keyed by `user_id`, children before parents. Scheduled messages
+/// the user created stay: they're guild content, `created_by` is only an audit field.
+const USER_TABLES: &[&str] = &[
+    "reminder_subscriptions",
+    "custom_reminder_subscriptions",
+    "user_settings",
+    "selfrole_cooldowns",
+    "uwufy_toggles",
+    "ai_usage",
+    "mediaonly_stats",
+    "pending_welcomes",
+    "user_guild_cache",
+    "dashboard_sessions",
+    "dashboard_users",
+];
+
+/// Deletes everything stored for a user in one transaction, including all of
+/// their dashboard sessions. Returns the number of rows removed.
+pub async fn delete_all(pool: &SqlitePool, user_id: &str) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+    let mut removed = 0;
+    for table in USER_TABLES {
+        removed += sqlx::query(&format!("DELETE FROM {} WHERE user_id = ?", table))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+    tx.commit().await?;
+    Ok(removed)
+}
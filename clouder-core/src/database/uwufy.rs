@@ -76,6 +76,52 @@ impl UwufyToggle {
         Ok(new_enabled)
     }
 
+    pub async fn get_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT guild_id, user_id, enabled, toggled_at
+            FROM uwufy_toggles
+            WHERE guild_id = ?
+            "#,
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Self {
+                guild_id: row.get("guild_id"),
+                user_id: row.get("user_id"),
+                enabled: row.get("enabled"),
+                toggled_at: parse_sqlite_datetime(&row.get::<String, _>("toggled_at")),
+            })
+            .collect())
+    }
+
+    pub async fn get_by_user(pool: &SqlitePool, user_id: &str) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT guild_id, user_id, enabled, toggled_at
+            FROM uwufy_toggles
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Self {
+                guild_id: row.get("guild_id"),
+                user_id: row.get("user_id"),
+                enabled: row.get("enabled"),
+                toggled_at: parse_sqlite_datetime(&row.get::<String, _>("toggled_at")),
+            })
+            .collect())
+    }
+
     pub async fn get_enabled_in_guild(
         pool: &SqlitePool,
         guild_id: &str,
@@ -1,8 +1,13 @@
 use crate::utils::parse_sqlite_datetime;
+use crate::utils::welcome_goodbye::NamePlaceholderSource;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, SqliteConnection, SqlitePool};
 use std::collections::HashMap;
 
+/// Stands in for placeholder values that couldn't be resolved at send time.
+pub const UNKNOWN_PLACEHOLDER: &str = "unknown";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WelcomeGoodbyeConfig {
     pub guild_id: String,
@@ -30,10 +35,33 @@ pub struct WelcomeGoodbyeConfig {
     pub goodbye_embed_thumbnail: Option<String>,
     pub goodbye_embed_image: Option<String>,
     pub goodbye_embed_timestamp: bool,
+    /// How variants are picked: `off`, `random`, or `round_robin`
+    pub welcome_rotation: String,
+    pub goodbye_rotation: String,
+    /// Hold the welcome until a member passes membership screening
+    pub welcome_after_screening: bool,
+    /// Which name fills `{username}`: `username`, `global_name`, or `nickname`
+    pub name_placeholder_source: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Which member message a variant or rotation setting belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberMessageKind {
+    Welcome,
+    Goodbye,
+}
+
+impl MemberMessageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemberMessageKind::Welcome => "welcome",
+            MemberMessageKind::Goodbye => "goodbye",
+        }
+    }
+}
+
 impl Default for WelcomeGoodbyeConfig {
     fn default() -> Self {
         Self {
@@ -60,6 +88,10 @@ impl Default for WelcomeGoodbyeConfig {
             goodbye_embed_thumbnail: None,
             goodbye_embed_image: None,
             goodbye_embed_timestamp: false,
+            welcome_rotation: "off".to_string(),
+            goodbye_rotation: "off".to_string(),
+            welcome_after_screening: false,
+            name_placeholder_source: "username".to_string(),
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         }
@@ -70,6 +102,15 @@ impl WelcomeGoodbyeConfig {
     pub async fn get_config(
         pool: &SqlitePool,
         guild_id: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        Self::get_config_in(&mut conn, guild_id).await
+    }
+
+    /// [`Self::get_config`] on an existing connection, so it can run inside a transaction.
+    pub async fn get_config_in(
+        conn: &mut SqliteConnection,
+        guild_id: &str,
     ) -> Result<Option<Self>, sqlx::Error> {
         let row = sqlx::query(
             r#"
@@ -79,13 +120,14 @@ impl WelcomeGoodbyeConfig {
                    welcome_embed_thumbnail, welcome_embed_image, welcome_embed_timestamp,
                    goodbye_embed_title, goodbye_embed_description, goodbye_embed_color, goodbye_embed_footer,
                    goodbye_embed_thumbnail, goodbye_embed_image, goodbye_embed_timestamp,
+                   welcome_rotation, goodbye_rotation, welcome_after_screening, name_placeholder_source,
                    created_at, updated_at
             FROM welcome_goodbye_configs
             WHERE guild_id = ?
             "#,
         )
         .bind(guild_id)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *conn)
         .await?;
 
         if let Some(row) = row {
@@ -113,6 +155,10 @@ impl WelcomeGoodbyeConfig {
                 goodbye_embed_thumbnail: row.get("goodbye_embed_thumbnail"),
                 goodbye_embed_image: row.get("goodbye_embed_image"),
                 goodbye_embed_timestamp: row.get("goodbye_embed_timestamp"),
+                welcome_rotation: row.get("welcome_rotation"),
+                goodbye_rotation: row.get("goodbye_rotation"),
+                welcome_after_screening: row.get("welcome_after_screening"),
+                name_placeholder_source: row.get("name_placeholder_source"),
                 created_at: parse_sqlite_datetime(&row.get::<String, _>("created_at")),
                 updated_at: parse_sqlite_datetime(&row.get::<String, _>("updated_at")),
             }))
@@ -122,6 +168,15 @@ impl WelcomeGoodbyeConfig {
     }
 
     pub async fn upsert_config(pool: &SqlitePool, config: &Self) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        Self::upsert_config_in(&mut conn, config).await
+    }
+
+    /// [`Self::upsert_config`] on an existing connection, so it can run inside a transaction.
+    pub async fn upsert_config_in(
+        conn: &mut SqliteConnection,
+        config: &Self,
+    ) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
             INSERT OR REPLACE INTO welcome_goodbye_configs (
@@ -131,8 +186,11 @@ impl WelcomeGoodbyeConfig {
                 welcome_embed_thumbnail, welcome_embed_image, welcome_embed_timestamp,
                 goodbye_embed_title, goodbye_embed_description, goodbye_embed_color, goodbye_embed_footer,
                 goodbye_embed_thumbnail, goodbye_embed_image, goodbye_embed_timestamp,
-                created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                welcome_rotation, goodbye_rotation, welcome_after_screening, name_placeholder_source,
+                welcome_rotation_counter, goodbye_rotation_counter, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                     COALESCE((SELECT welcome_rotation_counter FROM welcome_goodbye_configs WHERE guild_id = ?), 0),
+                     COALESCE((SELECT goodbye_rotation_counter FROM welcome_goodbye_configs WHERE guild_id = ?), 0),
                      COALESCE((SELECT created_at FROM welcome_goodbye_configs WHERE guild_id = ?), CURRENT_TIMESTAMP),
                      CURRENT_TIMESTAMP)
             "#,
@@ -160,44 +218,190 @@ impl WelcomeGoodbyeConfig {
         .bind(&config.goodbye_embed_thumbnail)
         .bind(&config.goodbye_embed_image)
         .bind(config.goodbye_embed_timestamp)
+        .bind(&config.welcome_rotation)
+        .bind(&config.goodbye_rotation)
+        .bind(config.welcome_after_screening)
+        .bind(&config.name_placeholder_source)
         .bind(&config.guild_id)
-        .execute(pool)
+        .bind(&config.guild_id)
+        .bind(&config.guild_id)
+        .execute(&mut *conn)
         .await?;
 
         Ok(())
     }
+
+    /// Message variants for `kind`, oldest first.
+    pub async fn get_variants(
+        pool: &SqlitePool,
+        guild_id: &str,
+        kind: MemberMessageKind,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT content FROM welcome_goodbye_variants WHERE guild_id = ? AND kind = ? ORDER BY id",
+        )
+        .bind(guild_id)
+        .bind(kind.as_str())
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Replaces every variant for `kind` with `variants`, keeping their order.
+    pub async fn set_variants(
+        pool: &SqlitePool,
+        guild_id: &str,
+        kind: MemberMessageKind,
+        variants: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        Self::set_variants_in(&mut tx, guild_id, kind, variants).await?;
+        tx.commit().await
+    }
+
+    /// [`Self::set_variants`] on an existing connection; the caller owns the transaction.
+    pub async fn set_variants_in(
+        conn: &mut SqliteConnection,
+        guild_id: &str,
+        kind: MemberMessageKind,
+        variants: &[String],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM welcome_goodbye_variants WHERE guild_id = ? AND kind = ?")
+            .bind(guild_id)
+            .bind(kind.as_str())
+            .execute(&mut *conn)
+            .await?;
+        for content in variants {
+            sqlx::query(
+                "INSERT INTO welcome_goodbye_variants (guild_id, kind, content) VALUES (?, ?, ?)",
+            )
+            .bind(guild_id)
+            .bind(kind.as_str())
+            .bind(content)
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Bumps the round-robin counter for `kind` and returns its value before the bump.
+    pub async fn next_rotation_counter(
+        pool: &SqlitePool,
+        guild_id: &str,
+        kind: MemberMessageKind,
+    ) -> Result<i64, sqlx::Error> {
+        let query = match kind {
+            MemberMessageKind::Welcome => {
+                "UPDATE welcome_goodbye_configs SET welcome_rotation_counter = welcome_rotation_counter + 1 WHERE guild_id = ? RETURNING welcome_rotation_counter - 1"
+            }
+            MemberMessageKind::Goodbye => {
+                "UPDATE welcome_goodbye_configs SET goodbye_rotation_counter = goodbye_rotation_counter + 1 WHERE guild_id = ? RETURNING goodbye_rotation_counter - 1"
+            }
+        };
+        Ok(sqlx::query_scalar(query)
+            .bind(guild_id)
+            .fetch_optional(pool)
+            .await?
+            .unwrap_or(0))
+    }
+}
+
+/// A member who joined while membership screening was pending, waiting for
+/// their welcome. Kept in the db so it survives restarts and doesn't depend on
+/// the old member state being cached when screening is passed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct PendingWelcome {
+    pub guild_id: String,
+    pub user_id: String,
+    pub joined_at: DateTime<Utc>,
+}
+
+impl PendingWelcome {
+    pub async fn record(
+        pool: &SqlitePool,
+        guild_id: &str,
+        user_id: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO pending_welcomes (guild_id, user_id) VALUES (?, ?)
+             ON CONFLICT(guild_id, user_id) DO UPDATE SET joined_at = CURRENT_TIMESTAMP",
+        )
+        .bind(guild_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Removes the entry; true if there was one, so only one update sends the welcome.
+    pub async fn take(
+        pool: &SqlitePool,
+        guild_id: &str,
+        user_id: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM pending_welcomes WHERE guild_id = ? AND user_id = ?")
+            .bind(guild_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_by_guild(pool: &SqlitePool, guild_id: &str) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM pending_welcomes WHERE guild_id = ? ORDER BY joined_at, user_id",
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn get_by_user(pool: &SqlitePool, user_id: &str) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM pending_welcomes WHERE user_id = ? ORDER BY joined_at, guild_id",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
 }
 
+/// Placeholder values for a member message. Only `user`, `username` and
+/// `user_id` are guaranteed; the guild may be missing from the cache and a
+/// departing member's data may not have been cached, so anything that can't
+/// be resolved reads as [`UNKNOWN_PLACEHOLDER`] instead of blocking the send.
+/// `{username}` follows `name_source`, falling back when that name isn't set.
 pub fn get_member_placeholders(
     user: &serenity::model::user::User,
-    guild_name: &str,
-    member_count: u64,
+    guild_name: Option<&str>,
+    member_count: Option<u64>,
     member: Option<&serenity::model::guild::Member>,
+    name_source: NamePlaceholderSource,
 ) -> HashMap<String, String> {
     let mut placeholders = HashMap::new();
 
+    let name = name_source.resolve(
+        &user.name,
+        user.global_name.as_deref(),
+        member.and_then(|m| m.nick.as_deref()),
+    );
     placeholders.insert("user".to_string(), format!("<@{}>", user.id));
-    placeholders.insert("username".to_string(), user.name.clone());
-    placeholders.insert("server".to_string(), guild_name.to_string());
-    placeholders.insert("member_count".to_string(), member_count.to_string());
+    placeholders.insert("username".to_string(), name.to_string());
+    placeholders.insert(
+        "server".to_string(),
+        guild_name.unwrap_or(UNKNOWN_PLACEHOLDER).to_string(),
+    );
+    placeholders.insert(
+        "member_count".to_string(),
+        member_count.map_or_else(|| UNKNOWN_PLACEHOLDER.to_string(), |n| n.to_string()),
+    );
     placeholders.insert("user_id".to_string(), user.id.to_string());
 
-    if let Some(member) = member {
-        let ts = member
-            .joined_at
-            .map(|j| j.unix_timestamp())
-            .unwrap_or_else(|| user.created_at().unix_timestamp());
-        let date_str = chrono::DateTime::from_timestamp(ts, 0)
-            .map(|dt| dt.format("%Y-%m-%d").to_string())
-            .unwrap_or_default();
-        placeholders.insert("join_date".to_string(), date_str);
-    } else {
-        let created_at = user.created_at();
-        let date_str = chrono::DateTime::from_timestamp(created_at.unix_timestamp(), 0)
-            .map(|dt| dt.format("%Y-%m-%d").to_string())
-            .unwrap_or_default();
-        placeholders.insert("join_date".to_string(), date_str);
-    }
+    let join_date = member
+        .and_then(|m| m.joined_at)
+        .and_then(|j| chrono::DateTime::from_timestamp(j.unix_timestamp(), 0))
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| UNKNOWN_PLACEHOLDER.to_string());
+    placeholders.insert("join_date".to_string(), join_date);
 
     placeholders
 }
@@ -219,7 +219,7 @@ pub async fn fetch_repos(username: &str, token: Option<&str>) -> Result<Vec<GhRe
         bail!("not found");
     }
     let mut repos: Vec<GhRepo> = resp.error_for_status()?.json().await?;
-    repos.sort_by(|a, b| b.stargazers_count.cmp(&a.stargazers_count));
+    repos.sort_by_key(|r| std::cmp::Reverse(r.stargazers_count));
 
     {
         let mut cache = repos_cache().lock().unwrap();
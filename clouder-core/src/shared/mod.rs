@@ -2,8 +2,13 @@ pub mod models;
 
 use crate::config::AppState;
 use crate::database;
+use crate::database::dashboard_sessions::DashboardSession;
+use crate::database::dashboard_users::DashboardUser;
 use crate::database::guild_cache::CachedGuild;
-use crate::database::selfroles::{SelfRoleConfig, SelfRoleLabel};
+use crate::database::selfroles::{
+    MessageIdTaken, SelfRoleConfig, SelfRoleEmojiPolicy, SelfRoleFeedback, SelfRoleLabel,
+    SelfRoleSettings,
+};
 use anyhow::Result;
 use serde_json::{Value, json};
 use serenity::all::{GuildId, Http, Permissions};
@@ -22,10 +27,61 @@ pub fn check_interaction_expired(error: &impl std::fmt::Display) {
     }
 }
 
+/// Plain-language reason for a Discord API failure an admin can act on, keyed by
+/// Discord's JSON error code and falling back to the HTTP status.
+pub fn discord_error_hint(status: u16, code: isize) -> Option<&'static str> {
+    match code {
+        50001 => Some("missing access: the bot can't see that channel"),
+        50013 => Some("missing permissions: the bot needs send messages and embed links there"),
+        10003 => Some("unknown channel: it may have been deleted"),
+        10008 => Some("unknown message: it may have been deleted"),
+        50035 => Some("invalid form body: discord rejected the message content"),
+        _ => match status {
+            429 => Some("rate limited by discord, try again shortly"),
+            500.. => Some("discord is having problems, try again shortly"),
+            _ => None,
+        },
+    }
+}
+
+/// Describes a failed Discord request for the dashboard instead of a bare "failed to send".
+pub fn describe_discord_error(error: &serenity::Error) -> String {
+    use serenity::all::HttpError;
+
+    match error {
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response)) => {
+            discord_error_hint(response.status_code.as_u16(), response.error.code)
+                .map(str::to_string)
+                .unwrap_or_else(|| {
+                    format!("{} (code {})", response.error.message, response.error.code)
+                })
+        }
+        serenity::Error::Http(HttpError::Request(_)) => "couldn't reach discord".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Discord rejects button labels longer than this many characters.
+pub const BUTTON_LABEL_MAX_CHARS: usize = 80;
+
+/// `"{emoji} {label}"`, with the role name cut short (ending in `…`) so the
+/// whole label stays within [`BUTTON_LABEL_MAX_CHARS`].
 pub fn format_selfrole_button_label(emoji: &str, label: &str) -> String {
     let trimmed = emoji.trim();
-    if trimmed.is_empty() {
+    let prefix_chars = if trimmed.is_empty() {
+        0
+    } else {
+        trimmed.chars().count() + 1
+    };
+    let budget = BUTTON_LABEL_MAX_CHARS.saturating_sub(prefix_chars);
+    let label = if label.chars().count() > budget {
+        // `truncate` appends the ellipsis after `max_chars`, so leave room for it
+        crate::utils::truncate(label, budget.saturating_sub(1))
+    } else {
         label.to_string()
+    };
+    if trimmed.is_empty() {
+        label
     } else {
         format!("{} {}", trimmed, label)
     }
@@ -96,8 +152,10 @@ pub async fn send_test_welcome_message(
     user_id: &str,
 ) -> Result<(), String> {
     use crate::database::welcome_goodbye::WelcomeGoodbyeConfig;
-    use crate::utils::welcome_goodbye::{EmbedConfig, build_embed, replace_placeholders};
-    use serenity::all::{ChannelId, CreateMessage};
+    use crate::utils::welcome_goodbye::{
+        EmbedConfig, NamePlaceholderSource, build_embed, replace_placeholders,
+    };
+    use serenity::all::{ChannelId, CreateMessage, UserId};
     use std::collections::HashMap;
 
     let config = WelcomeGoodbyeConfig::get_config(&app_state.db, &guild_id.to_string())
@@ -137,10 +195,31 @@ pub async fn send_test_welcome_message(
     let channel_id = channel_id.ok_or("No channel configured")?;
     let channel: ChannelId = channel_id.parse().map_err(|_| "Invalid channel ID")?;
 
+    // resolve {username} the way a real join would, falling back to the id
+    let name_source =
+        NamePlaceholderSource::parse(&config.name_placeholder_source).unwrap_or_default();
+    let username = match user_id.parse::<u64>() {
+        Ok(id) => match app_state
+            .http
+            .get_member(GuildId::new(guild_id), UserId::new(id))
+            .await
+        {
+            Ok(member) => name_source
+                .resolve(
+                    &member.user.name,
+                    member.user.global_name.as_deref(),
+                    member.nick.as_deref(),
+                )
+                .to_string(),
+            Err(_) => user_id.to_string(),
+        },
+        Err(_) => user_id.to_string(),
+    };
+
     let mut placeholders = HashMap::new();
     placeholders.insert("user".to_string(), format!("<@{}>", user_id));
     placeholders.insert("user_id".to_string(), user_id.to_string());
-    placeholders.insert("username".to_string(), user_id.to_string());
+    placeholders.insert("username".to_string(), username);
     placeholders.insert("server".to_string(), guild_id.to_string());
     placeholders.insert("member_count".to_string(), "?".to_string());
     placeholders.insert("join_date".to_string(), "today".to_string());
@@ -170,16 +249,113 @@ pub async fn send_test_welcome_message(
     Ok(())
 }
 
-/// Get self-roles configurations for a guild
-pub async fn list_selfroles(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+/// One role of a panel as the dashboard sees it. `guild_role` is the live discord
+/// role (`None` once deleted) and `bot_positions` the bot's role positions; when
+/// discord couldn't be asked, `role_name`, `color` and `manageable` are null.
+pub fn selfrole_role_entry(
+    role_id: &str,
+    emoji: &str,
+    label: &str,
+    guild_role: Option<&serenity::all::Role>,
+    roles_known: bool,
+    bot_positions: Option<&[u16]>,
+) -> Value {
+    let manageable = match (guild_role, bot_positions) {
+        (Some(role), Some(positions)) => json!(crate::utils::can_bot_manage_role(
+            positions.iter().copied(),
+            role.position,
+            role.managed,
+        )),
+        // a role that's gone can't be handed out
+        (None, _) if roles_known => json!(false),
+        _ => Value::Null,
+    };
+    json!({
+        "role_id": role_id,
+        "emoji": emoji,
+        "label": label,
+        "role_name": guild_role.map(|r| r.name.clone()),
+        "color": guild_role.map(|r| r.colour.0),
+        "manageable": manageable,
+    })
+}
+
+/// The bot's role positions in a guild, for hierarchy checks.
+async fn bot_role_positions(
+    app_state: &AppState,
+    guild_id: GuildId,
+    guild_roles: &[serenity::all::Role],
+) -> Option<Vec<u16>> {
+    let bot_id = app_state.http.get_current_user().await.ok()?.id;
+    let member = app_state.http.get_member(guild_id, bot_id).await.ok()?;
+    Some(
+        guild_roles
+            .iter()
+            .filter(|r| member.roles.contains(&r.id))
+            .map(|r| r.position)
+            .collect(),
+    )
+}
+
+/// Keeps the panels whose title contains `search` (ignoring case) and that
+/// post in `channel_id`. A missing or blank filter matches everything.
+pub fn filter_selfrole_configs(
+    configs: Vec<SelfRoleConfig>,
+    search: Option<&str>,
+    channel_id: Option<&str>,
+) -> Vec<SelfRoleConfig> {
+    let search = search
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_lowercase);
+    let channel_id = channel_id.map(str::trim).filter(|c| !c.is_empty());
+    configs
+        .into_iter()
+        .filter(|c| {
+            search
+                .as_ref()
+                .is_none_or(|s| c.title.to_lowercase().contains(s))
+        })
+        .filter(|c| channel_id.is_none_or(|id| c.channel_id == id))
+        .collect()
+}
+
+/// Get self-roles configurations for a guild, optionally filtered by title
+/// and channel. `total` counts every panel in the guild, filtered or not.
+pub async fn list_selfroles(
+    app_state: &AppState,
+    guild_id: u64,
+    search: Option<&str>,
+    channel_id: Option<&str>,
+) -> Result<Value, String> {
     let guild_id_str = guild_id.to_string();
     let configs = database::selfroles::SelfRoleConfig::get_by_guild(&app_state.db, &guild_id_str)
         .await
         .map_err(|e| format!("Failed to get self-roles: {}", e))?;
+    let total = configs.len();
+    let configs = filter_selfrole_configs(configs, search, channel_id);
 
     let labels = SelfRoleLabel::get_all_for_guild(&app_state.db, &guild_id_str)
         .await
         .unwrap_or_default();
+
+    // resolve names, colors and hierarchy once for every panel
+    let guild_roles = if configs.is_empty() {
+        None
+    } else {
+        match app_state.http.get_guild_roles(GuildId::new(guild_id)).await {
+            Ok(roles) => Some(roles),
+            Err(e) => {
+                warn!("get roles for guild {}: {}", guild_id, e);
+                None
+            }
+        }
+    };
+    let bot_positions = match &guild_roles {
+        Some(roles) => bot_role_positions(app_state, GuildId::new(guild_id), roles).await,
+        None => None,
+    };
+
     let mut config_data = Vec::new();
     for config in configs {
         let roles = config.get_roles(&app_state.db).await.unwrap_or_default();
@@ -188,11 +364,17 @@ pub async fn list_selfroles(app_state: &AppState, guild_id: u64) -> Result<Value
             .iter()
             .map(|r| {
                 let label = labels.get(&r.role_id).cloned().unwrap_or_default();
-                json!({
-                    "role_id": r.role_id,
-                    "emoji": r.emoji,
-                    "label": label,
-                })
+                let guild_role = guild_roles
+                    .as_ref()
+                    .and_then(|rs| rs.iter().find(|g| g.id.to_string() == r.role_id));
+                selfrole_role_entry(
+                    &r.role_id,
+                    &r.emoji,
+                    &label,
+                    guild_role,
+                    guild_roles.is_some(),
+                    bot_positions.as_deref(),
+                )
             })
             .collect();
 
@@ -204,23 +386,59 @@ pub async fn list_selfroles(app_state: &AppState, guild_id: u64) -> Result<Value
             "title": config.title,
             "description": config.body,
             "selection_type": config.selection_type,
+            "attached": config.attached,
             "roles": role_list,
             "created_at": config.created_at,
             "updated_at": config.updated_at,
         }));
     }
 
-    Ok(json!({ "success": true, "configs": config_data }))
+    Ok(json!({ "success": true, "configs": config_data, "total": total }))
 }
 
-/// Create a new self-role configuration
-pub async fn create_selfrole(
+const SELFROLE_TEMPLATES: &str = include_str!("selfrole_templates.json");
+
+/// Built-in starter panels the create form can pre-fill from
+pub fn list_selfrole_templates() -> Result<Value, String> {
+    let templates: Vec<models::SelfRoleTemplate> = serde_json::from_str(SELFROLE_TEMPLATES)
+        .map_err(|e| format!("Failed to parse templates: {}", e))?;
+    Ok(json!({ "success": true, "templates": templates }))
+}
+
+/// Fields of a self-role create/update payload that passed validation.
+#[derive(Clone, Copy)]
+struct SelfRoleRequest<'a> {
+    title: &'a str,
+    body: &'a str,
+    selection_type: &'a str,
+    channel_id: &'a str,
+    roles: &'a Vec<Value>,
+    /// Click cooldown the request asked for; new panels otherwise take the guild default.
+    cooldown_secs: Option<i64>,
+}
+
+/// Reads a click cooldown in seconds, allowing 0 (none) up to an hour.
+fn parse_selfrole_cooldown(value: &Value, field: &str) -> Result<i64, String> {
+    use database::selfroles::MAX_SELFROLE_COOLDOWN_SECS;
+
+    value
+        .as_i64()
+        .filter(|secs| (0..=MAX_SELFROLE_COOLDOWN_SECS).contains(secs))
+        .ok_or_else(|| {
+            format!(
+                "{} must be between 0 and {} seconds",
+                field, MAX_SELFROLE_COOLDOWN_SECS
+            )
+        })
+}
+
+/// Validates a self-role create/update payload against the shape rules and
+/// the guild's emoji policy. Runs before any discord calls.
+async fn validate_selfrole_request<'a>(
     app_state: &AppState,
     guild_id: u64,
-    user_id: u64,
-    payload: &Value,
-) -> Result<Value, String> {
-    // Validate the payload
+    payload: &'a Value,
+) -> Result<SelfRoleRequest<'a>, String> {
     let title = payload
         .get("title")
         .and_then(|v| v.as_str())
@@ -260,6 +478,456 @@ pub async fn create_selfrole(
         return Err("Must have 1-25 roles".to_string());
     }
 
+    // role ids end up in button custom ids, which discord requires to be unique per message
+    let mut seen_roles = std::collections::HashSet::new();
+    for role_data in roles {
+        if let Some(role_id) = role_data.get("role_id").and_then(|v| v.as_str())
+            && !seen_roles.insert(role_id)
+        {
+            return Err(format!("role {} is listed more than once", role_id));
+        }
+    }
+
+    let policy = SelfRoleEmojiPolicy::get(&app_state.db, &guild_id.to_string())
+        .await
+        .map_err(|e| format!("Failed to get emoji policy: {}", e))?;
+    if let Some(policy) = policy {
+        for role_data in roles {
+            let emoji = role_data
+                .get("emoji")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .trim();
+            if !policy.allows(emoji) {
+                return Err(format!("emoji {} is not allowed in this server", emoji));
+            }
+        }
+    }
+
+    let cooldown_secs = payload
+        .get("cooldown_secs")
+        .filter(|v| !v.is_null())
+        .map(|v| parse_selfrole_cooldown(v, "cooldown_secs"))
+        .transpose()?;
+
+    Ok(SelfRoleRequest {
+        title,
+        body,
+        selection_type,
+        channel_id,
+        roles,
+        cooldown_secs,
+    })
+}
+
+/// Checks a self-role emoji can actually be shown: empty, a unicode emoji, or a custom
+/// emoji whose id is in `guild_emoji_ids`. Returns the reason it can't, if any.
+pub fn selfrole_emoji_problem(
+    emoji: &str,
+    guild_emoji_ids: &std::collections::HashSet<u64>,
+) -> Option<String> {
+    let emoji = emoji.trim();
+    if emoji.is_empty() {
+        return None;
+    }
+    if emoji.starts_with('<') {
+        let id = emoji
+            .strip_suffix('>')
+            .and_then(|inner| inner.rsplit(':').next())
+            .and_then(|id| id.parse::<u64>().ok());
+        return match id {
+            Some(id) if guild_emoji_ids.contains(&id) => None,
+            Some(_) => Some(format!(
+                "emoji {} isn't from this server, so the bot can't show it",
+                emoji
+            )),
+            None => Some(format!("{} isn't a valid custom emoji", emoji)),
+        };
+    }
+    // unicode emoji are short and never plain ascii text (keycaps mix in a digit)
+    let looks_unicode =
+        emoji.chars().count() <= 16 && !emoji.chars().any(char::is_whitespace) && !emoji.is_ascii();
+    if looks_unicode {
+        None
+    } else {
+        Some(format!("{} isn't an emoji", emoji))
+    }
+}
+
+/// Rejects emojis the bot can't render. Guild emojis are only fetched when a custom
+/// emoji is actually used.
+async fn check_selfrole_emojis(
+    app_state: &AppState,
+    guild_id: u64,
+    roles: &[Value],
+) -> Result<(), String> {
+    let emojis: Vec<&str> = roles
+        .iter()
+        .filter_map(|r| r.get("emoji").and_then(|v| v.as_str()))
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .collect();
+    let guild_emoji_ids = if emojis.iter().any(|e| e.starts_with('<')) {
+        app_state
+            .http
+            .get_emojis(GuildId::new(guild_id))
+            .await
+            .map_err(|e| {
+                format!(
+                    "Failed to get server emojis: {}",
+                    describe_discord_error(&e)
+                )
+            })?
+            .into_iter()
+            .map(|e| e.id.get())
+            .collect()
+    } else {
+        std::collections::HashSet::new()
+    };
+    for emoji in emojis {
+        if let Some(problem) = selfrole_emoji_problem(emoji, &guild_emoji_ids) {
+            return Err(problem);
+        }
+    }
+    Ok(())
+}
+
+/// Errors once `channel_id` already holds the configured maximum of panels.
+async fn check_channel_panel_limit(
+    app_state: &AppState,
+    guild_id: u64,
+    channel_id: &str,
+) -> Result<(), String> {
+    let max = app_state.config.selfrole_max_panels_per_channel;
+    let count = SelfRoleConfig::count_by_channel(&app_state.db, &guild_id.to_string(), channel_id)
+        .await
+        .map_err(|e| format!("DB error: {}", e))?;
+    if count >= max {
+        return Err(format!(
+            "maximum of {} self-role panels per channel reached",
+            max
+        ));
+    }
+    Ok(())
+}
+
+/// Permissions the bot needs in a channel to post a self-role panel.
+const SELFROLE_CHANNEL_PERMISSIONS: Permissions = Permissions::VIEW_CHANNEL
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::EMBED_LINKS);
+
+/// Rejects a channel picked for one guild's settings that lives somewhere else,
+/// or outside any guild. `channel_guild` is the guild discord says owns it.
+pub fn channel_guild_problem(
+    channel_guild: Option<GuildId>,
+    guild_id: GuildId,
+) -> Option<&'static str> {
+    (channel_guild != Some(guild_id)).then_some("that channel isn't in this server")
+}
+
+/// Decides whether a channel can hold a self-role panel, given what discord reported
+/// about it. Returns the reason it can't, if any.
+pub fn selfrole_channel_problem(
+    kind: serenity::all::ChannelType,
+    channel_guild: GuildId,
+    guild_id: GuildId,
+    bot_permissions: Permissions,
+) -> Option<String> {
+    use serenity::all::ChannelType;
+
+    if let Some(problem) = channel_guild_problem(Some(channel_guild), guild_id) {
+        return Some(problem.to_string());
+    }
+    if !matches!(kind, ChannelType::Text | ChannelType::News) {
+        return Some("self-role panels need a text or announcement channel".to_string());
+    }
+    missing_channel_permissions(SELFROLE_CHANNEL_PERMISSIONS, bot_permissions)
+}
+
+/// Names whatever part of `required` the bot lacks in a channel, if anything.
+fn missing_channel_permissions(
+    required: Permissions,
+    bot_permissions: Permissions,
+) -> Option<String> {
+    if bot_permissions.administrator() {
+        return None;
+    }
+    let missing = required - bot_permissions;
+    if missing.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "missing permissions: the bot needs {} in that channel",
+            missing.get_permission_names().join(", ").to_lowercase()
+        ))
+    }
+}
+
+/// The bot's effective permissions in a guild channel, with overwrites applied.
+async fn get_bot_channel_permissions(
+    app_state: &AppState,
+    guild_id: u64,
+    channel_id: &str,
+) -> Result<(serenity::all::GuildChannel, Permissions), String> {
+    let channel_id: u64 = channel_id
+        .parse()
+        .map_err(|_| "Invalid channel ID".to_string())?;
+    let channel = app_state
+        .http
+        .get_channel(channel_id.into())
+        .await
+        .map_err(|e| format!("Failed to get channel: {}", describe_discord_error(&e)))?
+        .guild()
+        .ok_or("that channel isn't in this server")?;
+
+    let guild = app_state
+        .http
+        .get_guild(GuildId::new(guild_id))
+        .await
+        .map_err(|e| format!("Failed to get server: {}", describe_discord_error(&e)))?;
+    let bot_id = app_state
+        .http
+        .get_current_user()
+        .await
+        .map_err(|e| format!("Failed to get bot user: {}", e))?
+        .id;
+    let member = app_state
+        .http
+        .get_member(guild.id, bot_id)
+        .await
+        .map_err(|e| format!("Failed to get bot member: {}", describe_discord_error(&e)))?;
+
+    let permissions = guild.user_permissions_in(&channel, &member);
+    Ok((channel, permissions))
+}
+
+/// Checks the bot can actually post a panel in `channel_id` before anything is saved.
+async fn check_selfrole_channel(
+    app_state: &AppState,
+    guild_id: u64,
+    channel_id: &str,
+) -> Result<(), String> {
+    let (channel, permissions) =
+        get_bot_channel_permissions(app_state, guild_id, channel_id).await?;
+    match selfrole_channel_problem(
+        channel.kind,
+        channel.guild_id,
+        GuildId::new(guild_id),
+        permissions,
+    ) {
+        Some(problem) => Err(problem),
+        None => Ok(()),
+    }
+}
+
+/// Whether a freshly posted panel should be published: the guild opted in,
+/// the channel is an announcement channel, and the bot may send there, which
+/// is all discord asks of a bot publishing its own message.
+pub fn should_crosspost_selfrole(
+    enabled: bool,
+    kind: serenity::all::ChannelType,
+    bot_permissions: Permissions,
+) -> bool {
+    enabled
+        && kind == serenity::all::ChannelType::News
+        && (bot_permissions.administrator() || bot_permissions.send_messages())
+}
+
+/// Publishes a just-posted panel when the guild has crossposting on. The
+/// panel is already live either way, so failures are only logged.
+async fn crosspost_selfrole_message(
+    app_state: &AppState,
+    guild_id: u64,
+    channel_id: &str,
+    message_id: serenity::all::MessageId,
+) {
+    let enabled = SelfRoleSettings::get_or_default(&app_state.db, &guild_id.to_string())
+        .await
+        .map(|s| s.crosspost)
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    let (channel, permissions) =
+        match get_bot_channel_permissions(app_state, guild_id, channel_id).await {
+            Ok(found) => found,
+            Err(e) => {
+                warn!("check crosspost channel {}: {}", channel_id, e);
+                return;
+            }
+        };
+    if !should_crosspost_selfrole(enabled, channel.kind, permissions) {
+        return;
+    }
+    if let Err(e) = app_state
+        .http
+        .crosspost_message(channel.id, message_id)
+        .await
+    {
+        warn!(
+            "crosspost selfrole message {}: {}",
+            message_id,
+            describe_discord_error(&e)
+        );
+    }
+}
+
+/// Create a new self-role configuration
+pub async fn create_selfrole(
+    app_state: &AppState,
+    guild_id: u64,
+    user_id: u64,
+    payload: &Value,
+) -> Result<Value, String> {
+    let request = validate_selfrole_request(app_state, guild_id, payload).await?;
+    check_selfrole_emojis(app_state, guild_id, request.roles).await?;
+    check_channel_panel_limit(app_state, guild_id, request.channel_id).await?;
+    check_selfrole_channel(app_state, guild_id, request.channel_id).await?;
+
+    let mut config = save_selfrole_config(app_state, guild_id, &request).await?;
+
+    // Deploy the message
+    deploy_selfrole_message(app_state, &mut config, guild_id, request.roles, user_id).await
+}
+
+/// Decides whether buttons can be attached to an existing message. Returns the reason
+/// they can't, if any.
+pub fn selfrole_attach_problem(
+    message_author: serenity::all::UserId,
+    bot_id: serenity::all::UserId,
+    already_attached: bool,
+) -> Option<String> {
+    if already_attached {
+        return Some("that message already has a self-role panel".to_string());
+    }
+    if message_author != bot_id {
+        return Some(
+            "discord only lets the bot add buttons to its own messages; repost it through clouder first"
+                .to_string(),
+        );
+    }
+    None
+}
+
+/// Attach self-role buttons to an existing message instead of posting a new panel.
+///
+/// The message keeps its content and embeds; only its components are replaced.
+pub async fn attach_selfrole(
+    app_state: &AppState,
+    guild_id: u64,
+    payload: &Value,
+) -> Result<Value, String> {
+    use serenity::all::{ChannelId, MessageId};
+    use serenity::builder::EditMessage;
+
+    let message_id = payload
+        .get("message_id")
+        .and_then(|v| v.as_str())
+        .ok_or("Message ID is required")?;
+    let message_id_u64: u64 = message_id
+        .parse()
+        .map_err(|_| "Invalid message ID".to_string())?;
+
+    let request = validate_selfrole_request(app_state, guild_id, payload).await?;
+    check_selfrole_emojis(app_state, guild_id, request.roles).await?;
+    check_channel_panel_limit(app_state, guild_id, request.channel_id).await?;
+    check_selfrole_channel(app_state, guild_id, request.channel_id).await?;
+    let channel_id_u64: u64 = request
+        .channel_id
+        .parse()
+        .map_err(|_| "Invalid channel ID".to_string())?;
+
+    let message = app_state
+        .http
+        .get_message(
+            ChannelId::new(channel_id_u64),
+            MessageId::new(message_id_u64),
+        )
+        .await
+        .map_err(|e| format!("Failed to get message: {}", describe_discord_error(&e)))?;
+    let bot_id = app_state
+        .http
+        .get_current_user()
+        .await
+        .map_err(|e| format!("Failed to get bot user: {}", e))?
+        .id;
+    let already_attached = SelfRoleConfig::get_by_message_id(&app_state.db, message_id)
+        .await
+        .map_err(db_err)?
+        .is_some();
+    if let Some(problem) = selfrole_attach_problem(message.author.id, bot_id, already_attached) {
+        return Err(problem);
+    }
+
+    let mut config = save_selfrole_config(app_state, guild_id, &request).await?;
+
+    let (_, action_rows) = build_selfrole_embed_and_components(
+        app_state,
+        guild_id,
+        &config,
+        request.title,
+        request.body,
+        request.selection_type,
+        request.roles,
+    )
+    .await;
+    let edit = EditMessage::new().components(action_rows);
+    if let Err(e) = app_state
+        .http
+        .edit_message(message.channel_id, message.id, &edit, Vec::new())
+        .await
+    {
+        let _ = config.delete(&app_state.db).await;
+        return Err(format!(
+            "Failed to edit message: {}",
+            describe_discord_error(&e)
+        ));
+    }
+
+    config
+        .mark_attached(&app_state.db, message_id)
+        .await
+        .map_err(|e| format!("Failed to update message ID: {}", e))?;
+
+    Ok(json!({
+        "success": true,
+        "message": "Self-role buttons attached successfully",
+        "message_id": message_id,
+        "id": config.id,
+    }))
+}
+
+/// Cooldown for a new panel: the requested one, else the guild's default.
+pub async fn selfrole_cooldown_for(
+    app_state: &AppState,
+    guild_id: &str,
+    requested: Option<i64>,
+) -> Result<i64, String> {
+    match requested {
+        Some(secs) => Ok(secs),
+        None => Ok(SelfRoleSettings::get_or_default(&app_state.db, guild_id)
+            .await
+            .map_err(db_err)?
+            .default_cooldown_secs),
+    }
+}
+
+/// Rejects managed roles, then stores the config and its roles. Discord messages are
+/// left to the caller; the config has no message id yet.
+async fn save_selfrole_config(
+    app_state: &AppState,
+    guild_id: u64,
+    request: &SelfRoleRequest<'_>,
+) -> Result<SelfRoleConfig, String> {
+    let SelfRoleRequest {
+        title,
+        body,
+        selection_type,
+        channel_id,
+        roles,
+        cooldown_secs,
+    } = *request;
+
     let guild_roles = app_state
         .http
         .get_guild_roles(GuildId::new(guild_id))
@@ -295,7 +963,7 @@ pub async fn create_selfrole(
     let _ = SelfRoleLabel::upsert_many(&app_state.db, &guild_id_str, &pairs_ref).await;
 
     // Create the configuration
-    let config = database::selfroles::SelfRoleConfig::create(
+    let mut config = database::selfroles::SelfRoleConfig::create(
         &app_state.db,
         &guild_id_str,
         channel_id,
@@ -306,6 +974,14 @@ pub async fn create_selfrole(
     .await
     .map_err(|e| format!("Failed to save configuration: {}", e))?;
 
+    let cooldown_secs = selfrole_cooldown_for(app_state, &guild_id_str, cooldown_secs).await?;
+    if cooldown_secs != config.cooldown_secs {
+        config
+            .set_cooldown(&app_state.db, cooldown_secs)
+            .await
+            .map_err(|e| format!("Failed to save configuration: {}", e))?;
+    }
+
     // Save roles
     for role_data in roles {
         let role_id = role_data
@@ -335,8 +1011,7 @@ pub async fn create_selfrole(
         }
     }
 
-    // Deploy the message
-    deploy_selfrole_message(app_state, &config, guild_id, roles, user_id).await
+    Ok(config)
 }
 
 /// Update an existing self-role configuration.
@@ -350,47 +1025,15 @@ pub async fn update_selfrole(
     _user_id: u64,
     payload: &Value,
 ) -> Result<Value, String> {
-    use serenity::all::{ChannelId, MessageId};
-    use serenity::builder::EditMessage;
-
-    let title = payload
-        .get("title")
-        .and_then(|v| v.as_str())
-        .ok_or("Title is required")?;
-
-    // Accept both 'body' and 'description' for backwards compatibility
-    let body = payload
-        .get("description")
-        .or_else(|| payload.get("body"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("");
-
-    let selection_type = payload
-        .get("selection_type")
-        .and_then(|v| v.as_str())
-        .unwrap_or("multiple");
-
-    let channel_id = payload
-        .get("channel_id")
-        .and_then(|v| v.as_str())
-        .ok_or("Channel ID is required")?;
-
-    let roles = payload
-        .get("roles")
-        .and_then(|v| v.as_array())
-        .ok_or("Roles array is required")?;
-
-    if title.trim().is_empty() {
-        return Err("Title cannot be empty".to_string());
-    }
-
-    if selection_type != "radio" && selection_type != "multiple" {
-        return Err("Invalid selection type".to_string());
-    }
-
-    if roles.is_empty() || roles.len() > 25 {
-        return Err("Must have 1-25 roles".to_string());
-    }
+    let SelfRoleRequest {
+        title,
+        body,
+        selection_type,
+        channel_id,
+        roles,
+        cooldown_secs,
+    } = validate_selfrole_request(app_state, guild_id, payload).await?;
+    check_selfrole_emojis(app_state, guild_id, roles).await?;
 
     let guild_roles = app_state
         .http
@@ -432,11 +1075,125 @@ pub async fn update_selfrole(
     if config.guild_id != guild_id.to_string() {
         return Err("Configuration not found".to_string());
     }
+    if config.channel_id != channel_id {
+        if config.attached {
+            return Err("attached panels can't move to another channel".to_string());
+        }
+        check_channel_panel_limit(app_state, guild_id, channel_id).await?;
+        check_selfrole_channel(app_state, guild_id, channel_id).await?;
+    }
+
+    let mut role_pairs: Vec<(String, String)> = Vec::with_capacity(roles.len());
+    for role_data in roles {
+        let role_id = role_data
+            .get("role_id")
+            .and_then(|v| v.as_str())
+            .ok_or("Role ID is required")?;
+        let emoji = role_data
+            .get("emoji")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .trim();
+        role_pairs.push((role_id.to_string(), emoji.to_string()));
+    }
+
+    // the db is updated first, in one transaction, and put back if discord
+    // refuses the edit, so the panel and its buttons never disagree
+    let previous_roles: Vec<(String, String)> = config
+        .get_roles(&app_state.db)
+        .await
+        .map_err(|e| format!("Failed to get roles: {}", e))?
+        .into_iter()
+        .map(|r| (r.role_id, r.emoji))
+        .collect();
+    let previous = (
+        config.title.clone(),
+        config.body.clone(),
+        config.selection_type.clone(),
+    );
+    config
+        .replace_contents(&app_state.db, title, body, selection_type, &role_pairs)
+        .await
+        .map_err(|e| format!("Failed to save role configuration: {}", e))?;
+    let previous_cooldown = config.cooldown_secs;
+    if let Some(secs) = cooldown_secs {
+        config
+            .set_cooldown(&app_state.db, secs)
+            .await
+            .map_err(|e| format!("Failed to save role configuration: {}", e))?;
+    }
+
+    match publish_selfrole_update(
+        app_state,
+        guild_id,
+        &config,
+        channel_id,
+        title,
+        body,
+        selection_type,
+        roles,
+    )
+    .await
+    {
+        Ok(next_message_id) => {
+            finish_selfrole_update(
+                app_state,
+                guild_id,
+                config,
+                channel_id,
+                roles,
+                next_message_id,
+            )
+            .await
+        }
+        Err(e) => {
+            let (old_title, old_body, old_selection) = previous;
+            if let Err(restore) = config
+                .replace_contents(
+                    &app_state.db,
+                    &old_title,
+                    &old_body,
+                    &old_selection,
+                    &previous_roles,
+                )
+                .await
+            {
+                error!(
+                    "restore selfrole {} after failed edit: {}",
+                    config.id, restore
+                );
+            }
+            if let Err(restore) = config.set_cooldown(&app_state.db, previous_cooldown).await {
+                error!(
+                    "restore selfrole {} cooldown after failed edit: {}",
+                    config.id, restore
+                );
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Edits the panel in place, or reposts it when it has no message or moved
+/// channel. Returns the id of the message now showing the panel.
+#[allow(clippy::too_many_arguments)]
+async fn publish_selfrole_update(
+    app_state: &AppState,
+    guild_id: u64,
+    config: &SelfRoleConfig,
+    channel_id: &str,
+    title: &str,
+    body: &str,
+    selection_type: &str,
+    roles: &[Value],
+) -> Result<Option<String>, String> {
+    use serenity::all::{ChannelId, MessageId};
+    use serenity::builder::EditMessage;
 
     let (embed, action_rows) = build_selfrole_embed_and_components(
         app_state,
         guild_id,
-        config.id,
+        config,
         title,
         body,
         selection_type,
@@ -463,7 +1220,12 @@ pub async fn update_selfrole(
             .parse()
             .map_err(|_| "Invalid message ID".to_string())?;
 
-        let edit = EditMessage::new().embed(embed).components(action_rows);
+        // attached messages keep their own content; only the buttons are ours
+        let edit = if config.attached {
+            EditMessage::new().components(action_rows)
+        } else {
+            EditMessage::new().embed(embed).components(action_rows)
+        };
         app_state
             .http
             .edit_message(
@@ -506,12 +1268,24 @@ pub async fn update_selfrole(
             .http
             .send_message(channel_id_u64.into(), Vec::new(), &msg)
             .await
-            .map_err(|e| format!("Failed to send message: {}", e))?;
+            .map_err(|e| format!("Failed to send message: {}", describe_discord_error(&e)))?;
+        crosspost_selfrole_message(app_state, guild_id, channel_id, sent_message.id).await;
 
         next_message_id = Some(sent_message.id.to_string());
     }
 
-    // Persist db updates after discord succeeds
+    Ok(next_message_id)
+}
+
+/// Records where the panel now lives once discord has accepted the update.
+async fn finish_selfrole_update(
+    app_state: &AppState,
+    guild_id: u64,
+    mut config: SelfRoleConfig,
+    channel_id: &str,
+    roles: &[Value],
+    next_message_id: Option<String>,
+) -> Result<Value, String> {
     if config.channel_id != channel_id {
         config
             .update_channel_id(&app_state.db, channel_id)
@@ -519,41 +1293,18 @@ pub async fn update_selfrole(
             .map_err(|e| format!("Failed to update channel: {}", e))?;
     }
 
-    config
-        .update(&app_state.db, title, body, selection_type)
-        .await
-        .map_err(|e| format!("Failed to update configuration: {}", e))?;
-
-    database::selfroles::SelfRoleRole::delete_by_config_id(&app_state.db, config.id)
-        .await
-        .map_err(|e| format!("Failed to update roles: {}", e))?;
-
     for role_data in roles {
-        let role_id = role_data
-            .get("role_id")
-            .and_then(|v| v.as_str())
-            .ok_or("Role ID is required")?;
-        let emoji = role_data
-            .get("emoji")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .trim();
-        if let Some(label) = role_data
-            .get("label")
-            .and_then(|v| v.as_str())
-            .map(str::trim)
-            .filter(|s| !s.is_empty())
-        {
+        if let (Some(role_id), Some(label)) = (
+            role_data.get("role_id").and_then(|v| v.as_str()),
+            role_data
+                .get("label")
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty()),
+        ) {
             let _ =
                 SelfRoleLabel::upsert(&app_state.db, &guild_id.to_string(), role_id, label).await;
         }
-
-        if database::selfroles::SelfRoleRole::create(&app_state.db, config.id, role_id, emoji)
-            .await
-            .is_err()
-        {
-            return Err("Failed to save role configuration".to_string());
-        }
     }
 
     if let Some(message_id) = next_message_id {
@@ -575,6 +1326,83 @@ pub async fn update_selfrole(
     }
 }
 
+/// Re-posts a panel in another channel, e.g. after its channel was deleted,
+/// keeping its title, body and roles. The old message is removed if it still exists.
+pub async fn move_selfrole_channel(
+    app_state: &AppState,
+    guild_id: u64,
+    config_id: i64,
+    payload: &Value,
+) -> Result<Value, String> {
+    use serenity::all::CreateMessage;
+
+    let channel_id = payload
+        .get("channel_id")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or("Channel ID is required")?;
+    let channel_id_u64: u64 = channel_id
+        .parse()
+        .map_err(|_| "Invalid channel ID".to_string())?;
+
+    let mut config = SelfRoleConfig::get_by_id(&app_state.db, config_id)
+        .await
+        .map_err(|e| format!("Failed to get config: {}", e))?
+        .filter(|c| c.guild_id == guild_id.to_string())
+        .ok_or("Configuration not found")?;
+    if config.attached {
+        return Err("attached panels can't move to another channel".to_string());
+    }
+    if config.channel_id == channel_id {
+        return Err("the panel is already in that channel".to_string());
+    }
+    check_channel_panel_limit(app_state, guild_id, channel_id).await?;
+    check_selfrole_channel(app_state, guild_id, channel_id).await?;
+
+    let (embed, action_rows) = build_selfrole_panel(app_state, &config).await?;
+
+    let sent = app_state
+        .http
+        .send_message(
+            channel_id_u64.into(),
+            Vec::new(),
+            &CreateMessage::new().embed(embed).components(action_rows),
+        )
+        .await
+        .map_err(|e| format!("Failed to send message: {}", describe_discord_error(&e)))?;
+
+    // usually gone along with its channel, so failures here are expected
+    if let (Ok(old_channel), Some(Ok(old_message))) = (
+        config.channel_id.parse::<u64>(),
+        config.message_id.as_deref().map(str::parse::<u64>),
+    ) {
+        let _ = app_state
+            .http
+            .delete_message(
+                old_channel.into(),
+                old_message.into(),
+                Some("Self-role moved"),
+            )
+            .await;
+    }
+
+    let message_id = sent.id.to_string();
+    config
+        .move_to(&app_state.db, channel_id, &message_id)
+        .await
+        .map_err(|e| format!("Failed to update channel: {}", e))?;
+    crosspost_selfrole_message(app_state, guild_id, channel_id, sent.id).await;
+
+    Ok(json!({
+        "success": true,
+        "message": "Self-role moved successfully",
+        "id": config.id,
+        "channel_id": config.channel_id,
+        "message_id": message_id,
+    }))
+}
+
 /// Delete a self-role configuration
 pub async fn delete_selfrole(
     app_state: &AppState,
@@ -600,14 +1428,28 @@ pub async fn delete_selfrole(
         let message_id_u64: u64 = message_id
             .parse()
             .map_err(|_| "Invalid message ID".to_string())?;
-        let _ = app_state
-            .http
-            .delete_message(
-                channel_id_u64.into(),
-                message_id_u64.into(),
-                Some("Self-role deleted"),
-            )
-            .await;
+        if config.attached {
+            // the message predates the panel, so strip the buttons and leave it be
+            let edit = serenity::builder::EditMessage::new().components(Vec::new());
+            let _ = app_state
+                .http
+                .edit_message(
+                    channel_id_u64.into(),
+                    message_id_u64.into(),
+                    &edit,
+                    Vec::new(),
+                )
+                .await;
+        } else {
+            let _ = app_state
+                .http
+                .delete_message(
+                    channel_id_u64.into(),
+                    message_id_u64.into(),
+                    Some("Self-role deleted"),
+                )
+                .await;
+        }
     }
 
     // Delete the configuration by message_id if available, otherwise by config_id
@@ -617,19 +1459,225 @@ pub async fn delete_selfrole(
         config.delete(&app_state.db).await.map(|_| true)
     };
 
-    delete_result.map_err(|e| format!("Failed to delete configuration: {}", e))?;
+    delete_result.map_err(|e| format!("Failed to delete configuration: {}", e))?;
+
+    Ok(json!({
+        "success": true,
+        "message": "Self-role message deleted successfully"
+    }))
+}
+
+const MAX_SELFROLE_POLICY_EMOJIS: usize = 100;
+
+/// Get the guild's self-role emoji policy; `mode` is null when none is set
+pub async fn get_selfrole_emoji_policy(
+    app_state: &AppState,
+    guild_id: u64,
+) -> Result<Value, String> {
+    let policy = SelfRoleEmojiPolicy::get(&app_state.db, &guild_id.to_string())
+        .await
+        .map_err(|e| format!("DB error: {}", e))?;
+
+    Ok(json!({
+        "success": true,
+        "mode": policy.as_ref().map(|p| p.mode.clone()),
+        "emojis": policy.map(|p| p.emoji_list()).unwrap_or_default(),
+    }))
+}
+
+/// Set or clear (`mode: null`) the guild's self-role emoji policy
+pub async fn update_selfrole_emoji_policy(
+    app_state: &AppState,
+    guild_id: u64,
+    payload: &Value,
+) -> Result<Value, String> {
+    let guild_id_str = guild_id.to_string();
+
+    let Some(mode) = payload.get("mode").and_then(|v| v.as_str()) else {
+        SelfRoleEmojiPolicy::delete(&app_state.db, &guild_id_str)
+            .await
+            .map_err(|e| format!("DB error: {}", e))?;
+        return Ok(json!({ "success": true, "mode": null, "emojis": [] }));
+    };
+
+    if mode != "allow" && mode != "deny" {
+        return Err("mode must be 'allow' or 'deny'".to_string());
+    }
+
+    let emojis: Vec<String> = payload
+        .get("emojis")
+        .and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|e| e.as_str())
+                .map(|e| e.trim().to_string())
+                .filter(|e| !e.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if emojis.len() > MAX_SELFROLE_POLICY_EMOJIS {
+        return Err(format!(
+            "maximum of {} emojis per policy",
+            MAX_SELFROLE_POLICY_EMOJIS
+        ));
+    }
+
+    SelfRoleEmojiPolicy::upsert(&app_state.db, &guild_id_str, mode, &emojis)
+        .await
+        .map_err(|e| format!("DB error: {}", e))?;
+
+    get_selfrole_emoji_policy(app_state, guild_id).await
+}
+
+/// Get the guild's self-role panel settings
+pub async fn get_selfrole_settings(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    let settings = SelfRoleSettings::get_or_default(&app_state.db, &guild_id.to_string())
+        .await
+        .map_err(db_err)?;
+    Ok(json!({
+        "success": true,
+        "show_updated": settings.show_updated,
+        "show_cooldown": settings.show_cooldown,
+        "feedback_mode": settings.feedback().as_str(),
+        "crosspost": settings.crosspost,
+        "default_cooldown_secs": settings.default_cooldown_secs
+    }))
+}
+
+/// Update the guild's self-role panel settings. Panels pick changes up the
+/// next time they are posted or edited.
+pub async fn update_selfrole_settings(
+    app_state: &AppState,
+    guild_id: u64,
+    payload: &Value,
+) -> Result<Value, String> {
+    let mut settings = SelfRoleSettings::get_or_default(&app_state.db, &guild_id.to_string())
+        .await
+        .map_err(db_err)?;
+    if let Some(show_updated) = payload.get("show_updated") {
+        settings.show_updated = show_updated
+            .as_bool()
+            .ok_or("show_updated must be a boolean")?;
+    }
+    if let Some(show_cooldown) = payload.get("show_cooldown") {
+        settings.show_cooldown = show_cooldown
+            .as_bool()
+            .ok_or("show_cooldown must be a boolean")?;
+    }
+    if let Some(secs) = payload.get("default_cooldown_secs") {
+        settings.default_cooldown_secs = parse_selfrole_cooldown(secs, "default_cooldown_secs")?;
+    }
+    if let Some(crosspost) = payload.get("crosspost") {
+        settings.crosspost = crosspost.as_bool().ok_or("crosspost must be a boolean")?;
+    }
+    if let Some(mode) = payload.get("feedback_mode") {
+        let mode = mode
+            .as_str()
+            .and_then(SelfRoleFeedback::parse)
+            .ok_or("feedback_mode must be reply or react")?;
+        settings.feedback_mode = mode.as_str().to_string();
+    }
+    settings.upsert(&app_state.db).await.map_err(db_err)?;
+    get_selfrole_settings(app_state, guild_id).await
+}
+
+/// Panel footer text. With `show_updated` the embed also carries a timestamp,
+/// which discord renders after the footer in each viewer's local time; a
+/// `<t:..>` tag would show up as raw text there, since footers skip markdown.
+/// A non-zero `cooldown_secs` adds how long members wait between clicks.
+pub fn selfrole_footer_text(
+    selection_type: &str,
+    show_updated: bool,
+    cooldown_secs: Option<u64>,
+) -> String {
+    let mode = match selection_type {
+        "multiple" => "Multiple roles",
+        "radio" => "Single role",
+        _ => "",
+    };
+    let cooldown = cooldown_secs
+        .filter(|&secs| secs > 0)
+        .map(|secs| format!("Cooldown: {}", crate::utils::format_duration(secs)))
+        .unwrap_or_default();
+    let updated = if show_updated { "last updated" } else { "" };
+    [mode, cooldown.as_str(), updated]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// The custom_id of a panel's role button: `selfrole_{config_id}_{role_id}`.
+pub fn selfrole_custom_id(config_id: i64, role_id: &str) -> String {
+    crate::utils::custom_id("selfrole", &[&config_id, &role_id])
+}
+
+/// Embed and buttons for a saved panel, built the way it's posted in its channel.
+pub async fn build_selfrole_panel(
+    app_state: &AppState,
+    config: &database::selfroles::SelfRoleConfig,
+) -> Result<
+    (
+        serenity::all::CreateEmbed,
+        Vec<serenity::all::CreateActionRow>,
+    ),
+    String,
+> {
+    let guild_id: u64 = config
+        .guild_id
+        .parse()
+        .map_err(|_| "Invalid guild ID".to_string())?;
+    let roles: Vec<Value> = config
+        .get_roles(&app_state.db)
+        .await
+        .map_err(db_err)?
+        .iter()
+        .map(|r| json!({ "role_id": r.role_id, "emoji": r.emoji }))
+        .collect();
+    Ok(build_selfrole_embed_and_components(
+        app_state,
+        guild_id,
+        config,
+        &config.title,
+        &config.body,
+        &config.selection_type,
+        &roles,
+    )
+    .await)
+}
+
+/// Runs when the bot leaves a guild. Its panels are archived for
+/// `SELFROLE_ARCHIVE_RETENTION_DAYS` so a rejoin can bring them back, or
+/// deleted straight away when that's 0.
+pub async fn archive_guild_selfroles(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    let guild_id = guild_id.to_string();
+    if app_state.config.selfrole_archive_retention_days == 0 {
+        let deleted = SelfRoleConfig::delete_by_guild(&app_state.db, &guild_id)
+            .await
+            .map_err(db_err)?;
+        return Ok(json!({ "archived": 0, "deleted": deleted }));
+    }
+    let archived = SelfRoleConfig::archive_guild(&app_state.db, &guild_id)
+        .await
+        .map_err(db_err)?;
+    Ok(json!({ "archived": archived, "deleted": 0 }))
+}
 
-    Ok(json!({
-        "success": true,
-        "message": "Self-role message deleted successfully"
-    }))
+/// Runs when the bot joins a guild, restoring panels archived when it left.
+/// Anything past retention has already been purged by the cleanup task.
+pub async fn restore_guild_selfroles(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    let restored = SelfRoleConfig::restore_guild(&app_state.db, &guild_id.to_string())
+        .await
+        .map_err(db_err)?;
+    Ok(json!({ "restored": restored }))
 }
 
 // Helper functions
 
 async fn deploy_selfrole_message(
     app_state: &AppState,
-    config: &database::selfroles::SelfRoleConfig,
+    config: &mut database::selfroles::SelfRoleConfig,
     guild_id: u64,
     roles: &[Value],
     _user_id: u64,
@@ -639,7 +1687,7 @@ async fn deploy_selfrole_message(
     let (embed, action_rows) = build_selfrole_embed_and_components(
         app_state,
         guild_id,
-        config.id,
+        config,
         &config.title,
         &config.body,
         &config.selection_type,
@@ -659,15 +1707,26 @@ async fn deploy_selfrole_message(
         .await
     {
         Ok(sent_message) => {
-            // Update the config with the message_id
             let message_id = sent_message.id.to_string();
-            let _ = sqlx::query(
-                "UPDATE selfrole_configs SET message_id = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
-            )
-            .bind(&message_id)
-            .bind(config.id)
-            .execute(app_state.db.as_ref())
-            .await;
+            if let Err(e) = config.update_message_id(&app_state.db, &message_id).await {
+                // without its message id the panel's buttons can't find their
+                // config, so take the message back down rather than leave it dead
+                let _ = app_state
+                    .http
+                    .delete_message(
+                        channel_id_u64.into(),
+                        sent_message.id,
+                        Some("Self-role setup failed"),
+                    )
+                    .await;
+                let _ = config.delete(&app_state.db).await;
+                return Err(match e.downcast_ref::<MessageIdTaken>() {
+                    Some(taken) => format!("Failed to save panel: {}", taken),
+                    None => format!("Failed to save message ID: {}", e),
+                });
+            }
+            crosspost_selfrole_message(app_state, guild_id, &config.channel_id, sent_message.id)
+                .await;
 
             Ok(json!({
                 "success": true,
@@ -676,9 +1735,12 @@ async fn deploy_selfrole_message(
                 "id": config.id
             }))
         }
-        Err(_) => {
+        Err(e) => {
             let _ = config.delete(&app_state.db).await;
-            Err("Failed to send message".to_string())
+            Err(format!(
+                "Failed to send message: {}",
+                describe_discord_error(&e)
+            ))
         }
     }
 }
@@ -686,7 +1748,7 @@ async fn deploy_selfrole_message(
 async fn build_selfrole_embed_and_components(
     app_state: &AppState,
     guild_id: u64,
-    config_id: i64,
+    config: &SelfRoleConfig,
     title: &str,
     body: &str,
     selection_type: &str,
@@ -699,19 +1761,28 @@ async fn build_selfrole_embed_and_components(
         ButtonStyle, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
     };
 
-    let footer_text = match selection_type {
-        "multiple" => "Multiple roles",
-        "radio" => "Single role",
-        _ => "",
-    };
+    let guild_id_str = guild_id.to_string();
+    let settings = SelfRoleSettings::get_or_default(&app_state.db, &guild_id_str)
+        .await
+        .unwrap_or_default();
+    let show_updated = settings.show_updated;
+    let cooldown_secs = settings
+        .show_cooldown
+        .then(|| config.cooldown_secs.max(0) as u64);
 
-    let embed = CreateEmbed::new()
+    let mut embed = CreateEmbed::new()
         .title(title)
         .description(body)
         .colour(crate::utils::get_embed_color(app_state, Some(guild_id)).await)
-        .footer(CreateEmbedFooter::new(footer_text));
+        .footer(CreateEmbedFooter::new(selfrole_footer_text(
+            selection_type,
+            show_updated,
+            cooldown_secs,
+        )));
+    if show_updated {
+        embed = embed.timestamp(serenity::all::Timestamp::now());
+    }
 
-    let guild_id_str = guild_id.to_string();
     let mut action_rows = Vec::new();
     let mut current_row = Vec::new();
 
@@ -747,7 +1818,7 @@ async fn build_selfrole_embed_and_components(
         };
 
         let button_label = format_selfrole_button_label(emoji, &label);
-        let button = CreateButton::new(format!("selfrole_{}_{}", config_id, role_id))
+        let button = CreateButton::new(selfrole_custom_id(config.id, role_id))
             .label(button_label)
             .style(ButtonStyle::Primary);
 
@@ -772,27 +1843,159 @@ pub async fn get_welcome_goodbye_config(
     app_state: &AppState,
     guild_id: u64,
 ) -> Result<Value, String> {
-    use crate::database::welcome_goodbye::WelcomeGoodbyeConfig;
+    use crate::database::welcome_goodbye::{MemberMessageKind, WelcomeGoodbyeConfig};
 
     let guild_id_str = guild_id.to_string();
     let config = WelcomeGoodbyeConfig::get_config(&app_state.db, &guild_id_str)
         .await
         .map_err(|e| format!("Failed to get config: {}", e))?;
 
+    let welcome_variants = WelcomeGoodbyeConfig::get_variants(
+        &app_state.db,
+        &guild_id_str,
+        MemberMessageKind::Welcome,
+    )
+    .await
+    .map_err(db_err)?;
+    let goodbye_variants = WelcomeGoodbyeConfig::get_variants(
+        &app_state.db,
+        &guild_id_str,
+        MemberMessageKind::Goodbye,
+    )
+    .await
+    .map_err(db_err)?;
+
     let default_color = app_state.config.web.embed.default_color;
-    if let Some(config) = config {
-        Ok(json!({
-            "success": true,
-            "config": config,
-            "default_color": default_color
-        }))
-    } else {
-        Ok(json!({
-            "success": true,
-            "config": WelcomeGoodbyeConfig::default(),
-            "default_color": default_color
-        }))
+    Ok(json!({
+        "success": true,
+        "config": config.unwrap_or_default(),
+        "welcome_variants": welcome_variants,
+        "goodbye_variants": goodbye_variants,
+        "default_color": default_color
+    }))
+}
+
+const MAX_WELCOME_VARIANTS: usize = 20;
+
+/// Reads a `{kind}_variants` array from the payload, dropping blank entries.
+fn parse_welcome_variants(payload: &Value, field: &str) -> Result<Option<Vec<String>>, String> {
+    let Some(value) = payload.get(field) else {
+        return Ok(None);
+    };
+    let items = value
+        .as_array()
+        .ok_or_else(|| format!("{} must be an array of strings", field))?;
+    let mut variants = Vec::with_capacity(items.len());
+    for item in items {
+        let content = item
+            .as_str()
+            .ok_or_else(|| format!("{} must be an array of strings", field))?
+            .trim();
+        if content.is_empty() {
+            continue;
+        }
+        // a variant can stand in for plain text, so hold it to the message limit
+        validate_content_lengths(&[(Some(content), 2000, field)])?;
+        variants.push(content.to_string());
+    }
+    if variants.len() > MAX_WELCOME_VARIANTS {
+        return Err(format!(
+            "{} can hold at most {} messages",
+            field, MAX_WELCOME_VARIANTS
+        ));
+    }
+    Ok(Some(variants))
+}
+
+/// Checks a welcome/goodbye config against Discord's limits. Every error
+/// starts with the offending payload field so the dashboard can highlight it.
+pub fn validate_message_config(
+    config: &crate::database::welcome_goodbye::WelcomeGoodbyeConfig,
+) -> Result<(), String> {
+    let lengths: [(&Option<String>, usize, &str); 8] = [
+        (
+            &config.welcome_message_content,
+            2000,
+            "welcome_message_content",
+        ),
+        (
+            &config.goodbye_message_content,
+            2000,
+            "goodbye_message_content",
+        ),
+        (&config.welcome_embed_title, 256, "welcome_embed_title"),
+        (&config.goodbye_embed_title, 256, "goodbye_embed_title"),
+        (
+            &config.welcome_embed_description,
+            4096,
+            "welcome_embed_description",
+        ),
+        (
+            &config.goodbye_embed_description,
+            4096,
+            "goodbye_embed_description",
+        ),
+        (&config.welcome_embed_footer, 2048, "welcome_embed_footer"),
+        (&config.goodbye_embed_footer, 2048, "goodbye_embed_footer"),
+    ];
+    for (value, max, field) in lengths {
+        if let Some(value) = value
+            && value.chars().count() > max
+        {
+            return Err(format!("{} exceeds {} characters", field, max));
+        }
+    }
+
+    let urls: [(&Option<String>, &str); 4] = [
+        (&config.welcome_embed_thumbnail, "welcome_embed_thumbnail"),
+        (&config.welcome_embed_image, "welcome_embed_image"),
+        (&config.goodbye_embed_thumbnail, "goodbye_embed_thumbnail"),
+        (&config.goodbye_embed_image, "goodbye_embed_image"),
+    ];
+    for (url, field) in urls {
+        if let Some(url) = url
+            && !url.is_empty()
+            && !crate::utils::is_valid_https_url(url)
+        {
+            return Err(format!("{} must be an https URL with a public host", field));
+        }
+    }
+
+    // with rotation on, the variants stand in for the description
+    let embeds = [
+        (
+            "welcome",
+            config.welcome_enabled,
+            &config.welcome_message_type,
+            &config.welcome_rotation,
+            &config.welcome_embed_title,
+            &config.welcome_embed_description,
+        ),
+        (
+            "goodbye",
+            config.goodbye_enabled,
+            &config.goodbye_message_type,
+            &config.goodbye_rotation,
+            &config.goodbye_embed_title,
+            &config.goodbye_embed_description,
+        ),
+    ];
+    for (kind, enabled, message_type, rotation, title, description) in embeds {
+        let is_blank = |v: &Option<String>| v.as_deref().is_none_or(|v| v.trim().is_empty());
+        if enabled
+            && message_type == "embed"
+            && rotation == "off"
+            && is_blank(title)
+            && is_blank(description)
+        {
+            return Err(format!(
+                "{}_embed_title: an embed needs a title or a description",
+                kind
+            ));
+        }
     }
+
+    Ok(())
 }
 
 /// Update welcome/goodbye configuration for a guild
@@ -801,9 +2004,80 @@ pub async fn update_welcome_goodbye_config(
     guild_id: u64,
     payload: &Value,
 ) -> Result<Value, String> {
-    use crate::database::welcome_goodbye::WelcomeGoodbyeConfig;
+    merge_welcome_goodbye_config(app_state, guild_id, payload).await?;
+
+    Ok(json!({
+        "success": true,
+        "message": "configuration saved successfully"
+    }))
+}
+
+/// Apply only the fields present in `payload`, returning the merged config
+pub async fn patch_welcome_goodbye_config(
+    app_state: &AppState,
+    guild_id: u64,
+    payload: &Value,
+) -> Result<Value, String> {
+    let config = merge_welcome_goodbye_config(app_state, guild_id, payload).await?;
+
+    Ok(json!({
+        "success": true,
+        "message": "configuration saved successfully",
+        "config": config
+    }))
+}
+
+/// Checks each channel id in the payload belongs to `guild_id`, so a config
+/// can't point at a channel in some other server.
+async fn check_welcome_goodbye_channels(
+    app_state: &AppState,
+    guild_id: u64,
+    payload: &Value,
+) -> Result<(), String> {
+    for field in ["welcome_channel_id", "goodbye_channel_id"] {
+        let Some(channel_id) = payload
+            .get(field)
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+        else {
+            continue;
+        };
+        let channel_id: u64 = channel_id
+            .parse()
+            .map_err(|_| format!("{}: invalid channel id", field))?;
+        let channel = app_state
+            .http
+            .get_channel(channel_id.into())
+            .await
+            .map_err(|e| format!("{}: {}", field, describe_discord_error(&e)))?;
+        let channel_guild = channel.guild().map(|c| c.guild_id);
+        if let Some(problem) = channel_guild_problem(channel_guild, GuildId::new(guild_id)) {
+            return Err(format!("{}: {}", field, problem));
+        }
+    }
+    Ok(())
+}
+
+/// Reads the stored config, overlays the fields present in `payload` and
+/// writes it back in one `BEGIN IMMEDIATE` transaction, so a concurrent save
+/// can't slip in between the read and the write and get overwritten.
+async fn merge_welcome_goodbye_config(
+    app_state: &AppState,
+    guild_id: u64,
+    payload: &Value,
+) -> Result<crate::database::welcome_goodbye::WelcomeGoodbyeConfig, String> {
+    use crate::database::welcome_goodbye::{MemberMessageKind, WelcomeGoodbyeConfig};
+    use crate::utils::welcome_goodbye::{NamePlaceholderSource, RotationMode};
+
+    // before the write lock, since it talks to discord
+    check_welcome_goodbye_channels(app_state, guild_id, payload).await?;
 
-    let mut config = WelcomeGoodbyeConfig::get_config(&app_state.db, &guild_id.to_string())
+    let mut tx = app_state
+        .db
+        .begin_with("BEGIN IMMEDIATE")
+        .await
+        .map_err(db_err)?;
+    let mut config = WelcomeGoodbyeConfig::get_config_in(&mut tx, &guild_id.to_string())
         .await
         .map_err(|e| format!("Failed to get config: {}", e))?
         .unwrap_or_else(|| WelcomeGoodbyeConfig {
@@ -902,64 +2176,53 @@ pub async fn update_welcome_goodbye_config(
     {
         config.goodbye_embed_timestamp = v;
     }
-
-    // validate URL fields
-    for url in [
-        &config.welcome_embed_thumbnail,
-        &config.welcome_embed_image,
-        &config.goodbye_embed_thumbnail,
-        &config.goodbye_embed_image,
-    ]
-    .into_iter()
-    .flatten()
+    if let Some(v) = payload
+        .get("welcome_after_screening")
+        .and_then(|v| v.as_bool())
     {
-        if !crate::utils::is_valid_https_url(url) {
-            return Err(format!(
-                "invalid URL '{}': must be https with a public host",
-                url
-            ));
+        config.welcome_after_screening = v;
+    }
+    if let Some(v) = payload
+        .get("name_placeholder_source")
+        .and_then(|v| v.as_str())
+    {
+        let source = NamePlaceholderSource::parse(v)
+            .ok_or("name_placeholder_source must be username, global_name, or nickname")?;
+        config.name_placeholder_source = source.as_str().to_string();
+    }
+    for (field, target) in [
+        ("welcome_rotation", &mut config.welcome_rotation),
+        ("goodbye_rotation", &mut config.goodbye_rotation),
+    ] {
+        if let Some(v) = payload.get(field).and_then(|v| v.as_str()) {
+            let mode = RotationMode::parse(v)
+                .ok_or_else(|| format!("{} must be off, random, or round_robin", field))?;
+            *target = mode.as_str().to_string();
         }
     }
+    let welcome_variants = parse_welcome_variants(payload, "welcome_variants")?;
+    let goodbye_variants = parse_welcome_variants(payload, "goodbye_variants")?;
 
-    // validate content lengths (Discord limits)
-    let checks: &[(&Option<String>, usize, &str)] = &[
-        (
-            &config.welcome_message_content,
-            2000,
-            "welcome_message_content",
-        ),
-        (
-            &config.goodbye_message_content,
-            2000,
-            "goodbye_message_content",
-        ),
-        (&config.welcome_embed_title, 256, "welcome_embed_title"),
-        (&config.goodbye_embed_title, 256, "goodbye_embed_title"),
-        (
-            &config.welcome_embed_description,
-            4096,
-            "welcome_embed_description",
-        ),
-        (
-            &config.goodbye_embed_description,
-            4096,
-            "goodbye_embed_description",
-        ),
-        (&config.welcome_embed_footer, 2048, "welcome_embed_footer"),
-        (&config.goodbye_embed_footer, 2048, "goodbye_embed_footer"),
-    ];
-    for (field, max, name) in checks {
-        validate_content_lengths(&[((*field).as_deref(), *max, *name)])?;
-    }
+    validate_message_config(&config)?;
 
-    WelcomeGoodbyeConfig::upsert_config(&app_state.db, &config)
+    WelcomeGoodbyeConfig::upsert_config_in(&mut tx, &config)
         .await
         .map_err(|e| format!("Failed to save config: {}", e))?;
 
-    Ok(json!({
-        "success": true,
-        "message": "configuration saved successfully"
-    }))
+    for (kind, variants) in [
+        (MemberMessageKind::Welcome, welcome_variants),
+        (MemberMessageKind::Goodbye, goodbye_variants),
+    ] {
+        if let Some(variants) = variants {
+            WelcomeGoodbyeConfig::set_variants_in(&mut tx, &config.guild_id, kind, &variants)
+                .await
+                .map_err(|e| format!("Failed to save {} variants: {}", kind.as_str(), e))?;
+        }
+    }
+
+    tx.commit().await.map_err(db_err)?;
+
+    Ok(config)
 }
 
 // MediaOnly functions
@@ -969,13 +2232,199 @@ pub async fn list_mediaonly_configs(app_state: &AppState, guild_id: u64) -> Resu
     use crate::database::mediaonly::MediaOnlyConfig;
 
     let guild_id_str = guild_id.to_string();
-    let configs = MediaOnlyConfig::get_by_guild(&app_state.db, &guild_id_str)
+    let configs = MediaOnlyConfig::get_by_guild(&app_state.db, &guild_id_str)
+        .await
+        .map_err(|e| format!("Failed to get configs: {}", e))?;
+
+    Ok(json!({
+        "success": true,
+        "configs": configs
+    }))
+}
+
+/// Per-channel deletion stats over the configured retention window
+pub async fn get_mediaonly_stats(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    use crate::database::mediaonly::MediaOnlyStats;
+
+    let days = app_state.config.mediaonly_stats_retention_days;
+    if days <= 0 {
+        return Ok(json!({ "success": true, "enabled": false, "days": 0, "stats": [] }));
+    }
+
+    let since = chrono::Utc::now().date_naive() - chrono::Days::new(days as u64);
+    let stats = MediaOnlyStats::get_by_guild(&app_state.db, &guild_id.to_string(), since)
+        .await
+        .map_err(db_err)?;
+
+    Ok(json!({
+        "success": true,
+        "enabled": true,
+        "days": days,
+        "stats": stats
+    }))
+}
+
+/// Errors when configuring `channel_id` would take the guild past the media-only
+/// channel cap. Channels that are already configured and the bot owner pass.
+pub async fn check_mediaonly_channel_limit(
+    app_state: &AppState,
+    guild_id: u64,
+    channel_id: &str,
+    user_id: u64,
+) -> Result<(), String> {
+    use crate::database::mediaonly::MediaOnlyConfig;
+
+    if user_id == app_state.config.discord.bot_owner {
+        return Ok(());
+    }
+    let guild_id_str = guild_id.to_string();
+    if MediaOnlyConfig::get_by_channel(&app_state.db, &guild_id_str, channel_id)
+        .await
+        .map_err(db_err)?
+        .is_some()
+    {
+        return Ok(());
+    }
+    let max = app_state.config.mediaonly_max_channels;
+    let count = MediaOnlyConfig::count_by_guild(&app_state.db, &guild_id_str)
+        .await
+        .map_err(db_err)?;
+    if count >= max {
+        return Err(format!(
+            "maximum of {} media-only channels per server reached",
+            max
+        ));
+    }
+    Ok(())
+}
+
+/// The `allow_*` toggles of a media-only payload, in column order. Missing ones default to on.
+fn mediaonly_flags(payload: &Value) -> [bool; 4] {
+    [
+        "allow_links",
+        "allow_attachments",
+        "allow_gifs",
+        "allow_stickers",
+    ]
+    .map(|key| payload.get(key).and_then(|v| v.as_bool()).unwrap_or(true))
+}
+
+/// Most channels one bulk request may list.
+pub const MEDIAONLY_BULK_MAX: usize = 100;
+
+/// Configures several media-only channels in one request. Each channel must be
+/// one of the guild's; see [`apply_mediaonly_bulk`] for how failures are handled.
+pub async fn bulk_configure_mediaonly(
+    app_state: &AppState,
+    guild_id: u64,
+    user_id: u64,
+    payload: &Value,
+) -> Result<Value, String> {
+    let channels = app_state
+        .http
+        .get_channels(GuildId::new(guild_id))
+        .await
+        .map_err(|e| format!("Failed to get channels: {}", describe_discord_error(&e)))?;
+    let guild_channels: Vec<String> = channels.iter().map(|c| c.id.to_string()).collect();
+    apply_mediaonly_bulk(app_state, guild_id, user_id, payload, &guild_channels).await
+}
+
+/// Validates each `{ channel_id, allow_* }` entry in `payload.channels` against
+/// `guild_channels` and the channel cap, then saves every valid one in a single
+/// transaction. Invalid entries are skipped and reported in `results` rather than
+/// failing the batch; a database error rolls back the whole batch.
+pub async fn apply_mediaonly_bulk(
+    app_state: &AppState,
+    guild_id: u64,
+    user_id: u64,
+    payload: &Value,
+    guild_channels: &[String],
+) -> Result<Value, String> {
+    use crate::database::mediaonly::MediaOnlyConfig;
+    use std::collections::HashSet;
+
+    let entries = payload
+        .get("channels")
+        .and_then(|v| v.as_array())
+        .ok_or("channels must be a list")?;
+    if entries.is_empty() {
+        return Err("channels must not be empty".to_string());
+    }
+    if entries.len() > MEDIAONLY_BULK_MAX {
+        return Err(format!(
+            "at most {} channels can be configured at once",
+            MEDIAONLY_BULK_MAX
+        ));
+    }
+
+    let guild_id_str = guild_id.to_string();
+    let existing: HashSet<String> = MediaOnlyConfig::get_by_guild(&app_state.db, &guild_id_str)
         .await
-        .map_err(|e| format!("Failed to get configs: {}", e))?;
+        .map_err(db_err)?
+        .into_iter()
+        .map(|c| c.channel_id)
+        .collect();
+    let exempt = user_id == app_state.config.discord.bot_owner;
+    let max = app_state.config.mediaonly_max_channels;
+    let mut configured = existing.len() as i64;
+
+    let mut seen = HashSet::new();
+    let mut results = Vec::with_capacity(entries.len());
+    let mut valid = Vec::new();
+    for entry in entries {
+        let channel_id = entry
+            .get("channel_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let problem = if channel_id.parse::<u64>().is_err() {
+            Some("invalid channel id".to_string())
+        } else if !seen.insert(channel_id) {
+            Some("channel listed more than once".to_string())
+        } else if !guild_channels.iter().any(|c| c == channel_id) {
+            Some("that channel isn't in this server".to_string())
+        } else if !exempt && !existing.contains(channel_id) && configured >= max {
+            Some(format!(
+                "maximum of {} media-only channels per server reached",
+                max
+            ))
+        } else {
+            None
+        };
+
+        match problem {
+            Some(error) => {
+                results.push(json!({ "channel_id": channel_id, "success": false, "error": error }))
+            }
+            None => {
+                if !existing.contains(channel_id) {
+                    configured += 1;
+                }
+                results.push(json!({ "channel_id": channel_id, "success": true }));
+                valid.push((channel_id, mediaonly_flags(entry)));
+            }
+        }
+    }
+
+    let mut tx = app_state.db.begin().await.map_err(db_err)?;
+    for (channel_id, [links, attachments, gifs, stickers]) in &valid {
+        MediaOnlyConfig::upsert_with_config_in(
+            &mut tx,
+            &guild_id_str,
+            channel_id,
+            *links,
+            *attachments,
+            *gifs,
+            *stickers,
+        )
+        .await
+        .map_err(|e| format!("Failed to save config: {}", e))?;
+    }
+    tx.commit().await.map_err(db_err)?;
 
     Ok(json!({
         "success": true,
-        "configs": configs
+        "configured": valid.len(),
+        "results": results,
     }))
 }
 
@@ -983,27 +2432,15 @@ pub async fn list_mediaonly_configs(app_state: &AppState, guild_id: u64) -> Resu
 pub async fn create_or_update_mediaonly_config(
     app_state: &AppState,
     guild_id: u64,
+    user_id: u64,
     channel_id: &str,
     payload: &Value,
 ) -> Result<Value, String> {
     use crate::database::mediaonly::MediaOnlyConfig;
 
-    let allow_links = payload
-        .get("allow_links")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(true);
-    let allow_attachments = payload
-        .get("allow_attachments")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(true);
-    let allow_gifs = payload
-        .get("allow_gifs")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(true);
-    let allow_stickers = payload
-        .get("allow_stickers")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(true);
+    check_mediaonly_channel_limit(app_state, guild_id, channel_id, user_id).await?;
+
+    let [allow_links, allow_attachments, allow_gifs, allow_stickers] = mediaonly_flags(payload);
 
     let guild_id_str = guild_id.to_string();
     MediaOnlyConfig::upsert_with_config(
@@ -1216,6 +2653,133 @@ pub async fn get_guild_about(app_state: &AppState, guild_id: u64) -> Result<Valu
     }))
 }
 
+/// What each bot module has configured for a guild, for the dashboard landing page.
+/// Reads only the database, so it stays cheap to load on every visit.
+pub async fn get_guild_overview(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    use crate::database::reminders::{CustomReminder, ReminderConfig, ReminderType};
+    use crate::database::{
+        ai_usage, command_aliases::CommandAlias, mediaonly::MediaOnlyConfig,
+        scheduled_messages::ScheduledMessage, uwufy::UwufyToggle,
+        welcome_goodbye::WelcomeGoodbyeConfig,
+    };
+
+    let db = app_state.db.as_ref();
+    let guild_id_str = guild_id.to_string();
+    let gid = guild_id_str.as_str();
+
+    let selfroles = SelfRoleConfig::get_by_guild(db, gid)
+        .await
+        .map_err(db_err)?;
+    let emoji_policy = SelfRoleEmojiPolicy::get(db, gid).await.map_err(db_err)?;
+    let wg = WelcomeGoodbyeConfig::get_config(db, gid)
+        .await
+        .map_err(db_err)?;
+    let mediaonly = MediaOnlyConfig::get_by_guild(db, gid)
+        .await
+        .map_err(db_err)?;
+    let reminders = ReminderConfig::get_by_guild(db, gid)
+        .await
+        .map_err(db_err)?;
+    let custom_reminders = CustomReminder::get_by_guild(db, gid)
+        .await
+        .map_err(db_err)?;
+    let scheduled = ScheduledMessage::count_by_guild(db, gid)
+        .await
+        .map_err(db_err)?;
+    let uwufy = UwufyToggle::get_enabled_in_guild(db, gid)
+        .await
+        .map_err(db_err)?;
+    let aliases = CommandAlias::count_for_guild(db, gid)
+        .await
+        .map_err(db_err)?;
+    let ai_tokens = ai_usage::tokens_since(db, gid, ai_usage::period_start(chrono::Utc::now()))
+        .await
+        .map_err(db_err)?;
+
+    let wysi_enabled = reminders
+        .iter()
+        .any(|r| r.reminder_type == ReminderType::Wysi && r.enabled);
+
+    Ok(json!({
+        "success": true,
+        "selfroles": {
+            "panels": selfroles.len(),
+            "emoji_policy": emoji_policy.map(|p| p.mode),
+        },
+        "welcome": {
+            "enabled": wg.as_ref().is_some_and(|c| c.welcome_enabled),
+            "channel_id": wg.as_ref().and_then(|c| c.welcome_channel_id.clone()),
+        },
+        "goodbye": {
+            "enabled": wg.as_ref().is_some_and(|c| c.goodbye_enabled),
+            "channel_id": wg.as_ref().and_then(|c| c.goodbye_channel_id.clone()),
+        },
+        "mediaonly": {
+            "channels": mediaonly.len(),
+            "enabled": mediaonly.iter().filter(|c| c.enabled).count(),
+        },
+        "reminders": {
+            "wysi_enabled": wysi_enabled,
+            "custom": custom_reminders.len(),
+            "custom_enabled": custom_reminders.iter().filter(|r| r.enabled).count(),
+        },
+        "scheduled_messages": scheduled,
+        "uwufy_members": uwufy.len(),
+        "command_aliases": aliases,
+        "ai": {
+            "enabled": app_state.config.llm.provider.is_some(),
+            "tokens_this_month": ai_tokens,
+            "budget": app_state.config.llm.guild_monthly_token_budget,
+        },
+    }))
+}
+
+/// Logs a dashboard session out. The Discord grant and stored token are shared
+/// by all of the user's sessions, so they're only dropped when this was the
+/// last one: the token goes to `revoke` so it dies on Discord's side too, and
+/// a failed revoke is only logged.
+pub async fn end_dashboard_session<F, Fut>(
+    app_state: &AppState,
+    session_id: &str,
+    user_id: &str,
+    revoke: F,
+) where
+    F: FnOnce(String) -> Fut,
+    Fut: std::future::Future<Output = Result<(), String>>,
+{
+    if let Err(e) = DashboardSession::delete(&app_state.db, session_id).await {
+        error!("delete session on logout: {}", e);
+        return;
+    }
+
+    match DashboardSession::count_active_for_user(&app_state.db, user_id).await {
+        Ok(0) => {}
+        Ok(_) => return,
+        Err(e) => {
+            warn!("count sessions for {} on logout: {}", user_id, e);
+            return;
+        }
+    }
+
+    let user = DashboardUser::get_by_user_id(&app_state.db, user_id)
+        .await
+        .ok()
+        .flatten();
+    let token = user.and_then(|u| u.oauth_token).and_then(|encrypted| {
+        crate::crypto::decrypt(&app_state.config.web.oauth_encryption_key_bytes, &encrypted)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+    });
+    if let Some(token) = token
+        && let Err(e) = revoke(token).await
+    {
+        warn!("oauth token revoke failed for {}: {}", user_id, e);
+    }
+    if let Err(e) = DashboardUser::clear_oauth_token(&app_state.db, user_id).await {
+        warn!("clear oauth token for {}: {}", user_id, e);
+    }
+}
+
 /// Fetches the user's and bot's guild lists in parallel, intersects them filtered by
 /// management permissions, updates the DB cache, and returns `(guilds, updated)`.
 pub async fn refresh_guild_cache(
@@ -2269,6 +3833,538 @@ pub async fn delete_custom_reminder(
     Ok(json!({ "success": true }))
 }
 
+// Scheduled message functions
+
+const MAX_SCHEDULED_MESSAGES_PER_GUILD: i64 = 25;
+
+/// Permissions the bot needs in a channel to post a scheduled message.
+const SCHEDULED_CHANNEL_PERMISSIONS: Permissions =
+    Permissions::VIEW_CHANNEL.union(Permissions::SEND_MESSAGES);
+
+/// Decides whether a scheduled message can go to a channel, given what discord
+/// reported about it. Returns the reason it can't, if any.
+pub fn scheduled_channel_problem(
+    kind: serenity::all::ChannelType,
+    channel_guild: GuildId,
+    guild_id: GuildId,
+    bot_permissions: Permissions,
+) -> Option<String> {
+    use serenity::all::ChannelType;
+
+    if let Some(problem) = channel_guild_problem(Some(channel_guild), guild_id) {
+        return Some(problem.to_string());
+    }
+    if !matches!(kind, ChannelType::Text | ChannelType::News) {
+        return Some("scheduled messages need a text or announcement channel".to_string());
+    }
+    missing_channel_permissions(SCHEDULED_CHANNEL_PERMISSIONS, bot_permissions)
+}
+
+fn scheduled_message_json(m: &crate::database::scheduled_messages::ScheduledMessage) -> Value {
+    json!({
+        "id": m.id,
+        "channel_id": m.channel_id,
+        "content": m.content,
+        "cron": m.cron_spec,
+        "timezone": m.timezone,
+        "next_run_at": m.next_run_at,
+        "enabled": m.enabled,
+        "created_by": m.created_by,
+    })
+}
+
+pub async fn list_scheduled_messages(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    use crate::database::scheduled_messages::ScheduledMessage;
+
+    let messages = ScheduledMessage::get_by_guild(&app_state.db, &guild_id.to_string())
+        .await
+        .map_err(|e| format!("DB error: {}", e))?;
+    let result: Vec<Value> = messages.iter().map(scheduled_message_json).collect();
+    Ok(json!({ "success": true, "scheduled_messages": result }))
+}
+
+/// Creates a one-off (`run_at`, `YYYY-MM-DD HH:MM`) or recurring (`cron`)
+/// post. Times are read in `timezone`, defaulting to the guild's timezone.
+pub async fn create_scheduled_message(
+    app_state: &AppState,
+    guild_id: u64,
+    user_id: &str,
+    payload: &Value,
+) -> Result<Value, String> {
+    use crate::database::guild_configs::GuildConfig;
+    use crate::database::scheduled_messages::ScheduledMessage;
+    use crate::utils::cron::{CronSpec, parse_local_datetime};
+
+    let guild_id_str = guild_id.to_string();
+
+    let count = ScheduledMessage::count_by_guild(&app_state.db, &guild_id_str)
+        .await
+        .map_err(|e| format!("DB error: {}", e))?;
+    if count >= MAX_SCHEDULED_MESSAGES_PER_GUILD {
+        return Err(format!(
+            "maximum of {} scheduled messages per guild reached",
+            MAX_SCHEDULED_MESSAGES_PER_GUILD
+        ));
+    }
+
+    let channel_id = payload
+        .get("channel_id")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .ok_or("channel_id is required")?;
+    let content = payload
+        .get("content")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty())
+        .ok_or("content is required")?;
+    let cron = payload
+        .get("cron")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty());
+    let run_at = payload
+        .get("run_at")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty());
+
+    validate_content_lengths(&[(Some(content), 2000, "content")])?;
+
+    let timezone = match payload.get("timezone").and_then(|v| v.as_str()) {
+        Some(tz) if !tz.is_empty() => tz.to_string(),
+        _ => {
+            GuildConfig::get_or_default(&app_state.db, &guild_id_str)
+                .await
+                .map_err(|e| format!("DB error: {}", e))?
+                .timezone
+        }
+    };
+    let tz: chrono_tz::Tz = timezone
+        .parse()
+        .map_err(|_| format!("invalid timezone: {}", timezone))?;
+
+    let now = chrono::Utc::now();
+    let next_run = match (cron, run_at) {
+        (Some(spec), None) => CronSpec::parse(spec)?
+            .next_after(now, tz)
+            .ok_or("cron spec never fires")?,
+        (None, Some(at)) => {
+            let at =
+                parse_local_datetime(at, tz).ok_or("invalid run_at, expected YYYY-MM-DD HH:MM")?;
+            if at <= now {
+                return Err("run_at must be in the future".to_string());
+            }
+            at
+        }
+        _ => return Err("exactly one of cron or run_at is required".to_string()),
+    };
+
+    let (channel, permissions) =
+        get_bot_channel_permissions(app_state, guild_id, channel_id).await?;
+    if let Some(problem) = scheduled_channel_problem(
+        channel.kind,
+        channel.guild_id,
+        GuildId::new(guild_id),
+        permissions,
+    ) {
+        return Err(problem);
+    }
+
+    let id = ScheduledMessage::create(
+        &app_state.db,
+        &guild_id_str,
+        channel_id,
+        content,
+        cron,
+        &timezone,
+        next_run.timestamp(),
+        user_id,
+    )
+    .await
+    .map_err(|e| format!("DB error: {}", e))?;
+
+    Ok(json!({ "success": true, "id": id, "next_run_at": next_run.timestamp() }))
+}
+
+pub async fn delete_scheduled_message(
+    app_state: &AppState,
+    guild_id: u64,
+    message_id: i64,
+) -> Result<Value, String> {
+    use crate::database::scheduled_messages::ScheduledMessage;
+
+    let existing = ScheduledMessage::get_by_id(&app_state.db, message_id)
+        .await
+        .map_err(|e| format!("DB error: {}", e))?
+        .ok_or("scheduled message not found")?;
+
+    if existing.guild_id != guild_id.to_string() {
+        return Err("scheduled message not found".to_string());
+    }
+
+    ScheduledMessage::delete(&app_state.db, message_id)
+        .await
+        .map_err(|e| format!("DB error: {}", e))?;
+
+    Ok(json!({ "success": true }))
+}
+
+// Guild data functions
+
+/// Delivery logs kept per reminder in an export; older ones are operational noise.
+const EXPORT_RECENT_LOGS: i64 = 100;
+
+fn db_err(e: impl std::fmt::Display) -> String {
+    format!("DB error: {}", e)
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Serialize error: {}", e))
+}
+
+/// Serializes `value` and adds `extra` fields to the resulting object.
+fn to_json_with<T: serde::Serialize>(value: T, extra: Value) -> Result<Value, String> {
+    let mut value = to_json(value)?;
+    if let (Some(obj), Value::Object(extra)) = (value.as_object_mut(), extra) {
+        obj.extend(extra);
+    }
+    Ok(value)
+}
+
+/// Everything the bot stores for a guild, grouped by module
+pub async fn export_guild_data(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    use crate::database::reminders::{
+        CustomReminder, CustomReminderLog, CustomReminderPingRole, CustomReminderSubscription,
+        ReminderConfig, ReminderLog, ReminderPingRole, ReminderSubscription,
+    };
+    use crate::database::{
+        command_aliases::CommandAlias,
+        guild_configs::GuildConfig,
+        mediaonly::{MediaOnlyConfig, MediaOnlyStats},
+        scheduled_messages::ScheduledMessage,
+        selfroles::SelfRoleCooldown,
+        uwufy::UwufyToggle,
+        welcome_goodbye::{MemberMessageKind, PendingWelcome, WelcomeGoodbyeConfig},
+    };
+
+    let db = &app_state.db;
+    let guild_id_str = guild_id.to_string();
+
+    let mut selfroles = Vec::new();
+    for config in SelfRoleConfig::get_by_guild(db, &guild_id_str)
+        .await
+        .map_err(db_err)?
+    {
+        let roles = config.get_roles(db).await.map_err(db_err)?;
+        selfroles.push(to_json_with(config, json!({ "roles": roles }))?);
+    }
+
+    let mut reminders = Vec::new();
+    for config in ReminderConfig::get_by_guild(db, &guild_id_str)
+        .await
+        .map_err(db_err)?
+    {
+        let ping_roles = ReminderPingRole::get_by_config(db, config.id)
+            .await
+            .map_err(db_err)?;
+        let subscriptions = ReminderSubscription::get_by_config(db, config.id)
+            .await
+            .map_err(db_err)?;
+        let recent_logs = ReminderLog::get_recent_by_config(db, config.id, EXPORT_RECENT_LOGS)
+            .await
+            .map_err(db_err)?;
+        reminders.push(to_json_with(
+            config,
+            json!({
+                "ping_roles": ping_roles,
+                "subscriptions": subscriptions,
+                "recent_logs": recent_logs,
+            }),
+        )?);
+    }
+
+    let welcome_goodbye = match WelcomeGoodbyeConfig::get_config(db, &guild_id_str)
+        .await
+        .map_err(db_err)?
+    {
+        Some(config) => {
+            let welcome_variants =
+                WelcomeGoodbyeConfig::get_variants(db, &guild_id_str, MemberMessageKind::Welcome)
+                    .await
+                    .map_err(db_err)?;
+            let goodbye_variants =
+                WelcomeGoodbyeConfig::get_variants(db, &guild_id_str, MemberMessageKind::Goodbye)
+                    .await
+                    .map_err(db_err)?;
+            to_json_with(
+                config,
+                json!({
+                    "welcome_variants": welcome_variants,
+                    "goodbye_variants": goodbye_variants,
+                }),
+            )?
+        }
+        None => Value::Null,
+    };
+
+    let mut custom_reminders = Vec::new();
+    for reminder in CustomReminder::get_by_guild(db, &guild_id_str)
+        .await
+        .map_err(db_err)?
+    {
+        let ping_roles = CustomReminderPingRole::get_by_reminder(db, reminder.id)
+            .await
+            .map_err(db_err)?;
+        let subscriptions = CustomReminderSubscription::get_by_reminder(db, reminder.id)
+            .await
+            .map_err(db_err)?;
+        let recent_logs =
+            CustomReminderLog::get_recent_by_reminder(db, reminder.id, EXPORT_RECENT_LOGS)
+                .await
+                .map_err(db_err)?;
+        custom_reminders.push(to_json_with(
+            reminder,
+            json!({
+                "ping_roles": ping_roles,
+                "subscriptions": subscriptions,
+                "recent_logs": recent_logs,
+            }),
+        )?);
+    }
+
+    Ok(json!({
+        "success": true,
+        "guild_id": guild_id_str,
+        "exported_at": chrono::Utc::now().to_rfc3339(),
+        "guild_config": to_json(GuildConfig::get(db, &guild_id_str).await.map_err(db_err)?)?,
+        "selfroles": selfroles,
+        "selfrole_labels": SelfRoleLabel::get_all_for_guild(db, &guild_id_str)
+            .await
+            .map_err(db_err)?,
+        "selfrole_emoji_policy": to_json(
+            SelfRoleEmojiPolicy::get(db, &guild_id_str).await.map_err(db_err)?
+        )?,
+        "selfrole_settings": to_json(
+            SelfRoleSettings::get(db, &guild_id_str).await.map_err(db_err)?
+        )?,
+        "ai_settings": to_json(
+            crate::database::ai_settings::GuildAiSettings::get(db, &guild_id_str)
+                .await
+                .map_err(db_err)?
+        )?,
+        "ai_usage": to_json(
+            crate::database::ai_usage::get_by_guild(db, &guild_id_str)
+                .await
+                .map_err(db_err)?
+        )?,
+        "selfrole_cooldowns": to_json(
+            SelfRoleCooldown::get_by_guild(db, &guild_id_str).await.map_err(db_err)?
+        )?,
+        "reminders": reminders,
+        "custom_reminders": custom_reminders,
+        "scheduled_messages": to_json(
+            ScheduledMessage::get_by_guild(db, &guild_id_str).await.map_err(db_err)?
+        )?,
+        "welcome_goodbye": welcome_goodbye,
+        "pending_welcomes": to_json(
+            PendingWelcome::get_by_guild(db, &guild_id_str).await.map_err(db_err)?
+        )?,
+        "mediaonly": to_json(
+            MediaOnlyConfig::get_by_guild(db, &guild_id_str).await.map_err(db_err)?
+        )?,
+        "mediaonly_stats": to_json(
+            MediaOnlyStats::rows_by_guild(db, &guild_id_str).await.map_err(db_err)?
+        )?,
+        "uwufy": to_json(
+            UwufyToggle::get_by_guild(db, &guild_id_str).await.map_err(db_err)?
+        )?,
+        "command_aliases": to_json(
+            CommandAlias::get_by_guild(db, &guild_id_str).await.map_err(db_err)?
+        )?,
+    }))
+}
+
+/// Delete everything the bot stores for a guild
+pub async fn delete_guild_data(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    let removed = database::guild_data::delete_all(&app_state.db, &guild_id.to_string())
+        .await
+        .map_err(|e| format!("DB error: {}", e))?;
+
+    Ok(json!({ "success": true, "removed": removed }))
+}
+
+// User data functions
+
+/// Everything the bot stores about a user. Credentials (API key, OAuth token)
+/// are reported as present/absent, never exported.
+pub async fn export_user_data(app_state: &AppState, user_id: &str) -> Result<Value, String> {
+    use crate::database::reminders::{
+        CustomReminderSubscription, ReminderSubscription, UserSettings,
+    };
+    use crate::database::{
+        scheduled_messages::ScheduledMessage, selfroles::SelfRoleCooldown, uwufy::UwufyToggle,
+    };
+
+    let db = &app_state.db;
+
+    let profile = DashboardUser::get_by_user_id(db, user_id)
+        .await
+        .map_err(db_err)?
+        .map(|u| {
+            json!({
+                "username": u.username,
+                "avatar": u.avatar,
+                "has_api_key": u.api_key_hash.is_some(),
+                "has_oauth_token": u.oauth_token.is_some(),
+                "created_at": u.created_at,
+                "updated_at": u.updated_at,
+            })
+        });
+
+    Ok(json!({
+        "success": true,
+        "user_id": user_id,
+        "exported_at": chrono::Utc::now().to_rfc3339(),
+        "profile": profile,
+        "settings": to_json(UserSettings::get(db, user_id).await.map_err(db_err)?)?,
+        "reminder_subscriptions": to_json(
+            ReminderSubscription::get_by_user(db, user_id).await.map_err(db_err)?
+        )?,
+        "custom_reminder_subscriptions": to_json(
+            CustomReminderSubscription::get_by_user(db, user_id).await.map_err(db_err)?
+        )?,
+        "selfrole_cooldowns": to_json(
+            SelfRoleCooldown::get_by_user(db, user_id).await.map_err(db_err)?
+        )?,
+        "uwufy": to_json(UwufyToggle::get_by_user(db, user_id).await.map_err(db_err)?)?,
+        "ai_usage": to_json(
+            crate::database::ai_usage::get_by_user(db, user_id)
+                .await
+                .map_err(db_err)?
+        )?,
+        "mediaonly_stats": to_json(
+            crate::database::mediaonly::MediaOnlyStats::rows_by_user(db, user_id)
+                .await
+                .map_err(db_err)?
+        )?,
+        "pending_welcomes": to_json(
+            crate::database::welcome_goodbye::PendingWelcome::get_by_user(db, user_id)
+                .await
+                .map_err(db_err)?
+        )?,
+        "guilds": to_json(CachedGuild::get_for_user(db, user_id).await.map_err(db_err)?)?,
+        "scheduled_messages_created": to_json(
+            ScheduledMessage::get_by_creator(db, user_id).await.map_err(db_err)?
+        )?,
+    }))
+}
+
+/// Delete everything stored about a user, signing out all of their sessions
+pub async fn delete_user_data(app_state: &AppState, user_id: &str) -> Result<Value, String> {
+    let removed = database::user_data::delete_all(&app_state.db, user_id)
+        .await
+        .map_err(db_err)?;
+
+    Ok(json!({ "success": true, "removed": removed }))
+}
+
+// AI usage functions
+
+/// Users listed on the usage page; enough to spot heavy use without a full leaderboard.
+const AI_USAGE_TOP_USERS: i64 = 10;
+
+/// This month's LLM usage for a guild, with the remaining budget if one is configured
+pub async fn get_ai_usage(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    use crate::database::ai_usage;
+
+    let llm = &app_state.config.llm;
+    let period_start = ai_usage::period_start(chrono::Utc::now());
+    let top_n = if llm.usage_show_top_users {
+        AI_USAGE_TOP_USERS
+    } else {
+        0
+    };
+
+    let summary = ai_usage::summary(&app_state.db, &guild_id.to_string(), period_start, top_n)
+        .await
+        .map_err(db_err)?;
+    let total_tokens = summary.total_tokens();
+    let remaining = llm
+        .guild_monthly_token_budget
+        .map(|budget| budget.saturating_sub(total_tokens.max(0) as u64));
+
+    to_json_with(
+        &summary,
+        json!({
+            "success": true,
+            "enabled": llm.provider.is_some(),
+            "period_start": period_start.to_rfc3339(),
+            "total_tokens": total_tokens,
+            "budget": llm.guild_monthly_token_budget,
+            "remaining": remaining,
+            "show_top_users": llm.usage_show_top_users,
+        }),
+    )
+}
+
+/// Get the guild's AI reply settings
+pub async fn get_ai_settings(app_state: &AppState, guild_id: u64) -> Result<Value, String> {
+    use crate::database::ai_settings::GuildAiSettings;
+
+    let settings = GuildAiSettings::get_or_default(&app_state.db, &guild_id.to_string())
+        .await
+        .map_err(db_err)?;
+    Ok(json!({
+        "success": true,
+        "allowed_channel_ids": settings.channel_list(),
+        "channel_cooldown_secs": settings.channel_cooldown_secs
+    }))
+}
+
+/// Longest per-channel gap a guild can put between AI replies.
+pub const MAX_AI_CHANNEL_COOLDOWN_SECS: i64 = 3600;
+
+/// Update the guild's AI reply settings. An empty `allowed_channel_ids` lets
+/// the bot answer in every channel.
+pub async fn update_ai_settings(
+    app_state: &AppState,
+    guild_id: u64,
+    payload: &Value,
+) -> Result<Value, String> {
+    use crate::database::ai_settings::GuildAiSettings;
+
+    let mut settings = GuildAiSettings::get_or_default(&app_state.db, &guild_id.to_string())
+        .await
+        .map_err(db_err)?;
+    if let Some(value) = payload.get("allowed_channel_ids") {
+        let items = value
+            .as_array()
+            .ok_or("allowed_channel_ids must be an array of channel ids")?;
+        let mut channel_ids: Vec<String> = Vec::with_capacity(items.len());
+        for item in items {
+            let id = item
+                .as_str()
+                .filter(|s| s.parse::<u64>().is_ok())
+                .ok_or("allowed_channel_ids must be an array of channel ids")?;
+            if !channel_ids.iter().any(|c| c == id) {
+                channel_ids.push(id.to_string());
+            }
+        }
+        settings.set_channel_list(&channel_ids);
+    }
+    if let Some(value) = payload.get("channel_cooldown_secs") {
+        settings.channel_cooldown_secs = value
+            .as_i64()
+            .filter(|secs| (0..=MAX_AI_CHANNEL_COOLDOWN_SECS).contains(secs))
+            .ok_or_else(|| {
+                format!(
+                    "channel_cooldown_secs must be between 0 and {}",
+                    MAX_AI_CHANNEL_COOLDOWN_SECS
+                )
+            })?;
+    }
+    settings.upsert(&app_state.db).await.map_err(db_err)?;
+    get_ai_settings(app_state, guild_id).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::split_message_for_discord;
@@ -47,3 +47,20 @@ pub struct SelfRoleData {
     pub role_id: String,
     pub emoji: String,
 }
+
+/// A built-in starter panel. Roles carry suggestions only; the admin picks the actual role.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfRoleTemplate {
+    pub id: String,
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub selection_type: String,
+    pub roles: Vec<SelfRoleTemplateRole>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfRoleTemplateRole {
+    pub emoji: String,
+    pub label: String,
+}
@@ -1,5 +1,6 @@
 use regex::Regex;
-use serenity::all::Message;
+use serenity::all::{Attachment, Message};
+use std::fmt;
 use std::sync::LazyLock;
 
 static URL_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"https?://\S+").unwrap());
@@ -27,18 +28,19 @@ pub fn has_attachment(message: &Message) -> bool {
     !message.attachments.is_empty()
 }
 
+/// Check if an attachment is a GIF. Discord's sniffed `content_type` wins when present,
+/// since filenames can be missing an extension or lie about it; otherwise the extension decides.
+pub fn is_gif_attachment(attachment: &Attachment) -> bool {
+    match attachment.content_type.as_deref() {
+        Some(content_type) => content_type.to_lowercase().starts_with("image/gif"),
+        None => attachment.filename.to_lowercase().ends_with(".gif"),
+    }
+}
+
 /// Check if a message contains GIFs (files, URLs, or Tenor/Giphy)
 pub fn has_gif(message: &Message) -> bool {
     // Check for GIF file attachments
-    let has_gif_attachment = message.attachments.iter().any(|attachment| {
-        attachment.filename.to_lowercase().ends_with(".gif")
-            || attachment
-                .content_type
-                .as_ref()
-                .is_some_and(|ct: &String| ct.starts_with("image/gif"))
-    });
-
-    if has_gif_attachment {
+    if message.attachments.iter().any(is_gif_attachment) {
         return true;
     }
 
@@ -75,13 +77,7 @@ pub fn has_sticker(message: &Message) -> bool {
 }
 
 fn has_non_gif_attachment(message: &Message) -> bool {
-    message.attachments.iter().any(|a| {
-        !a.filename.to_lowercase().ends_with(".gif")
-            && !a
-                .content_type
-                .as_ref()
-                .is_some_and(|ct| ct.starts_with("image/gif"))
-    })
+    message.attachments.iter().any(|a| !is_gif_attachment(a))
 }
 
 /// Check if a message contains any allowed content based on configuration
@@ -117,3 +113,66 @@ pub fn has_allowed_content(
 
     false
 }
+
+/// Why a message was rejected in a media-only channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationReason {
+    /// Plain text with no media of any kind
+    NoMedia,
+    LinksNotAllowed,
+    AttachmentsNotAllowed,
+    GifsNotAllowed,
+    StickersNotAllowed,
+}
+
+impl ViolationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ViolationReason::NoMedia => "no media",
+            ViolationReason::LinksNotAllowed => "links are not allowed",
+            ViolationReason::AttachmentsNotAllowed => "attachments are not allowed",
+            ViolationReason::GifsNotAllowed => "GIFs are not allowed",
+            ViolationReason::StickersNotAllowed => "stickers are not allowed",
+        }
+    }
+}
+
+impl fmt::Display for ViolationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Classify why a message fails the media-only check, or `None` if it's allowed
+pub fn classify_violation(
+    message: &Message,
+    allow_links: bool,
+    allow_attachments: bool,
+    allow_gifs: bool,
+    allow_stickers: bool,
+) -> Option<ViolationReason> {
+    if has_allowed_content(
+        message,
+        allow_links,
+        allow_attachments,
+        allow_gifs,
+        allow_stickers,
+    ) {
+        return None;
+    }
+
+    // most specific first: a GIF attachment is also an attachment, and a
+    // tenor link is also a link, so report the GIF rule that rejected it
+    let reason = if !allow_gifs && has_gif(message) {
+        ViolationReason::GifsNotAllowed
+    } else if !allow_attachments && has_attachment(message) {
+        ViolationReason::AttachmentsNotAllowed
+    } else if !allow_links && (has_link(message) || has_embedded_link(message)) {
+        ViolationReason::LinksNotAllowed
+    } else if !allow_stickers && has_sticker(message) {
+        ViolationReason::StickersNotAllowed
+    } else {
+        ViolationReason::NoMedia
+    };
+    Some(reason)
+}
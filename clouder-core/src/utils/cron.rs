@@ -0,0 +1,139 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+// far enough to reach the next Feb 29 across a skipped leap year (e.g. 2100)
+const MAX_SEARCH_DAYS: i64 = 366 * 9;
+
+/// Five-field cron spec: `minute hour day-of-month month day-of-week`.
+/// Supports `*`, numbers, lists (`1,15`), ranges (`1-5`) and steps (`*/15`, `0-30/10`).
+/// Day-of-week is 0-6 from sunday; 7 is accepted as sunday too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSpec {
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    // standard cron: when both day fields are restricted, either one matching is enough
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => {
+                let step: u32 = s
+                    .parse()
+                    .map_err(|_| format!("invalid step in {}: {}", name, part))?;
+                if step == 0 {
+                    return Err(format!("step must be positive in {}: {}", name, part));
+                }
+                (r, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            let a = a
+                .parse()
+                .map_err(|_| format!("invalid {}: {}", name, part))?;
+            let b = b
+                .parse()
+                .map_err(|_| format!("invalid {}: {}", name, part))?;
+            (a, b)
+        } else {
+            let v: u32 = range
+                .parse()
+                .map_err(|_| format!("invalid {}: {}", name, part))?;
+            // `5/15` means "from 5 every 15"
+            if part.contains('/') { (v, max) } else { (v, v) }
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!("{} out of range {}-{}: {}", name, min, max, part));
+        }
+
+        let mut v = start;
+        while v <= end {
+            bits |= 1 << v;
+            v += step;
+        }
+    }
+    Ok(bits)
+}
+
+impl CronSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err("cron spec needs 5 fields: minute hour day month weekday".to_string());
+        };
+
+        let mut days_of_week = parse_field(dow, 0, 7, "weekday")?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, "minute")?,
+            hours: parse_field(hour, 0, 23, "hour")? as u32,
+            days_of_month: parse_field(dom, 1, 31, "day")? as u32,
+            months: parse_field(month, 1, 12, "month")? as u16,
+            days_of_week: days_of_week as u8,
+            dom_restricted: dom != "*",
+            dow_restricted: dow != "*",
+        })
+    }
+
+    fn matches_day(&self, date: chrono::NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let dom = self.days_of_month & (1 << date.day()) != 0;
+        let dow = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.dom_restricted && self.dow_restricted {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+
+    /// First run strictly after `after`, evaluated in wall-clock time of `tz`.
+    /// Local times skipped by a DST jump are skipped; repeated ones fire once.
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let start = after.with_timezone(&tz).date_naive();
+
+        for offset in 0..MAX_SEARCH_DAYS {
+            let date = start + Duration::days(offset);
+            if !self.matches_day(date) {
+                continue;
+            }
+            for hour in (0..24).filter(|h| self.hours & (1 << h) != 0) {
+                for minute in (0..60).filter(|m| self.minutes & (1 << m) != 0) {
+                    let Some(naive) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    let Some(local) = tz.from_local_datetime(&naive).earliest() else {
+                        continue;
+                    };
+                    let utc = local.with_timezone(&Utc);
+                    if utc > after {
+                        return Some(utc);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Parses a one-off `YYYY-MM-DD HH:MM` in the given timezone.
+pub fn parse_local_datetime(s: &str, tz: Tz) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%d %H:%M").ok()?;
+    let local = tz.from_local_datetime(&naive).earliest()?;
+    Some(local.with_timezone(&Utc))
+}
@@ -2,6 +2,7 @@ use crate::config::AppState;
 use serenity::all::{ButtonStyle, Color, CreateActionRow, CreateButton};
 
 pub mod content_detection;
+pub mod cron;
 pub mod welcome_goodbye;
 
 /// Resolves the embed color for an optional guild.
@@ -77,6 +78,22 @@ pub fn has_permission(
     perms.contains(required)
 }
 
+/// Discord only lets the bot grant or revoke roles strictly below its own
+/// highest role, and never integration-managed ones. A panel that validated at
+/// creation can still fail later if someone reorders roles, so callers re-check
+/// this at click time.
+pub fn can_bot_manage_role(
+    bot_role_positions: impl IntoIterator<Item = u16>,
+    target_position: u16,
+    target_managed: bool,
+) -> bool {
+    if target_managed {
+        return false;
+    }
+    let highest = bot_role_positions.into_iter().max().unwrap_or(0);
+    target_position < highest
+}
+
 pub fn parse_hhmm(s: &str) -> Option<chrono::NaiveTime> {
     let (hours, minutes) = s.split_once(':')?;
 
@@ -149,6 +166,25 @@ pub fn truncate(s: &str, max_chars: usize) -> String {
     }
 }
 
+/// Discord rejects a whole message if any component's custom_id is longer than this.
+pub const CUSTOM_ID_MAX_LEN: usize = 100;
+
+/// Joins `prefix` and `parts` with `_` into a component custom_id. One past
+/// [`CUSTOM_ID_MAX_LEN`] trips a debug assertion and is logged in release, since
+/// Discord would otherwise drop the message without saying which button broke it.
+pub fn custom_id(prefix: &str, parts: &[&dyn std::fmt::Display]) -> String {
+    let id = parts.iter().fold(prefix.to_string(), |mut id, part| {
+        id.push('_');
+        id.push_str(&part.to_string());
+        id
+    });
+    debug_assert!(id.len() <= CUSTOM_ID_MAX_LEN, "custom_id too long: {id}");
+    if id.len() > CUSTOM_ID_MAX_LEN {
+        tracing::error!("custom_id over {} chars: {}", CUSTOM_ID_MAX_LEN, id);
+    }
+    id
+}
+
 /// A previous/next pagination button row (◀ / ▶). `total` is the page count —
 /// a one-item-per-page list passes its length. Buttons disable at the first and
 /// last page. Wrap in `vec![..]` to use as a message's full component list.
@@ -71,3 +71,106 @@ pub fn replace_placeholders(content: &str, placeholders: &HashMap<String, String
     }
     result
 }
+
+/// How a welcome/goodbye message is picked when the guild has variants
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationMode {
+    /// Always send the single configured message
+    Off,
+    Random,
+    RoundRobin,
+}
+
+impl RotationMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(RotationMode::Off),
+            "random" => Some(RotationMode::Random),
+            "round_robin" => Some(RotationMode::RoundRobin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RotationMode::Off => "off",
+            RotationMode::Random => "random",
+            RotationMode::RoundRobin => "round_robin",
+        }
+    }
+}
+
+/// Which name fills the `{username}` placeholder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NamePlaceholderSource {
+    /// The unique account username
+    #[default]
+    Username,
+    /// The global display name, falling back to the username
+    GlobalName,
+    /// The server nickname, then the global display name, then the username
+    Nickname,
+}
+
+impl NamePlaceholderSource {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "username" => Some(NamePlaceholderSource::Username),
+            "global_name" => Some(NamePlaceholderSource::GlobalName),
+            "nickname" => Some(NamePlaceholderSource::Nickname),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NamePlaceholderSource::Username => "username",
+            NamePlaceholderSource::GlobalName => "global_name",
+            NamePlaceholderSource::Nickname => "nickname",
+        }
+    }
+
+    /// The preferred name, skipping sources the user hasn't set.
+    pub fn resolve<'a>(
+        &self,
+        username: &'a str,
+        global_name: Option<&'a str>,
+        nickname: Option<&'a str>,
+    ) -> &'a str {
+        let global_name = global_name.filter(|n| !n.trim().is_empty());
+        let nickname = nickname.filter(|n| !n.trim().is_empty());
+        match self {
+            NamePlaceholderSource::Username => username,
+            NamePlaceholderSource::GlobalName => global_name.unwrap_or(username),
+            NamePlaceholderSource::Nickname => nickname.or(global_name).unwrap_or(username),
+        }
+    }
+}
+
+/// Index of the variant to send, or `None` to fall back to the configured message.
+/// `counter` is the number of earlier rotated sends and drives round-robin; `roll` is
+/// any random number and drives random picks.
+pub fn select_variant_index(
+    mode: RotationMode,
+    variant_count: usize,
+    counter: u64,
+    roll: u64,
+) -> Option<usize> {
+    if variant_count == 0 {
+        return None;
+    }
+    let count = variant_count as u64;
+    match mode {
+        RotationMode::Off => None,
+        RotationMode::Random => Some((roll % count) as usize),
+        RotationMode::RoundRobin => Some((counter % count) as usize),
+    }
+}
+
+/// Whether a member update is the moment they passed membership screening.
+/// `recorded` is whether their pending join was stored; `was_pending` is `None`
+/// when the previous member state wasn't cached, so it only covers joins from
+/// before the bot recorded them.
+pub fn completed_screening(recorded: bool, was_pending: Option<bool>, is_pending: bool) -> bool {
+    !is_pending && (recorded || was_pending == Some(true))
+}
@@ -1,3 +1,3 @@
 pub mod openai;
 
-pub use openai::{ChatMessage, LlmClient};
+pub use openai::{ChatMessage, LlmClient, Usage};
@@ -29,6 +29,18 @@ pub struct ChatMessage {
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     choices: Vec<Choice>,
+    // not every OpenAI-compatible server reports usage
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+/// Token counts reported by the provider for one completion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u32,
+    #[serde(default)]
+    pub completion_tokens: u32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,6 +54,8 @@ pub struct LlmClient {
     base_url: String,
     api_key: String,
     cooldowns: Arc<Mutex<HashMap<u64, Instant>>>,
+    /// Per channel, when the next reply may go out.
+    channel_cooldowns: Arc<Mutex<HashMap<u64, Instant>>>,
 }
 
 impl LlmClient {
@@ -56,6 +70,7 @@ impl LlmClient {
             base_url,
             api_key,
             cooldowns: Arc::new(Mutex::new(HashMap::new())),
+            channel_cooldowns: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -68,6 +83,28 @@ impl LlmClient {
         stop: Option<&str>,
         reasoning_effort: Option<&str>,
     ) -> Result<String> {
+        self.generate_with_usage(
+            model,
+            messages,
+            temperature,
+            max_tokens,
+            stop,
+            reasoning_effort,
+        )
+        .await
+        .map(|(text, _)| text)
+    }
+
+    /// Like [`generate`](Self::generate), also returning the provider's token usage if reported.
+    pub async fn generate_with_usage(
+        &self,
+        model: &str,
+        messages: Vec<ChatMessage>,
+        temperature: f32,
+        max_tokens: u32,
+        stop: Option<&str>,
+        reasoning_effort: Option<&str>,
+    ) -> Result<(String, Option<Usage>)> {
         let url = format!("{}/chat/completions", self.base_url.trim_end_matches('/'));
 
         let stop_sequences = stop
@@ -111,9 +148,11 @@ impl LlmClient {
         let text = response.text().await?;
         let chat_response: ChatResponse = serde_json::from_str(&text)?;
 
+        let usage = chat_response.usage;
+
         if chat_response.choices.is_empty() {
             warn!("llm returned empty choices");
-            return Ok("no response generated.".to_string());
+            return Ok(("no response generated.".to_string(), usage));
         }
 
         let content = chat_response.choices[0].message.content.clone();
@@ -123,31 +162,87 @@ impl LlmClient {
         // refusals) return an empty content field; sending that to Discord errors out.
         if cleaned.trim().is_empty() {
             warn!("llm returned empty content");
-            return Ok("no response generated.".to_string());
+            return Ok(("no response generated.".to_string(), usage));
         }
 
         debug!("llm response: {}", cleaned);
-        Ok(cleaned)
+        Ok((cleaned, usage))
     }
 
     pub fn check_and_update_cooldown(&self, user_id: u64, cooldown_duration: Duration) -> bool {
+        check_and_update(&self.cooldowns, user_id, cooldown_duration)
+    }
+
+    /// Whether [`Self::check_and_update_cooldown`] would refuse the user, without
+    /// starting a cooldown; lets callers run other checks before spending it.
+    pub fn on_cooldown(&self, user_id: u64, cooldown_duration: Duration) -> bool {
+        let cooldowns = self.cooldowns.lock().expect("cooldowns lock poisoned");
+        cooldowns
+            .get(&user_id)
+            .is_some_and(|last| last.elapsed() < cooldown_duration)
+    }
+
+    /// Like [`Self::check_and_update_cooldown`], but for the retry button. Retries
+    /// share the prompt timestamps, so a retry right after a prompt still waits;
+    /// only the duration is the retry's own. Nothing is evicted here, as a shorter
+    /// retry window would drop entries that are still cooling down for prompts.
+    pub fn check_and_update_retry_cooldown(
+        &self,
+        user_id: u64,
+        cooldown_duration: Duration,
+    ) -> bool {
         let mut cooldowns = self.cooldowns.lock().expect("cooldowns lock poisoned");
+        if cooldowns
+            .get(&user_id)
+            .is_some_and(|last| last.elapsed() < cooldown_duration)
+        {
+            return false;
+        }
+        cooldowns.insert(user_id, Instant::now());
+        true
+    }
+
+    /// Like [`Self::check_and_update_cooldown`], but keyed by channel. Guilds pick
+    /// their own spacing, so entries hold their expiry rather than the last reply.
+    pub fn check_and_update_channel_cooldown(&self, channel_id: u64, spacing: Duration) -> bool {
+        let mut cooldowns = self
+            .channel_cooldowns
+            .lock()
+            .expect("channel cooldowns lock poisoned");
         let now = Instant::now();
 
-        // Evict expired entries to prevent unbounded growth
-        cooldowns.retain(|_, last| now.duration_since(*last) < cooldown_duration);
+        cooldowns.retain(|_, until| *until > now);
 
-        if let Some(&last_request) = cooldowns.get(&user_id)
-            && now.duration_since(last_request) < cooldown_duration
-        {
+        if cooldowns.contains_key(&channel_id) {
             return false;
         }
 
-        cooldowns.insert(user_id, now);
+        cooldowns.insert(channel_id, now + spacing);
         true
     }
 }
 
+fn check_and_update(
+    cooldowns: &Mutex<HashMap<u64, Instant>>,
+    user_id: u64,
+    cooldown_duration: Duration,
+) -> bool {
+    let mut cooldowns = cooldowns.lock().expect("cooldowns lock poisoned");
+    let now = Instant::now();
+
+    // Evict expired entries to prevent unbounded growth
+    cooldowns.retain(|_, last| now.duration_since(*last) < cooldown_duration);
+
+    if let Some(&last_request) = cooldowns.get(&user_id)
+        && now.duration_since(last_request) < cooldown_duration
+    {
+        return false;
+    }
+
+    cooldowns.insert(user_id, now);
+    true
+}
+
 fn clean_response_tokens(text: &str) -> String {
     // Strip common EOS tokens emitted by Ollama, LLaMA, Mistral, and other providers
     const EOS_TOKENS: &[&str] = &[
@@ -204,6 +299,62 @@ mod tests {
         assert!(client.check_and_update_cooldown(other_user_id, cooldown_duration));
     }
 
+    #[test]
+    fn test_on_cooldown_does_not_start_one() {
+        let client = LlmClient::new(
+            "https://api.openai.com/v1".to_string(),
+            "test-key".to_string(),
+            30,
+        );
+        let user_id = 123456789;
+        let cooldown_duration = Duration::from_secs(5);
+
+        assert!(!client.on_cooldown(user_id, cooldown_duration));
+        assert!(!client.on_cooldown(user_id, cooldown_duration));
+        assert!(client.check_and_update_cooldown(user_id, cooldown_duration));
+        assert!(client.on_cooldown(user_id, cooldown_duration));
+    }
+
+    #[test]
+    fn test_retry_cooldown_shares_prompt_timestamps() {
+        let client = LlmClient::new(
+            "https://api.openai.com/v1".to_string(),
+            "test-key".to_string(),
+            30,
+        );
+
+        let user_id = 123456789;
+        assert!(client.check_and_update_cooldown(user_id, Duration::from_secs(10)));
+        // a retry right after the prompt waits out the retry window
+        assert!(!client.check_and_update_retry_cooldown(user_id, Duration::from_secs(30)));
+        // zero turns the retry cooldown off, and a retry counts as a prompt
+        assert!(client.check_and_update_retry_cooldown(user_id, Duration::ZERO));
+        assert!(!client.check_and_update_cooldown(user_id, Duration::from_secs(10)));
+        // a retry check with a short window leaves other users' entries alone
+        assert!(client.check_and_update_cooldown(1, Duration::from_secs(10)));
+        assert!(client.check_and_update_retry_cooldown(user_id, Duration::ZERO));
+        assert!(!client.check_and_update_cooldown(1, Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_channel_cooldown() {
+        let client = LlmClient::new(
+            "https://api.openai.com/v1".to_string(),
+            "test-key".to_string(),
+            30,
+        );
+
+        let channel_id = 111;
+        assert!(client.check_and_update_channel_cooldown(channel_id, Duration::from_secs(60)));
+        assert!(!client.check_and_update_channel_cooldown(channel_id, Duration::from_secs(60)));
+
+        // a shorter gap elsewhere doesn't free up the busy channel
+        assert!(client.check_and_update_channel_cooldown(222, Duration::from_millis(1)));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(client.check_and_update_channel_cooldown(222, Duration::from_millis(1)));
+        assert!(!client.check_and_update_channel_cooldown(channel_id, Duration::from_secs(60)));
+    }
+
     #[test]
     fn test_stop_functionality() {
         let request_with_stop = ChatRequest {
@@ -1,10 +1,13 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
+use axum_extra::extract::cookie::SignedCookieJar;
 use clouder_core::DashboardUser;
 use clouder_core::config::AppState;
+use serde::Deserialize;
 use serde_json::{Value, json};
 use serenity::all::Permissions;
 use tracing::{error, info, warn};
@@ -29,6 +32,15 @@ async fn require_guild_perm(
     Ok(())
 }
 
+/// Error response carrying the shared layer's message, for pages that toast `data.message`.
+fn error_with_message(status: StatusCode, message: String) -> Response {
+    (
+        status,
+        Json(json!({ "success": false, "message": message })),
+    )
+        .into_response()
+}
+
 /// Verifies the user has any cached access to the guild (read-only endpoints).
 async fn require_guild_access(
     state: &AppState,
@@ -128,9 +140,16 @@ pub async fn api_get_roles(
     }
 }
 
+#[derive(Deserialize)]
+pub struct SelfRoleListQuery {
+    search: Option<String>,
+    channel: Option<String>,
+}
+
 pub async fn api_selfroles_list(
     auth: Auth,
     Path(guild_id): Path<String>,
+    Query(query): Query<SelfRoleListQuery>,
     State(state): State<AppState>,
 ) -> Result<Json<Value>, StatusCode> {
     require_guild_perm(
@@ -141,7 +160,14 @@ pub async fn api_selfroles_list(
     )
     .await?;
     let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
-    match clouder_core::shared::list_selfroles(&state, guild_id_u64).await {
+    match clouder_core::shared::list_selfroles(
+        &state,
+        guild_id_u64,
+        query.search.as_deref(),
+        query.channel.as_deref(),
+    )
+    .await
+    {
         Ok(result) => Ok(Json(result)),
         Err(e) => {
             error!("failed to list selfroles: {}", e);
@@ -150,12 +176,22 @@ pub async fn api_selfroles_list(
     }
 }
 
+pub async fn api_selfroles_templates(_auth: Auth) -> Result<Json<serde_json::Value>, StatusCode> {
+    match clouder_core::shared::list_selfrole_templates() {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            error!("failed to list selfrole templates: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn api_selfroles_create(
     auth: CsrfAuth,
     Path(guild_id): Path<String>,
     State(state): State<AppState>,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Response, StatusCode> {
     require_guild_perm(
         &state,
         &auth.0.user_id,
@@ -168,11 +204,37 @@ pub async fn api_selfroles_create(
     match clouder_core::shared::create_selfrole(&state, guild_id_u64, user_id_u64, &payload).await {
         Ok(result) => {
             info!("selfrole created for guild {}", guild_id);
-            Ok(Json(result))
+            Ok(Json(result).into_response())
         }
         Err(e) => {
             error!("failed to create selfrole: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
+        }
+    }
+}
+
+pub async fn api_selfroles_attach(
+    auth: CsrfAuth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Response, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_ROLES,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::attach_selfrole(&state, guild_id_u64, &payload).await {
+        Ok(result) => {
+            info!("selfrole attached for guild {}", guild_id);
+            Ok(Json(result).into_response())
+        }
+        Err(e) => {
+            error!("failed to attach selfrole: {}", e);
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
         }
     }
 }
@@ -182,7 +244,7 @@ pub async fn api_selfroles_update(
     Path((guild_id, config_id)): Path<(String, String)>,
     State(state): State<AppState>,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Response, StatusCode> {
     require_guild_perm(
         &state,
         &auth.0.user_id,
@@ -204,11 +266,40 @@ pub async fn api_selfroles_update(
     {
         Ok(result) => {
             info!("selfrole {} updated for guild {}", config_id, guild_id);
-            Ok(Json(result))
+            Ok(Json(result).into_response())
         }
         Err(e) => {
             error!("failed to update selfrole: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
+        }
+    }
+}
+
+pub async fn api_selfroles_move_channel(
+    auth: CsrfAuth,
+    Path((guild_id, config_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Response, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_ROLES,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let config_id_i64 = config_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::move_selfrole_channel(&state, guild_id_u64, config_id_i64, &payload)
+        .await
+    {
+        Ok(result) => {
+            info!("selfrole {} moved for guild {}", config_id, guild_id);
+            Ok(Json(result).into_response())
+        }
+        Err(e) => {
+            error!("failed to move selfrole: {}", e);
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
         }
     }
 }
@@ -239,6 +330,102 @@ pub async fn api_selfroles_delete(
     }
 }
 
+pub async fn api_selfroles_emoji_policy_get(
+    auth: Auth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_ROLES,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::get_selfrole_emoji_policy(&state, guild_id_u64).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            error!("failed to get selfrole emoji policy: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn api_selfroles_emoji_policy_update(
+    auth: CsrfAuth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<Value>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_ROLES,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::update_selfrole_emoji_policy(&state, guild_id_u64, &payload).await {
+        Ok(result) => {
+            info!("selfrole emoji policy updated for guild {}", guild_id);
+            Ok(Json(result))
+        }
+        Err(e) => {
+            error!("failed to update selfrole emoji policy: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn api_selfroles_settings_get(
+    auth: Auth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_ROLES,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::get_selfrole_settings(&state, guild_id_u64).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            error!("failed to get selfrole settings: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn api_selfroles_settings_update(
+    auth: CsrfAuth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<Value>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_ROLES,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::update_selfrole_settings(&state, guild_id_u64, &payload).await {
+        Ok(result) => {
+            info!("selfrole settings updated for guild {}", guild_id);
+            Ok(Json(result))
+        }
+        Err(e) => {
+            error!("failed to update selfrole settings: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn api_welcome_goodbye_get(
     auth: Auth,
     Path(guild_id): Path<String>,
@@ -266,7 +453,7 @@ pub async fn api_welcome_goodbye_post(
     Path(guild_id): Path<String>,
     State(state): State<AppState>,
     Json(payload): Json<Value>,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Response, StatusCode> {
     require_guild_perm(
         &state,
         &auth.0.user_id,
@@ -279,11 +466,37 @@ pub async fn api_welcome_goodbye_post(
     {
         Ok(result) => {
             info!("welcome/goodbye config updated for guild {}", guild_id);
-            Ok(Json(result))
+            Ok(Json(result).into_response())
         }
         Err(e) => {
             error!("failed to update welcome/goodbye config: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
+        }
+    }
+}
+
+pub async fn api_welcome_goodbye_patch(
+    auth: CsrfAuth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<Value>,
+) -> Result<Response, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_GUILD,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::patch_welcome_goodbye_config(&state, guild_id_u64, &payload).await {
+        Ok(result) => {
+            info!("welcome/goodbye config patched for guild {}", guild_id);
+            Ok(Json(result).into_response())
+        }
+        Err(e) => {
+            error!("failed to patch welcome/goodbye config: {}", e);
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
         }
     }
 }
@@ -344,12 +557,34 @@ pub async fn api_mediaonly_get(
     }
 }
 
+pub async fn api_mediaonly_stats(
+    auth: Auth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_CHANNELS,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::get_mediaonly_stats(&state, guild_id_u64).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            error!("failed to get mediaonly stats: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn api_mediaonly_post(
     auth: CsrfAuth,
     Path(guild_id): Path<String>,
     State(state): State<AppState>,
     Json(payload): Json<Value>,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Response, StatusCode> {
     require_guild_perm(
         &state,
         &auth.0.user_id,
@@ -362,9 +597,11 @@ pub async fn api_mediaonly_post(
         .get("channel_id")
         .and_then(|v| v.as_str())
         .ok_or(StatusCode::BAD_REQUEST)?;
+    let user_id_u64: u64 = auth.0.user_id.parse().unwrap_or(0);
     match clouder_core::shared::create_or_update_mediaonly_config(
         &state,
         guild_id_u64,
+        user_id_u64,
         channel_id,
         &payload,
     )
@@ -375,11 +612,48 @@ pub async fn api_mediaonly_post(
                 "mediaonly config created for guild {} channel {}",
                 guild_id, channel_id
             );
-            Ok(Json(result))
+            Ok(Json(result).into_response())
         }
         Err(e) => {
             error!("failed to create mediaonly config: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
+        }
+    }
+}
+
+pub async fn api_mediaonly_bulk(
+    auth: CsrfAuth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<Value>,
+) -> Result<Response, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_CHANNELS,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let user_id_u64: u64 = auth.0.user_id.parse().unwrap_or(0);
+    match clouder_core::shared::bulk_configure_mediaonly(
+        &state,
+        guild_id_u64,
+        user_id_u64,
+        &payload,
+    )
+    .await
+    {
+        Ok(result) => {
+            info!(
+                "mediaonly bulk configured {} channels for guild {}",
+                result["configured"], guild_id
+            );
+            Ok(Json(result).into_response())
+        }
+        Err(e) => {
+            error!("failed to bulk configure mediaonly: {}", e);
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
         }
     }
 }
@@ -389,7 +663,7 @@ pub async fn api_mediaonly_put(
     Path((guild_id, channel_id)): Path<(String, String)>,
     State(state): State<AppState>,
     Json(payload): Json<Value>,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Response, StatusCode> {
     require_guild_perm(
         &state,
         &auth.0.user_id,
@@ -398,9 +672,11 @@ pub async fn api_mediaonly_put(
     )
     .await?;
     let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let user_id_u64: u64 = auth.0.user_id.parse().unwrap_or(0);
     match clouder_core::shared::create_or_update_mediaonly_config(
         &state,
         guild_id_u64,
+        user_id_u64,
         &channel_id,
         &payload,
     )
@@ -411,11 +687,11 @@ pub async fn api_mediaonly_put(
                 "mediaonly config updated for guild {} channel {}",
                 guild_id, channel_id
             );
-            Ok(Json(result))
+            Ok(Json(result).into_response())
         }
         Err(e) => {
             error!("failed to update mediaonly config: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
         }
     }
 }
@@ -470,6 +746,28 @@ pub async fn api_about_get(
     }
 }
 
+pub async fn api_guild_overview(
+    auth: Auth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_GUILD,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::get_guild_overview(&state, guild_id_u64).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            error!("failed to get guild overview: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn api_guild_config_get(
     auth: Auth,
     Path(guild_id): Path<String>,
@@ -536,6 +834,62 @@ pub async fn api_guild_config_post(
     }
 }
 
+pub async fn api_guild_data_export(
+    auth: Auth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::ADMINISTRATOR,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::export_guild_data(&state, guild_id_u64).await {
+        Ok(result) => {
+            info!(
+                "guild data exported for guild {} by {}",
+                guild_id, auth.0.user_id
+            );
+            Ok(Json(result))
+        }
+        Err(e) => {
+            error!("failed to export guild data: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn api_guild_data_delete(
+    auth: CsrfAuth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::ADMINISTRATOR,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::delete_guild_data(&state, guild_id_u64).await {
+        Ok(result) => {
+            warn!(
+                "all guild data deleted for guild {} by {}",
+                guild_id, auth.0.user_id
+            );
+            Ok(Json(result))
+        }
+        Err(e) => {
+            error!("failed to delete guild data: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn api_uwufy_get(
     auth: Auth,
     Path(guild_id): Path<String>,
@@ -558,6 +912,76 @@ pub async fn api_uwufy_get(
     }
 }
 
+pub async fn api_ai_usage_get(
+    auth: Auth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_GUILD,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::get_ai_usage(&state, guild_id_u64).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            error!("failed to get ai usage: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn api_ai_settings_get(
+    auth: Auth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_GUILD,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::get_ai_settings(&state, guild_id_u64).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            error!("failed to get ai settings: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn api_ai_settings_update(
+    auth: CsrfAuth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<Value>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_GUILD,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::update_ai_settings(&state, guild_id_u64, &payload).await {
+        Ok(result) => {
+            info!("ai settings updated for guild {}", guild_id);
+            Ok(Json(result))
+        }
+        Err(e) => {
+            error!("failed to update ai settings: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn api_uwufy_toggle(
     auth: CsrfAuth,
     Path((guild_id, user_id)): Path<(String, String)>,
@@ -858,6 +1282,41 @@ pub async fn api_user_dm_reminders_post(
     }
 }
 
+pub async fn api_user_data_export(
+    auth: Auth,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    match clouder_core::shared::export_user_data(&state, &auth.0.user_id).await {
+        Ok(v) => {
+            info!("user data exported for {}", auth.0.user_id);
+            Ok(Json(v))
+        }
+        Err(e) => {
+            error!("failed to export user data: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Deletes the user's rows (sessions included) and clears the cookie, so the
+/// caller is signed out by the response itself.
+pub async fn api_user_data_delete(
+    auth: CsrfAuth,
+    State(state): State<AppState>,
+    jar: SignedCookieJar,
+) -> Result<(SignedCookieJar, Json<Value>), StatusCode> {
+    match clouder_core::shared::delete_user_data(&state, &auth.0.user_id).await {
+        Ok(v) => {
+            warn!("all user data deleted for {}", auth.0.user_id);
+            Ok((crate::session::clear(jar), Json(v)))
+        }
+        Err(e) => {
+            error!("failed to delete user data: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn api_user_subscriptions_get(
     auth: Auth,
     State(state): State<AppState>,
@@ -1148,3 +1607,88 @@ async fn clouder_web_custom_reminder_test(
     let _ = CustomReminderLog::create(&state.db, reminder_id, "success", None, true, 0, 0).await;
     Ok(())
 }
+
+pub async fn api_scheduled_messages_list(
+    auth: Auth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_GUILD,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::list_scheduled_messages(&state, guild_id_u64).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            error!("failed to list scheduled messages: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn api_scheduled_message_create(
+    auth: CsrfAuth,
+    Path(guild_id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<Value>,
+) -> Result<Response, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_GUILD,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::create_scheduled_message(
+        &state,
+        guild_id_u64,
+        &auth.0.user_id,
+        &payload,
+    )
+    .await
+    {
+        Ok(result) => {
+            info!("scheduled message created for guild {}", guild_id);
+            Ok(Json(result).into_response())
+        }
+        Err(e) => {
+            error!("failed to create scheduled message: {}", e);
+            Ok(error_with_message(StatusCode::INTERNAL_SERVER_ERROR, e))
+        }
+    }
+}
+
+pub async fn api_scheduled_message_delete(
+    auth: CsrfAuth,
+    Path((guild_id, message_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    require_guild_perm(
+        &state,
+        &auth.0.user_id,
+        &guild_id,
+        Permissions::MANAGE_GUILD,
+    )
+    .await?;
+    let guild_id_u64 = guild_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let message_id_i64: i64 = message_id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match clouder_core::shared::delete_scheduled_message(&state, guild_id_u64, message_id_i64).await
+    {
+        Ok(result) => {
+            info!(
+                "scheduled message {} deleted for guild {}",
+                message_id, guild_id
+            );
+            Ok(Json(result))
+        }
+        Err(e) => {
+            error!("failed to delete scheduled message: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
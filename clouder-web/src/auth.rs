@@ -1,6 +1,7 @@
 use crate::WebState;
-use crate::session;
+use crate::session::{self, CsrfAuth};
 use axum::extract::{Query, State};
+use axum::http::StatusCode;
 use axum::response::Redirect;
 use axum_extra::extract::cookie::{Cookie, SameSite, SignedCookieJar};
 use clouder_core::DashboardUser;
@@ -38,6 +39,31 @@ fn clear_oauth_state(jar: SignedCookieJar) -> SignedCookieJar {
     jar.remove(c)
 }
 
+/// The state Discord echoes back must match the one signed into the cookie at
+/// `/auth/login`. Anything missing counts as a mismatch.
+fn oauth_state_valid(stored: Option<&str>, presented: Option<&str>) -> bool {
+    match (stored, presented) {
+        (Some(stored), Some(presented)) if !stored.is_empty() && !presented.is_empty() => {
+            stored.as_bytes().ct_eq(presented.as_bytes()).into()
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug)]
+enum ExchangeError {
+    /// Discord rejected the code itself: already redeemed, expired, or forged.
+    InvalidGrant,
+    Other(String),
+}
+
+/// Discord answers a reused or expired code with 400 `invalid_grant`.
+fn is_invalid_grant(status: reqwest::StatusCode, body: &str) -> bool {
+    status == reqwest::StatusCode::BAD_REQUEST
+        && serde_json::from_str::<serde_json::Value>(body)
+            .is_ok_and(|json| json["error"] == "invalid_grant")
+}
+
 #[derive(Debug)]
 struct DiscordProfile {
     user_id: String,
@@ -71,13 +97,7 @@ pub async fn callback(
     // was signed when /auth/login fired; missing or mismatched values reject
     // before we exchange any code.
     let stored_state = jar.get(OAUTH_STATE_COOKIE).map(|c| c.value().to_string());
-    let presented_state = query.state.as_deref().unwrap_or("");
-    let valid_state = match stored_state.as_deref() {
-        Some(stored) if !stored.is_empty() && !presented_state.is_empty() => {
-            stored.as_bytes().ct_eq(presented_state.as_bytes()).into()
-        }
-        _ => false,
-    };
+    let valid_state = oauth_state_valid(stored_state.as_deref(), query.state.as_deref());
     let jar = clear_oauth_state(jar);
     if !valid_state {
         warn!("oauth state mismatch or missing — possible csrf");
@@ -96,7 +116,11 @@ pub async fn callback(
 
     let access_token = match exchange_code(&state, &code).await {
         Ok(t) => t,
-        Err(e) => {
+        Err(ExchangeError::InvalidGrant) => {
+            warn!("oauth code rejected as used or expired");
+            return (jar, Redirect::to("/login?error=expired"));
+        }
+        Err(ExchangeError::Other(e)) => {
             error!("token exchange failed: {}", e);
             return (jar, Redirect::to("/login?error=auth_failed"));
         }
@@ -177,17 +201,24 @@ pub async fn callback(
     (new_jar, Redirect::to("/servers"))
 }
 
+/// POST with the session's CSRF token, so a cross-site link can't sign
+/// someone out and revoke their Discord grant.
 pub async fn logout(
+    auth: CsrfAuth,
     State(state): State<WebState>,
     jar: SignedCookieJar,
-) -> (SignedCookieJar, Redirect) {
-    if let Some(session_id) = session::read_cookie(&jar) {
-        let _ = DashboardSession::delete(&state.app_state.db, &session_id).await;
-    }
-    (session::clear(jar), Redirect::to("/"))
+) -> (SignedCookieJar, StatusCode) {
+    clouder_core::shared::end_dashboard_session(
+        &state.app_state,
+        &auth.0.session_id,
+        &auth.0.user_id,
+        |token| revoke_token(&state, token),
+    )
+    .await;
+    (session::clear(jar), StatusCode::NO_CONTENT)
 }
 
-async fn exchange_code(state: &WebState, code: &str) -> Result<String, String> {
+async fn exchange_code(state: &WebState, code: &str) -> Result<String, ExchangeError> {
     let oauth = &state.app_state.config.web.oauth;
     let client = reqwest::Client::new();
 
@@ -202,18 +233,49 @@ async fn exchange_code(state: &WebState, code: &str) -> Result<String, String> {
         ])
         .send()
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| ExchangeError::Other(e.to_string()))?;
 
-    if !response.status().is_success() {
+    let status = response.status();
+    if !status.is_success() {
         let body = response.text().await.unwrap_or_default();
-        return Err(format!("discord token endpoint returned error: {}", body));
+        if is_invalid_grant(status, &body) {
+            return Err(ExchangeError::InvalidGrant);
+        }
+        return Err(ExchangeError::Other(format!(
+            "discord token endpoint returned error: {}",
+            body
+        )));
     }
 
-    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| ExchangeError::Other(e.to_string()))?;
     json["access_token"]
         .as_str()
         .map(|s| s.to_string())
-        .ok_or_else(|| "missing access_token in discord response".to_string())
+        .ok_or_else(|| ExchangeError::Other("missing access_token in discord response".to_string()))
+}
+
+async fn revoke_token(state: &WebState, token: String) -> Result<(), String> {
+    let oauth = &state.app_state.config.web.oauth;
+    let response = reqwest::Client::new()
+        .post("https://discord.com/api/oauth2/token/revoke")
+        .form(&[
+            ("client_id", oauth.client_id.as_str()),
+            ("client_secret", oauth.client_secret.as_str()),
+            ("token", token.as_str()),
+            ("token_type_hint", "access_token"),
+        ])
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("discord revoke endpoint returned error: {}", body));
+    }
+    Ok(())
 }
 
 async fn fetch_user(access_token: &str) -> Result<DiscordProfile, String> {
@@ -236,3 +298,39 @@ async fn fetch_user(access_token: &str) -> Result<DiscordProfile, String> {
         avatar: json["avatar"].as_str().map(|s| s.to_string()),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_must_match_the_signed_cookie() {
+        assert!(oauth_state_valid(Some("abc123"), Some("abc123")));
+
+        assert!(!oauth_state_valid(Some("abc123"), Some("abc124")));
+        assert!(!oauth_state_valid(Some("abc123"), None));
+        assert!(!oauth_state_valid(None, Some("abc123")));
+        assert!(!oauth_state_valid(None, None));
+        // an empty cookie can't vouch for an empty query param
+        assert!(!oauth_state_valid(Some(""), Some("")));
+    }
+
+    #[test]
+    fn reused_codes_are_told_apart_from_other_failures() {
+        let bad_request = reqwest::StatusCode::BAD_REQUEST;
+        assert!(is_invalid_grant(
+            bad_request,
+            r#"{"error": "invalid_grant", "error_description": "Invalid \"code\" in request."}"#
+        ));
+
+        assert!(!is_invalid_grant(
+            bad_request,
+            r#"{"error": "invalid_client"}"#
+        ));
+        assert!(!is_invalid_grant(bad_request, "not json"));
+        assert!(!is_invalid_grant(
+            reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+            r#"{"error": "invalid_grant"}"#
+        ));
+    }
+}
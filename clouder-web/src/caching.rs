@@ -0,0 +1,161 @@
+use axum::body::{Body, HttpBody, to_bytes};
+use axum::extract::Request;
+use axum::http::{HeaderValue, Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+
+// Role and channel lists change rarely and are fetched on every dashboard
+// page, so let the browser reuse them briefly instead of hitting Discord.
+const GUILD_LIST_MAX_AGE_SECS: u32 = 30;
+// API responses are small JSON documents; anything larger (data exports) or of
+// unknown length is passed through untouched rather than buffered and hashed.
+const MAX_ETAG_BODY_BYTES: usize = 1024 * 1024;
+
+fn weak_etag(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("W/\"{}\"", hex)
+}
+
+/// Weak comparison per RFC 9110: `W/` prefixes are ignored, `*` matches anything.
+fn if_none_match_hits(header: &str, etag: &str) -> bool {
+    let strip = |t: &str| t.trim().trim_start_matches("W/").to_string();
+    let etag = strip(etag);
+    header
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || strip(candidate) == etag)
+}
+
+fn cache_control_for(path: &str) -> String {
+    if path.ends_with("/roles") || path.ends_with("/channels") {
+        format!("private, max-age={}", GUILD_LIST_MAX_AGE_SECS)
+    } else {
+        "private, no-cache".to_string()
+    }
+}
+
+/// Adds a weak ETag and `Cache-Control` to successful API GETs and answers
+/// `304 Not Modified` when the client already holds the same body.
+pub async fn etag(req: Request, next: Next) -> Response {
+    if req.method() != Method::GET || !req.uri().path().starts_with("/api/") {
+        return next.run(req).await;
+    }
+
+    let path = req.uri().path().to_string();
+    let if_none_match = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.run(req).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let small_enough = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|len| len <= MAX_ETAG_BODY_BYTES as u64);
+    if !small_enough {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ETAG_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    let etag = weak_etag(&bytes);
+    let cache_control = cache_control_for(&path);
+    let (Ok(etag_value), Ok(cache_value)) = (
+        HeaderValue::from_str(&etag),
+        HeaderValue::from_str(&cache_control),
+    ) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    if if_none_match.is_some_and(|h| if_none_match_hits(&h, &etag)) {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG, etag_value),
+                (header::CACHE_CONTROL, cache_value),
+            ],
+        )
+            .into_response();
+    }
+
+    parts.headers.insert(header::ETAG, etag_value);
+    parts.headers.insert(header::CACHE_CONTROL, cache_value);
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, routing::get};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/api/guild/{guild_id}/roles",
+                get(|| async { "[\"role\"]" }),
+            )
+            .route("/api/selfroles/{guild_id}", get(|| async { "[]" }))
+            .route(
+                "/api/export/{guild_id}",
+                get(|| async { "x".repeat(MAX_ETAG_BODY_BYTES + 1) }),
+            )
+            .layer(axum::middleware::from_fn(etag))
+    }
+
+    async fn get_with(path: &str, if_none_match: Option<&str>) -> Response {
+        let mut req = Request::builder().uri(path);
+        if let Some(tag) = if_none_match {
+            req = req.header(header::IF_NONE_MATCH, tag);
+        }
+        app()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn repeated_get_with_etag_is_not_modified() {
+        let first = get_with("/api/guild/1/roles", None).await;
+        assert_eq!(first.status(), StatusCode::OK);
+        let tag = first.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert!(tag.starts_with("W/\""));
+        assert_eq!(
+            first.headers()[header::CACHE_CONTROL],
+            format!("private, max-age={}", GUILD_LIST_MAX_AGE_SECS)
+        );
+
+        let second = get_with("/api/guild/1/roles", Some(&tag)).await;
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert!(to_bytes(second.into_body(), 16).await.unwrap().is_empty());
+
+        let stale = get_with("/api/guild/1/roles", Some("W/\"nope\"")).await;
+        assert_eq!(stale.status(), StatusCode::OK);
+
+        let other = get_with("/api/selfroles/1", None).await;
+        assert_eq!(other.headers()[header::CACHE_CONTROL], "private, no-cache");
+    }
+
+    #[tokio::test]
+    async fn oversized_bodies_pass_through_without_an_etag() {
+        let res = get_with("/api/export/1", None).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(header::ETAG).is_none());
+
+        let body = to_bytes(res.into_body(), MAX_ETAG_BODY_BYTES * 2)
+            .await
+            .unwrap();
+        assert_eq!(body.len(), MAX_ETAG_BODY_BYTES + 1);
+    }
+}
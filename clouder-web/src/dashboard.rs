@@ -17,9 +17,12 @@ static SELFROLES_HTML: &str = include_str!("../templates/selfroles.html");
 static WELCOME_HTML: &str = include_str!("../templates/welcome_goodbye.html");
 static MEDIAONLY_HTML: &str = include_str!("../templates/mediaonly.html");
 static ABOUT_HTML: &str = include_str!("../templates/about.html");
+static OVERVIEW_HTML: &str = include_str!("../templates/overview.html");
 static UWUFY_HTML: &str = include_str!("../templates/uwufy.html");
+static AI_USAGE_HTML: &str = include_str!("../templates/ai_usage.html");
 static PROFILE_HTML: &str = include_str!("../templates/profile.html");
 static REMINDERS_HTML: &str = include_str!("../templates/reminders.html");
+static SCHEDULED_HTML: &str = include_str!("../templates/scheduled.html");
 
 async fn guild_name_or_id(state: &WebState, user_id: &str, guild_id: &str) -> String {
     CachedGuild::get_name(&state.app_state.db, user_id, guild_id)
@@ -55,6 +58,7 @@ fn render_sidebar(guild_id: &str, active: &str, raw_perms: i64) -> String {
     let perms = Permissions::from_bits_truncate(raw_perms as u64);
 
     let pages: &[(&str, &str, Permissions)] = &[
+        ("overview", "overview", Permissions::MANAGE_GUILD),
         ("about", "about", Permissions::MANAGE_GUILD),
         ("selfroles", "self-roles", Permissions::MANAGE_ROLES),
         (
@@ -63,8 +67,10 @@ fn render_sidebar(guild_id: &str, active: &str, raw_perms: i64) -> String {
             Permissions::MANAGE_GUILD,
         ),
         ("reminders", "reminders", Permissions::MANAGE_GUILD),
+        ("scheduled", "scheduled posts", Permissions::MANAGE_GUILD),
         ("mediaonly", "media-only", Permissions::MANAGE_CHANNELS),
         ("uwufy", "uwufy", Permissions::MANAGE_GUILD),
+        ("ai", "ai usage", Permissions::MANAGE_GUILD),
     ];
 
     pages
@@ -162,6 +168,8 @@ pub async fn login_page(
                 "denied" => "access denied.",
                 "auth_failed" => "authentication failed. try again.",
                 "missing_code" => "invalid oauth response.",
+                "state_mismatch" => "login expired or didn't start here. try again.",
+                "expired" => "that login link was already used or expired. try again.",
                 _ => "something went wrong.",
             };
             format!(r#"<p class="error-msg">{}</p>"#, html_escape(msg))
@@ -243,7 +251,7 @@ fn render_guild_cards(guilds: &[CachedGuild]) -> String {
                 }
             };
             format!(
-                r#"<div class="card server-card" role="button" tabindex="0" onclick="location.href='/dashboard/{gid}/about'" onkeydown="if(event.key==='Enter')location.href='/dashboard/{gid}/about'">
+                r#"<div class="card server-card" role="button" tabindex="0" onclick="location.href='/dashboard/{gid}/overview'" onkeydown="if(event.key==='Enter')location.href='/dashboard/{gid}/overview'">
                     {icon}
                     <div class="server-info">
                         <span class="server-name">{name}</span>
@@ -264,7 +272,20 @@ pub async fn dashboard_redirect(Path(guild_id): Path<String>) -> Response {
     if parse_snowflake(&guild_id).is_none() {
         return Redirect::to("/servers").into_response();
     }
-    Redirect::to(&format!("/dashboard/{}/about", guild_id)).into_response()
+    Redirect::to(&format!("/dashboard/{}/overview", guild_id)).into_response()
+}
+
+/// Decides whether cached permissions let the user into a guild page. A guild
+/// missing from the cache (left, kicked, or a stale OAuth guild list) goes back
+/// to `/servers`, which re-fetches the list from Discord on load.
+fn guild_access(cached: Option<i64>, required: Permissions) -> Result<i64, Redirect> {
+    let Some(raw_perms) = cached else {
+        return Err(Redirect::to("/servers"));
+    };
+    if !has_permission(Permissions::from_bits_truncate(raw_perms as u64), required) {
+        return Err(Redirect::to("/servers"));
+    }
+    Ok(raw_perms)
 }
 
 /// Common scaffolding for permission-gated dashboard pages: extracts the
@@ -291,13 +312,8 @@ async fn page_context(
     if parse_snowflake(raw_guild_id).is_none() {
         return Err(Redirect::to("/servers").into_response());
     }
-    let Some(raw_perms) = guild_perms(state, &user.user_id, raw_guild_id).await else {
-        return Err(Redirect::to("/servers").into_response());
-    };
-    let perms = Permissions::from_bits_truncate(raw_perms as u64);
-    if !has_permission(perms, required) {
-        return Err(Redirect::to("/servers").into_response());
-    }
+    let cached = guild_perms(state, &user.user_id, raw_guild_id).await;
+    let raw_perms = guild_access(cached, required).map_err(IntoResponse::into_response)?;
     let guild_name = guild_name_or_id(state, &user.user_id, raw_guild_id).await;
     let sidebar = render_sidebar(raw_guild_id, active, raw_perms);
     let profile = load_profile(state, &user).await;
@@ -406,6 +422,37 @@ pub async fn mediaonly_page(
     .into_response()
 }
 
+pub async fn overview_page(
+    State(state): State<WebState>,
+    jar: SignedCookieJar,
+    Path(guild_id): Path<String>,
+) -> Response {
+    let ctx = match page_context(
+        &state,
+        jar,
+        &guild_id,
+        "overview",
+        Permissions::MANAGE_GUILD,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(r) => return r,
+    };
+    Html(render(
+        OVERVIEW_HTML,
+        &[
+            ("USERNAME", &ctx.profile.username),
+            ("AVATAR_URL", &ctx.profile.avatar_url),
+            ("GUILD_ID", &ctx.guild_id),
+            ("GUILD_NAME", &ctx.guild_name),
+            ("SIDEBAR_LINKS", &ctx.sidebar),
+            ("CSRF_TOKEN", &ctx.csrf),
+        ],
+    ))
+    .into_response()
+}
+
 pub async fn about_page(
     State(state): State<WebState>,
     jar: SignedCookieJar,
@@ -452,6 +499,60 @@ pub async fn uwufy_page(
     .into_response()
 }
 
+pub async fn scheduled_page(
+    State(state): State<WebState>,
+    jar: SignedCookieJar,
+    Path(guild_id): Path<String>,
+) -> Response {
+    let ctx = match page_context(
+        &state,
+        jar,
+        &guild_id,
+        "scheduled",
+        Permissions::MANAGE_GUILD,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(r) => return r,
+    };
+    Html(render(
+        SCHEDULED_HTML,
+        &[
+            ("USERNAME", &ctx.profile.username),
+            ("AVATAR_URL", &ctx.profile.avatar_url),
+            ("GUILD_ID", &ctx.guild_id),
+            ("GUILD_NAME", &ctx.guild_name),
+            ("SIDEBAR_LINKS", &ctx.sidebar),
+            ("CSRF_TOKEN", &ctx.csrf),
+        ],
+    ))
+    .into_response()
+}
+
+pub async fn ai_usage_page(
+    State(state): State<WebState>,
+    jar: SignedCookieJar,
+    Path(guild_id): Path<String>,
+) -> Response {
+    let ctx = match page_context(&state, jar, &guild_id, "ai", Permissions::MANAGE_GUILD).await {
+        Ok(c) => c,
+        Err(r) => return r,
+    };
+    Html(render(
+        AI_USAGE_HTML,
+        &[
+            ("USERNAME", &ctx.profile.username),
+            ("AVATAR_URL", &ctx.profile.avatar_url),
+            ("GUILD_ID", &ctx.guild_id),
+            ("GUILD_NAME", &ctx.guild_name),
+            ("SIDEBAR_LINKS", &ctx.sidebar),
+            ("CSRF_TOKEN", &ctx.csrf),
+        ],
+    ))
+    .into_response()
+}
+
 pub async fn profile_page(State(state): State<WebState>, jar: SignedCookieJar) -> Response {
     let Some(user) = session::extract(&state, &jar).await else {
         return (session::clear(jar), Redirect::to("/login")).into_response();
@@ -533,3 +634,40 @@ pub async fn reminders_page(
     ))
     .into_response()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{StatusCode, header};
+
+    fn location(redirect: Redirect) -> String {
+        let res = redirect.into_response();
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        res.headers()[header::LOCATION]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn missing_guild_redirects_to_servers() {
+        let denied = guild_access(None, Permissions::MANAGE_GUILD).unwrap_err();
+        assert_eq!(location(denied), "/servers");
+    }
+
+    #[test]
+    fn insufficient_permissions_redirect_to_servers() {
+        let perms = Permissions::SEND_MESSAGES.bits() as i64;
+        let denied = guild_access(Some(perms), Permissions::MANAGE_GUILD).unwrap_err();
+        assert_eq!(location(denied), "/servers");
+    }
+
+    #[test]
+    fn cached_permissions_are_passed_through() {
+        let perms = Permissions::MANAGE_GUILD.bits() as i64;
+        assert_eq!(
+            guild_access(Some(perms), Permissions::MANAGE_GUILD).unwrap(),
+            perms
+        );
+    }
+}
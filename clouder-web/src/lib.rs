@@ -1,11 +1,15 @@
 mod api;
 mod auth;
+mod caching;
 mod dashboard;
+mod request_id;
 mod session;
 
 use anyhow::Result;
+use axum::BoxError;
+use axum::error_handling::HandleErrorLayer;
 use axum::extract::DefaultBodyLimit;
-use axum::http::{HeaderName, HeaderValue};
+use axum::http::{HeaderName, HeaderValue, StatusCode};
 use axum::{Router, routing::get, routing::post};
 use axum_extra::extract::cookie::Key;
 use clouder_core::database::dashboard_sessions::DashboardSession;
@@ -14,9 +18,12 @@ use sha2::Sha256;
 use std::sync::Arc;
 use std::time::Duration;
 use tower::ServiceBuilder;
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower::load_shed::LoadShedLayer;
 use tower_governor::GovernorLayer;
 use tower_governor::governor::GovernorConfigBuilder;
 use tower_http::set_header::SetResponseHeaderLayer;
+use tower_http::timeout::TimeoutLayer;
 use tracing::{info, warn};
 
 pub use clouder_core::config::AppState;
@@ -59,6 +66,24 @@ impl axum::extract::FromRef<WebState> for Key {
     }
 }
 
+/// Sheds requests beyond `max_concurrent` in flight with `503` instead of
+/// queueing them, and answers `408` to any that run longer than `timeout`.
+/// The semaphore is shared across every route, so the cap is server-wide.
+fn with_load_limits(app: Router, max_concurrent: usize, timeout: Duration) -> Router {
+    app.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(|_: BoxError| async {
+                StatusCode::SERVICE_UNAVAILABLE
+            }))
+            .layer(LoadShedLayer::new())
+            .layer(GlobalConcurrencyLimitLayer::new(max_concurrent))
+            .layer(TimeoutLayer::with_status_code(
+                StatusCode::REQUEST_TIMEOUT,
+                timeout,
+            )),
+    )
+}
+
 pub async fn run(app_state: AppState) -> Result<()> {
     info!("starting API: {}/api", app_state.config.web.bind_addr,);
 
@@ -166,12 +191,21 @@ pub async fn run(app_state: AppState) -> Result<()> {
             "/dashboard/{guild_id}/welcome-goodbye",
             get(dashboard::welcome_goodbye_page),
         )
+        .route(
+            "/dashboard/{guild_id}/overview",
+            get(dashboard::overview_page),
+        )
         .route("/dashboard/{guild_id}/about", get(dashboard::about_page))
         .route(
             "/dashboard/{guild_id}/mediaonly",
             get(dashboard::mediaonly_page),
         )
         .route("/dashboard/{guild_id}/uwufy", get(dashboard::uwufy_page))
+        .route(
+            "/dashboard/{guild_id}/scheduled",
+            get(dashboard::scheduled_page),
+        )
+        .route("/dashboard/{guild_id}/ai", get(dashboard::ai_usage_page))
         .route(
             "/dashboard/{guild_id}/reminders",
             get(dashboard::reminders_page),
@@ -180,7 +214,7 @@ pub async fn run(app_state: AppState) -> Result<()> {
         // auth
         .route("/auth/login", get(auth::login))
         .route("/auth/callback", get(auth::callback))
-        .route("/auth/logout", get(auth::logout))
+        .route("/auth/logout", post(auth::logout))
         // static assets
         .route("/static/style.css", get(static_css))
         .route("/static/app.js", get(static_js))
@@ -188,6 +222,10 @@ pub async fn run(app_state: AppState) -> Result<()> {
         .route("/api/guilds/refresh", post(api::api_guilds_refresh))
         .route("/api/guild/{guild_id}/channels", get(api::api_get_channels))
         .route("/api/guild/{guild_id}/roles", get(api::api_get_roles))
+        .route(
+            "/api/selfroles/templates",
+            get(api::api_selfroles_templates),
+        )
         .route(
             "/api/selfroles/{guild_id}",
             get(api::api_selfroles_list).post(api::api_selfroles_create),
@@ -196,9 +234,27 @@ pub async fn run(app_state: AppState) -> Result<()> {
             "/api/selfroles/{guild_id}/{config_id}",
             axum::routing::delete(api::api_selfroles_delete).put(api::api_selfroles_update),
         )
+        .route(
+            "/api/selfroles/{guild_id}/{config_id}/channel",
+            axum::routing::patch(api::api_selfroles_move_channel),
+        )
+        .route(
+            "/api/selfroles/{guild_id}/attach",
+            post(api::api_selfroles_attach),
+        )
+        .route(
+            "/api/selfroles/{guild_id}/emoji-policy",
+            get(api::api_selfroles_emoji_policy_get).put(api::api_selfroles_emoji_policy_update),
+        )
+        .route(
+            "/api/selfroles/{guild_id}/settings",
+            get(api::api_selfroles_settings_get).put(api::api_selfroles_settings_update),
+        )
         .route(
             "/api/welcome-goodbye/{guild_id}/config",
-            get(api::api_welcome_goodbye_get).post(api::api_welcome_goodbye_post),
+            get(api::api_welcome_goodbye_get)
+                .post(api::api_welcome_goodbye_post)
+                .patch(api::api_welcome_goodbye_patch),
         )
         .route(
             "/api/welcome-goodbye/{guild_id}/test/{message_type}",
@@ -208,15 +264,35 @@ pub async fn run(app_state: AppState) -> Result<()> {
             "/api/mediaonly/{guild_id}",
             get(api::api_mediaonly_get).post(api::api_mediaonly_post),
         )
+        .route(
+            "/api/mediaonly/{guild_id}/bulk",
+            post(api::api_mediaonly_bulk),
+        )
+        .route(
+            "/api/mediaonly/{guild_id}/stats",
+            get(api::api_mediaonly_stats),
+        )
         .route(
             "/api/mediaonly/{guild_id}/{channel_id}",
             axum::routing::delete(api::api_mediaonly_delete).put(api::api_mediaonly_put),
         )
         .route("/api/guild/{guild_id}/about", get(api::api_about_get))
+        .route(
+            "/api/guild/{guild_id}/overview",
+            get(api::api_guild_overview),
+        )
         .route(
             "/api/guild/{guild_id}/config",
             get(api::api_guild_config_get).post(api::api_guild_config_post),
         )
+        .route(
+            "/api/guild/{guild_id}/data-export",
+            get(api::api_guild_data_export),
+        )
+        .route(
+            "/api/guild/{guild_id}/data",
+            axum::routing::delete(api::api_guild_data_delete),
+        )
         .route(
             "/api/uwufy/{guild_id}",
             get(api::api_uwufy_get).delete(api::api_uwufy_disable_all),
@@ -225,6 +301,11 @@ pub async fn run(app_state: AppState) -> Result<()> {
             "/api/uwufy/{guild_id}/{user_id}",
             axum::routing::put(api::api_uwufy_toggle),
         )
+        .route("/api/ai/{guild_id}/usage", get(api::api_ai_usage_get))
+        .route(
+            "/api/ai/{guild_id}/settings",
+            get(api::api_ai_settings_get).put(api::api_ai_settings_update),
+        )
         .route("/api/profile/regenerate-key", post(api::api_regenerate_key))
         .route(
             "/api/reminders/{guild_id}",
@@ -248,6 +329,15 @@ pub async fn run(app_state: AppState) -> Result<()> {
             "/api/custom-reminders/{guild_id}/{reminder_id}/test",
             post(api::api_custom_reminder_test),
         )
+        // scheduled message endpoints
+        .route(
+            "/api/scheduled-messages/{guild_id}",
+            get(api::api_scheduled_messages_list).post(api::api_scheduled_message_create),
+        )
+        .route(
+            "/api/scheduled-messages/{guild_id}/{message_id}",
+            axum::routing::delete(api::api_scheduled_message_delete),
+        )
         // user-specific reminder endpoints
         .route(
             "/api/user/dm_reminders",
@@ -269,11 +359,25 @@ pub async fn run(app_state: AppState) -> Result<()> {
             "/api/user/subscription/{id}",
             axum::routing::delete(api::api_user_subscription_delete),
         )
+        // user data governance
+        .route("/api/user/data-export", get(api::api_user_data_export))
+        .route(
+            "/api/user/data",
+            axum::routing::delete(api::api_user_data_delete),
+        )
+        .layer(axum::middleware::from_fn(caching::etag))
         .layer(DefaultBodyLimit::max(DEFAULT_BODY_LIMIT_BYTES))
         .layer(rate_limit)
         .layer(security_headers)
         .with_state(state.clone())
         .merge(dm_route);
+    let app = with_load_limits(
+        app,
+        state.app_state.config.web.max_concurrent_requests,
+        Duration::from_secs(state.app_state.config.web.request_timeout_secs),
+    );
+    // outermost, so even shed or timed-out requests get an id
+    let app = app.layer(axum::middleware::from_fn(request_id::request_id));
 
     let listener = tokio::net::TcpListener::bind(&state.app_state.config.web.bind_addr).await?;
     info!(
@@ -321,11 +425,23 @@ async fn static_js() -> impl axum::response::IntoResponse {
 
 #[cfg(test)]
 mod tests {
+    use axum::body::Body;
+    use axum::extract::Request;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Notify;
+    use tower::ServiceExt;
+
     const AUTHENTICATED_TEMPLATES: &[&str] = &[
         include_str!("../templates/about.html"),
+        include_str!("../templates/ai_usage.html"),
         include_str!("../templates/mediaonly.html"),
+        include_str!("../templates/overview.html"),
         include_str!("../templates/profile.html"),
         include_str!("../templates/reminders.html"),
+        include_str!("../templates/scheduled.html"),
         include_str!("../templates/selfroles.html"),
         include_str!("../templates/servers.html"),
         include_str!("../templates/uwufy.html"),
@@ -339,6 +455,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sign_out_goes_through_the_csrf_checked_post() {
+        for template in AUTHENTICATED_TEMPLATES {
+            assert!(!template.contains(r#"href="/auth/logout""#));
+        }
+        let app_js = include_str!("../static/app.js");
+        assert!(app_js.contains("apiFetch('POST', '/auth/logout')"));
+    }
+
     #[test]
     fn app_js_sends_csrf_header() {
         let app_js = include_str!("../static/app.js");
@@ -346,4 +471,61 @@ mod tests {
         assert!(app_js.contains("X-CSRF-Token"));
         assert!(app_js.contains("credentials: 'same-origin'"));
     }
+
+    /// `/slow` signals `entered` once it holds a permit, then waits on `release`.
+    fn slow_app(
+        max_concurrent: usize,
+        timeout: Duration,
+    ) -> (super::Router, Arc<Notify>, Arc<Notify>) {
+        let entered = Arc::new(Notify::new());
+        let release = Arc::new(Notify::new());
+        let (signal, held) = (entered.clone(), release.clone());
+        let app = super::Router::new().route(
+            "/slow",
+            get(move || {
+                let (signal, held) = (signal.clone(), held.clone());
+                async move {
+                    signal.notify_one();
+                    held.notified().await;
+                    "done"
+                }
+            }),
+        );
+        (
+            super::with_load_limits(app, max_concurrent, timeout),
+            entered,
+            release,
+        )
+    }
+
+    fn slow_request() -> Request<Body> {
+        Request::builder().uri("/slow").body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_beyond_limit_are_shed() {
+        let (app, entered, release) = slow_app(1, Duration::from_secs(30));
+
+        let first = tokio::spawn(app.clone().oneshot(slow_request()));
+        // The first request is in the handler, so it holds the only permit.
+        entered.notified().await;
+
+        let shed = app.clone().oneshot(slow_request()).await.unwrap();
+        assert_eq!(shed.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        release.notify_one();
+        let first = first.await.unwrap().unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+
+        release.notify_one();
+        let after = app.oneshot(slow_request()).await.unwrap();
+        assert_eq!(after.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn slow_requests_time_out() {
+        let (app, _entered, _release) = slow_app(1, Duration::from_millis(20));
+        let res = app.oneshot(slow_request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
+    }
 }
@@ -0,0 +1,116 @@
+use axum::extract::Request;
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use clouder_core::crypto::random_hex;
+use tracing::Instrument;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+// 8 random bytes is plenty to tell concurrent requests apart in the logs.
+const REQUEST_ID_BYTES: usize = 8;
+// Caller-supplied ids longer than this are replaced rather than logged.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// Keeps a caller's id only if it's short and plain enough to drop into a log line.
+fn incoming_id(value: &HeaderValue) -> Option<String> {
+    let id = value.to_str().ok()?;
+    let valid = !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    valid.then(|| id.to_string())
+}
+
+/// Tags each request with an `X-Request-Id`, reusing the caller's when it
+/// sent a sane one. Everything logged while handling the request runs inside
+/// a span carrying the id, and the response echoes it back.
+pub async fn request_id(mut req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(incoming_id)
+        .unwrap_or_else(|| random_hex(REQUEST_ID_BYTES));
+    let header = HeaderValue::from_str(&id).expect("request ids are visible ascii");
+    req.headers_mut().insert(REQUEST_ID_HEADER, header.clone());
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+    let mut response = next.run(req).instrument(span).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, header);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::{Router, routing::get};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        // echo what the handler saw so the test can check it matches the response
+        Router::new()
+            .route(
+                "/api/ping",
+                get(|req: Request| async move {
+                    req.headers()
+                        .get(&REQUEST_ID_HEADER)
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or_default()
+                        .to_string()
+                }),
+            )
+            .layer(axum::middleware::from_fn(request_id))
+    }
+
+    async fn get_with(id: Option<&str>) -> Response {
+        let mut req = Request::builder().uri("/api/ping");
+        if let Some(id) = id {
+            req = req.header(&REQUEST_ID_HEADER, id);
+        }
+        app()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    fn response_id(res: &Response) -> String {
+        res.headers()
+            .get(&REQUEST_ID_HEADER)
+            .expect("response carries a request id")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn generates_an_id_when_none_is_sent() {
+        let res = get_with(None).await;
+        let id = response_id(&res);
+        assert_eq!(id.len(), REQUEST_ID_BYTES * 2);
+
+        let body = axum::body::to_bytes(res.into_body(), 1024).await.unwrap();
+        assert_eq!(body, id.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn propagates_a_callers_id() {
+        let res = get_with(Some("trace-123")).await;
+        assert_eq!(response_id(&res), "trace-123");
+    }
+
+    #[tokio::test]
+    async fn replaces_unsafe_ids() {
+        let long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+        for bad in ["has space", "line\tbreak", long.as_str()] {
+            let res = get_with(Some(bad)).await;
+            assert_ne!(response_id(&res), bad);
+        }
+    }
+}
@@ -40,10 +40,6 @@ pub fn store_cookie(jar: SignedCookieJar, session_id: &str, secure: bool) -> Sig
     jar.add(cookie)
 }
 
-pub fn read_cookie(jar: &SignedCookieJar) -> Option<String> {
-    jar.get(COOKIE_NAME).map(|c| c.value().to_string())
-}
-
 pub fn clear(jar: SignedCookieJar) -> SignedCookieJar {
     let mut cookie = Cookie::from(COOKIE_NAME);
     cookie.set_path("/");
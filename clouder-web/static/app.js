@@ -50,6 +50,12 @@ async function apiFetch(method, url, body) {
   return res;
 }
 
+// logout is a csrf-checked POST, so the sign out buttons go through here
+async function signOut() {
+  await apiFetch('POST', '/auth/logout');
+  window.location.href = '/';
+}
+
 // HTML escape utility - used across all templates
 function escHtml(s) {
   if (s == null) return '';
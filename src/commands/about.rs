@@ -3,6 +3,7 @@ use clouder_core::config::AppState;
 use clouder_core::utils::{discord_timestamp, format_duration, get_embed_color};
 use lazy_static::lazy_static;
 use poise::serenity_prelude as serenity;
+use serde::Serialize;
 use serenity::CreateEmbed;
 use sqlx::SqlitePool;
 use std::time::SystemTime;
@@ -15,13 +16,15 @@ lazy_static! {
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, AppState, Error>;
 
-struct BotDbStats {
-    selfrole_configs: i64,
-    selfrole_roles: i64,
-    active_cooldowns: i64,
-    guilds: i64,
-    recent_configs: i64,
-    expired_cooldowns: i64,
+/// Selfrole counts from the database, as shown by `/about bot`.
+#[derive(Debug, Serialize)]
+pub struct BotDbStats {
+    pub selfrole_configs: i64,
+    pub selfrole_roles: i64,
+    pub active_cooldowns: i64,
+    pub guilds: i64,
+    pub recent_configs: i64,
+    pub expired_cooldowns: i64,
 }
 
 async fn fetch_bot_db_stats(db: &SqlitePool) -> BotDbStats {
@@ -61,16 +64,64 @@ async fn fetch_bot_db_stats(db: &SqlitePool) -> BotDbStats {
     }
 }
 
-#[poise::command(slash_command, subcommands("bot", "server", "user", "role", "channel"))]
-pub async fn about(_ctx: Context<'_>) -> Result<(), Error> {
-    Ok(())
+#[derive(Debug, Serialize)]
+pub struct LatencyStats {
+    pub api_ms: u128,
+    pub gateway_ms: u128,
 }
 
-#[poise::command(slash_command)]
-pub async fn bot(ctx: Context<'_>) -> Result<(), Error> {
+#[derive(Debug, Serialize)]
+pub struct DiscordStats {
+    pub guilds: usize,
+    pub cached_users: usize,
+    pub cached_channels: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CpuStats {
+    pub usage_percent: f32,
+    pub cores: usize,
+    pub arch: &'static str,
+}
+
+/// Sizes are in MB.
+#[derive(Debug, Serialize)]
+pub struct MemoryStats {
+    pub used_mb: u64,
+    pub available_mb: u64,
+    pub total_mb: u64,
+    pub used_percent: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SystemStats {
+    pub os: String,
+    pub kernel: String,
+    pub pid: u32,
+}
+
+/// Everything `/about bot` reports, shared by the embed and the JSON output.
+#[derive(Debug, Serialize)]
+pub struct BotStatus {
+    pub bot_id: u64,
+    pub version: &'static str,
+    pub uptime_secs: u64,
+    pub latency: LatencyStats,
+    pub discord: DiscordStats,
+    pub database: BotDbStats,
+    pub cpu: CpuStats,
+    pub memory: MemoryStats,
+    pub system: SystemStats,
+}
+
+impl BotStatus {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+async fn gather_bot_status(ctx: Context<'_>) -> Result<(BotStatus, serenity::CurrentUser), Error> {
     let uptime = BOT_START_TIME.elapsed().unwrap_or_default();
-    let uptime_str = format_duration(uptime.as_secs());
-    let bot_version = env!("CARGO_PKG_VERSION");
 
     let start = std::time::Instant::now();
     let _ = ctx.http().get_current_user().await;
@@ -88,14 +139,6 @@ pub async fn bot(ctx: Context<'_>) -> Result<(), Error> {
     let available_memory = sys.available_memory() / 1024 / 1024;
     let memory_percentage = (used_memory as f64 / total_memory as f64) * 100.0;
 
-    let cpu_count = sys.cpus().len();
-    let cpu_usage = sys.global_cpu_usage();
-
-    let current_pid = std::process::id();
-
-    let guild_count = ctx.cache().guild_count();
-    let cached_users = ctx.cache().user_count();
-
     let mut cached_channels = 0;
     for guild_id in ctx.cache().guilds() {
         if let Some(guild) = ctx.cache().guild(guild_id) {
@@ -105,7 +148,73 @@ pub async fn bot(ctx: Context<'_>) -> Result<(), Error> {
 
     let bot_user = ctx.http().get_current_user().await?;
 
-    let db_stats_raw = fetch_bot_db_stats(&ctx.data().db).await;
+    let status = BotStatus {
+        bot_id: bot_user.id.get(),
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_secs: uptime.as_secs(),
+        latency: LatencyStats {
+            api_ms: api_latency.as_millis(),
+            gateway_ms: gateway_latency.as_millis(),
+        },
+        discord: DiscordStats {
+            guilds: ctx.cache().guild_count(),
+            cached_users: ctx.cache().user_count(),
+            cached_channels,
+        },
+        database: fetch_bot_db_stats(&ctx.data().db).await,
+        cpu: CpuStats {
+            usage_percent: sys.global_cpu_usage(),
+            cores: sys.cpus().len(),
+            arch: std::env::consts::ARCH,
+        },
+        memory: MemoryStats {
+            used_mb: used_memory,
+            available_mb: available_memory,
+            total_mb: total_memory,
+            used_percent: memory_percentage,
+        },
+        system: SystemStats {
+            os: format!(
+                "{} {}",
+                System::name().unwrap_or_else(|| "Unknown".to_string()),
+                System::os_version().unwrap_or_else(|| "Unknown".to_string())
+            ),
+            kernel: System::kernel_version().unwrap_or_else(|| "Unknown".to_string()),
+            pid: std::process::id(),
+        },
+    };
+    Ok((status, bot_user))
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum StatusFormat {
+    #[name = "embed"]
+    Embed,
+    #[name = "json"]
+    Json,
+}
+
+#[poise::command(slash_command, subcommands("bot", "server", "user", "role", "channel"))]
+pub async fn about(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+pub async fn bot(
+    ctx: Context<'_>,
+    #[description = "output format (default: embed)"] format: Option<StatusFormat>,
+) -> Result<(), Error> {
+    let (status, bot_user) = gather_bot_status(ctx).await?;
+
+    if let Some(StatusFormat::Json) = format {
+        ctx.send(
+            poise::CreateReply::default().content(format!("```json\n{}\n```", status.to_json())),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let db = &status.database;
     let db_stats = format!(
         "configs: **`{}`**
         roles: **`{}`**
@@ -113,27 +222,20 @@ pub async fn bot(ctx: Context<'_>) -> Result<(), Error> {
         servers: **`{}`**
         recent (7d): **`{}`**
         expired: **`{}`**",
-        db_stats_raw.selfrole_configs,
-        db_stats_raw.selfrole_roles,
-        db_stats_raw.active_cooldowns,
-        db_stats_raw.guilds,
-        db_stats_raw.recent_configs,
-        db_stats_raw.expired_cooldowns
-    );
-
-    let os_info = format!(
-        "{} {}",
-        System::name().unwrap_or_else(|| "Unknown".to_string()),
-        System::os_version().unwrap_or_else(|| "Unknown".to_string())
+        db.selfrole_configs,
+        db.selfrole_roles,
+        db.active_cooldowns,
+        db.guilds,
+        db.recent_configs,
+        db.expired_cooldowns
     );
-    let kernel_version = System::kernel_version().unwrap_or_else(|| "Unknown".to_string());
 
     let embed = CreateEmbed::new()
         .title("info")
         .description(format!(
             "<@{}> `{}`
             v{}",
-            bot_user.id, bot_user.id, bot_version
+            bot_user.id, bot_user.id, status.version
         ))
         .color(get_embed_color(ctx.data(), ctx.guild_id().map(|g| g.get())).await)
         .thumbnail(bot_user.face())
@@ -143,9 +245,9 @@ pub async fn bot(ctx: Context<'_>) -> Result<(), Error> {
                 "uptime: **`{}`**
                 api: **`{}ms`**
                 gateway: **`{}ms`**",
-                uptime_str,
-                api_latency.as_millis(),
-                gateway_latency.as_millis()
+                format_duration(status.uptime_secs),
+                status.latency.api_ms,
+                status.latency.gateway_ms
             ),
             true,
         )
@@ -156,7 +258,7 @@ pub async fn bot(ctx: Context<'_>) -> Result<(), Error> {
                 **cached:**
                 users: **`{}`**
                 channels: **`{}`**",
-                guild_count, cached_users, cached_channels
+                status.discord.guilds, status.discord.cached_users, status.discord.cached_channels
             ),
             true,
         )
@@ -167,9 +269,7 @@ pub async fn bot(ctx: Context<'_>) -> Result<(), Error> {
                 "usage: **{:.1}%**
                 cores: **`{}`**
                 arch: **`{}`**",
-                cpu_usage,
-                cpu_count,
-                std::env::consts::ARCH
+                status.cpu.usage_percent, status.cpu.cores, status.cpu.arch
             ),
             true,
         )
@@ -179,7 +279,10 @@ pub async fn bot(ctx: Context<'_>) -> Result<(), Error> {
                 "used: **{:.1}% ({}MB)**
                 free: **{:.3}MB**
                 total: **{:.3}MB**",
-                memory_percentage, used_memory, available_memory, total_memory
+                status.memory.used_percent,
+                status.memory.used_mb,
+                status.memory.available_mb,
+                status.memory.total_mb
             ),
             true,
         )
@@ -189,7 +292,7 @@ pub async fn bot(ctx: Context<'_>) -> Result<(), Error> {
                 "**`{}`**
                 **`{}`**
                 bot pid: **`{}`**",
-                os_info, kernel_version, current_pid
+                status.system.os, status.system.kernel, status.system.pid
             ),
             true,
         )
@@ -574,6 +677,57 @@ pub async fn server(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Whether `about user` may show the account's locale, email and verification
+/// status. These only populate for accounts the token can see privately (the
+/// bot's own user), so they're reserved for the bot owner.
+pub fn shows_private_user_fields(invoker_id: u64, bot_owner: u64) -> bool {
+    invoker_id == bot_owner
+}
+
+/// Adds the private fields `user` has set when `show_private`, and reports
+/// whether any were added.
+pub fn add_private_user_fields(
+    mut embed: CreateEmbed,
+    user: &serenity::User,
+    show_private: bool,
+) -> (CreateEmbed, bool) {
+    if !show_private {
+        return (embed, false);
+    }
+    let mut included = false;
+
+    if let Some(locale) = &user.locale {
+        embed = embed.field("locale", format!("**`{}`**", locale), true);
+        included = true;
+    }
+
+    if let Some(verified) = user.verified {
+        embed = embed.field(
+            "email verified",
+            if verified { "**yes**" } else { "**no**" },
+            true,
+        );
+        included = true;
+    }
+
+    if let Some(email) = &user.email
+        && !email.is_empty()
+    {
+        embed = embed.field("email", format!("**`{}`**", email), true);
+        included = true;
+    }
+
+    (embed, included)
+}
+
+/// The `about user` reply. Private fields only ever go to the invoker, so the
+/// reply is ephemeral when they're included.
+pub fn user_info_reply(embed: CreateEmbed, private_included: bool) -> poise::CreateReply {
+    poise::CreateReply::default()
+        .embed(embed)
+        .ephemeral(private_included)
+}
+
 #[poise::command(slash_command)]
 pub async fn user(
     ctx: Context<'_>,
@@ -629,23 +783,9 @@ pub async fn user(
         embed = embed.field("system user", "yes", true);
     }
 
-    if let Some(locale) = &full_user.locale {
-        embed = embed.field("locale", format!("**`{}`**", locale), true);
-    }
-
-    if let Some(verified) = full_user.verified {
-        embed = embed.field(
-            "email verified",
-            if verified { "**yes**" } else { "**no**" },
-            true,
-        );
-    }
-
-    if let Some(email) = &full_user.email
-        && !email.is_empty()
-    {
-        embed = embed.field("email", format!("**`{}`**", email), true);
-    }
+    let show_private =
+        shows_private_user_fields(ctx.author().id.get(), ctx.data().config.discord.bot_owner);
+    let (mut embed, private_included) = add_private_user_fields(embed, &full_user, show_private);
 
     if full_user.mfa_enabled {
         embed = embed.field("2FA enabled", "**yes**", true);
@@ -835,7 +975,7 @@ pub async fn user(
 
     embed = embed.timestamp(serenity::Timestamp::now());
 
-    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    ctx.send(user_info_reply(embed, private_included)).await?;
     Ok(())
 }
 
@@ -0,0 +1,187 @@
+use anyhow::Result;
+use clouder_core::config::AppState;
+use clouder_core::database::command_aliases::CommandAlias;
+use clouder_core::utils::get_embed_color;
+use poise::serenity_prelude as serenity;
+use serenity::CreateEmbed;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Context<'a> = poise::Context<'a, AppState, Error>;
+
+const MAX_ALIAS_LEN: usize = 32;
+const MAX_ALIASES_PER_GUILD: i64 = 25;
+// 25 long aliases overflow an embed field, so /help cuts the list short
+pub const EMBED_FIELD_LIMIT: usize = 1024;
+const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+
+#[poise::command(slash_command, subcommands("set", "remove", "list"), guild_only)]
+pub async fn alias(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Qualified names of every invocable command, e.g. `about server` or `help`.
+pub fn command_names(commands: &[poise::Command<AppState, Error>]) -> Vec<String> {
+    let mut names = Vec::new();
+    for cmd in commands {
+        if cmd.subcommands.is_empty() {
+            names.push(cmd.qualified_name.clone());
+        } else {
+            names.extend(command_names(&cmd.subcommands));
+        }
+    }
+    names
+}
+
+/// Normalizes an alias and its target, rejecting aliases that would shadow a
+/// real command and targets that don't exist.
+pub fn validate_alias(
+    alias: &str,
+    command: &str,
+    command_names: &[String],
+) -> Result<(String, String), String> {
+    let alias = alias.trim().trim_start_matches('/').to_lowercase();
+    if alias.is_empty() || alias.chars().count() > MAX_ALIAS_LEN {
+        return Err(format!("alias must be 1-{} characters", MAX_ALIAS_LEN));
+    }
+    if !alias
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("alias may only contain letters, numbers, `-` and `_`".to_string());
+    }
+    if command_names
+        .iter()
+        .any(|name| name.split_whitespace().next() == Some(alias.as_str()))
+    {
+        return Err(format!("`{}` is already a command", alias));
+    }
+
+    let command = command
+        .trim()
+        .trim_start_matches('/')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    if !command_names.contains(&command) {
+        return Err(format!("unknown command `/{}`", command));
+    }
+
+    Ok((alias, command))
+}
+
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_GUILD",
+    guild_only,
+    ephemeral
+)]
+async fn set(
+    ctx: Context<'_>,
+    #[description = "alias name"] alias: String,
+    #[description = "command it points to, e.g. about server"] command: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command").to_string();
+    let names = command_names(&ctx.framework().options().commands);
+
+    let (alias, command) = match validate_alias(&alias, &command, &names) {
+        Ok(v) => v,
+        Err(msg) => {
+            ctx.say(msg).await?;
+            return Ok(());
+        }
+    };
+
+    let db = &ctx.data().db;
+    let exists = CommandAlias::resolve(db, &guild_id, &alias)
+        .await?
+        .is_some();
+    if !exists && CommandAlias::count_for_guild(db, &guild_id).await? >= MAX_ALIASES_PER_GUILD {
+        ctx.say(format!(
+            "maximum of {} aliases per guild reached",
+            MAX_ALIASES_PER_GUILD
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    CommandAlias::upsert(db, &guild_id, &alias, &command).await?;
+    ctx.say(format!("`{}` now points to `/{}`", alias, command))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_GUILD",
+    guild_only,
+    ephemeral
+)]
+async fn remove(
+    ctx: Context<'_>,
+    #[description = "alias to remove"] alias: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command").to_string();
+    let alias = alias.trim().trim_start_matches('/');
+
+    let msg = if CommandAlias::delete(&ctx.data().db, &guild_id, alias).await? {
+        format!("removed alias `{}`", alias)
+    } else {
+        format!("no alias named `{}`", alias)
+    };
+    ctx.say(msg).await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, ephemeral)]
+async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command");
+    let aliases = CommandAlias::get_by_guild(&ctx.data().db, &guild_id.to_string()).await?;
+
+    let description = if aliases.is_empty() {
+        "no aliases yet".to_string()
+    } else {
+        format_alias_list(&aliases, EMBED_DESCRIPTION_LIMIT)
+    };
+
+    let embed = CreateEmbed::new()
+        .title("command aliases")
+        .description(description)
+        .color(get_embed_color(ctx.data(), Some(guild_id.get())).await);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// One line per alias, ending in "…and N more" once `max_chars` would be exceeded.
+pub fn format_alias_list(aliases: &[CommandAlias], max_chars: usize) -> String {
+    let lines: Vec<String> = aliases
+        .iter()
+        .map(|a| format!("`{}` → `/{}`", a.alias, a.command))
+        .collect();
+
+    let mut out = String::new();
+    let mut len = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let sep = usize::from(i > 0);
+        let remaining = lines.len() - i - 1;
+        let tail = if remaining == 0 {
+            0
+        } else {
+            format!("\n…and {} more", remaining).chars().count()
+        };
+        let line_len = line.chars().count();
+        if len + sep + line_len + tail > max_chars {
+            if i > 0 {
+                out.push('\n');
+            }
+            out.push_str(&format!("…and {} more", lines.len() - i));
+            break;
+        }
+        if sep == 1 {
+            out.push('\n');
+        }
+        out.push_str(line);
+        len += sep + line_len;
+    }
+    out
+}
@@ -1,5 +1,7 @@
+use crate::commands::alias::{EMBED_FIELD_LIMIT, format_alias_list};
 use anyhow::Result;
 use clouder_core::config::AppState;
+use clouder_core::database::command_aliases::CommandAlias;
 use clouder_core::utils::{get_embed_color, nav_row};
 use poise::serenity_prelude as serenity;
 use serenity::all::{
@@ -65,12 +67,19 @@ impl CommandCategory {
 pub fn get_all_commands() -> Vec<CommandInfo> {
     vec![
         CommandInfo {
-            name: "/selfroles".to_string(),
+            name: "/selfroles dashboard".to_string(),
             description: "manage selfroles".to_string(),
-            usage: Some("/selfroles".to_string()),
+            usage: Some("/selfroles dashboard".to_string()),
             category: CommandCategory::Management,
             permissions: Some("manage roles".to_string()),
         },
+        CommandInfo {
+            name: "/selfroles dm".to_string(),
+            description: "get a private copy of a self-role panel in your DMs".to_string(),
+            usage: Some("/selfroles dm <panel>".to_string()),
+            category: CommandCategory::Management,
+            permissions: None,
+        },
         CommandInfo {
             name: "/about bot".to_string(),
             description: "some info about me :3".to_string(),
@@ -99,6 +108,27 @@ pub fn get_all_commands() -> Vec<CommandInfo> {
             category: CommandCategory::Management,
             permissions: Some("manage messages".to_string()),
         },
+        CommandInfo {
+            name: "/role massadd".to_string(),
+            description: "give a role to everyone who has another role".to_string(),
+            usage: Some("/role massadd [source] [target]".to_string()),
+            category: CommandCategory::Management,
+            permissions: Some("manage roles".to_string()),
+        },
+        CommandInfo {
+            name: "/role toggle".to_string(),
+            description: "add or remove a self-assignable role".to_string(),
+            usage: Some("/role toggle [name]".to_string()),
+            category: CommandCategory::Management,
+            permissions: None,
+        },
+        CommandInfo {
+            name: "/role resetcooldown".to_string(),
+            description: "clear a member's self-role cooldowns".to_string(),
+            usage: Some("/role resetcooldown [@user] [role]".to_string()),
+            category: CommandCategory::Management,
+            permissions: Some("manage roles".to_string()),
+        },
         CommandInfo {
             name: "/mediaonly".to_string(),
             description: "configure media-only channels".to_string(),
@@ -127,6 +157,20 @@ pub fn get_all_commands() -> Vec<CommandInfo> {
             category: CommandCategory::Management,
             permissions: Some("manage channels".to_string()),
         },
+        CommandInfo {
+            name: "/alias".to_string(),
+            description: "give commands server-specific nicknames".to_string(),
+            usage: Some("/alias [set / remove / list]".to_string()),
+            category: CommandCategory::Management,
+            permissions: Some("manage server".to_string()),
+        },
+        CommandInfo {
+            name: "/schedule".to_string(),
+            description: "schedule one-off or recurring posts".to_string(),
+            usage: Some("/schedule [add / list / remove]".to_string()),
+            category: CommandCategory::Management,
+            permissions: Some("manage server".to_string()),
+        },
         CommandInfo {
             name: "/random".to_string(),
             description: "freaky link generator".to_string(),
@@ -191,7 +235,21 @@ pub async fn help(
 
 async fn show_general_help(ctx: Context<'_>, commands: &[CommandInfo]) -> Result<(), Error> {
     let color = get_embed_color(ctx.data(), ctx.guild_id().map(|g| g.get())).await;
-    let embed = create_help_embed(commands, color);
+    let mut embed = create_help_embed(commands, color);
+
+    if let Some(guild_id) = ctx.guild_id() {
+        let aliases = CommandAlias::get_by_guild(&ctx.data().db, &guild_id.to_string())
+            .await
+            .unwrap_or_default();
+        if !aliases.is_empty() {
+            embed = embed.field(
+                "> ***aliases***",
+                format_alias_list(&aliases, EMBED_FIELD_LIMIT),
+                false,
+            );
+        }
+    }
+
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
     Ok(())
 }
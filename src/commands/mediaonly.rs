@@ -1,6 +1,7 @@
 use anyhow::Result;
 use clouder_core::config::AppState;
 use clouder_core::database::mediaonly::MediaOnlyConfig;
+use clouder_core::shared::check_mediaonly_channel_limit;
 use clouder_core::utils::get_embed_color;
 use poise::serenity_prelude as serenity;
 use serenity::{CreateEmbed, Mentionable};
@@ -31,6 +32,18 @@ pub async fn mediaonly(
     };
     let channel_id = target_channel.id.to_string();
 
+    if let Err(msg) = check_mediaonly_channel_limit(
+        ctx.data(),
+        ctx.guild_id().expect("guild_only command").get(),
+        &channel_id,
+        ctx.author().id.get(),
+    )
+    .await
+    {
+        ctx.say(msg).await?;
+        return Ok(());
+    }
+
     let final_enabled = if let Some(enabled) = enabled {
         MediaOnlyConfig::upsert(&ctx.data().db, &guild_id, &channel_id, enabled).await?;
         enabled
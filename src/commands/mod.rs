@@ -1,4 +1,5 @@
 pub mod about;
+pub mod alias;
 pub mod channel;
 pub mod github;
 pub mod github_trending;
@@ -8,6 +9,8 @@ pub mod mediaonly;
 pub mod purge;
 pub mod random;
 pub mod reminders;
+pub mod role;
+pub mod schedule;
 pub mod selfroles;
 pub mod tinyfox;
 pub mod uwufy;
@@ -0,0 +1,564 @@
+use anyhow::Result;
+use chrono::Utc;
+use clouder_core::config::AppState;
+use clouder_core::database::selfroles::{SelfRoleConfig, SelfRoleCooldown};
+use clouder_core::utils::can_bot_manage_role;
+use poise::serenity_prelude as serenity;
+use serenity::all::{
+    ButtonStyle, CreateActionRow, CreateAllowedMentions, CreateButton, CreateInteractionResponse,
+    CreateMessage, Mentionable, RoleId, UserId,
+};
+use serenity::collector::ComponentInteractionCollector;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Context<'a> = poise::Context<'a, AppState, Error>;
+
+const MEMBERS_PAGE_SIZE: u64 = 1000;
+// how long the cancel button stays live. the reply it sits on is edited through
+// the interaction token, which Discord expires after 15 minutes
+const CANCEL_TIMEOUT: Duration = Duration::from_secs(14 * 60);
+
+/// (guild, source, target)
+type MassaddKey = (u64, u64, u64);
+
+/// Last user id processed by a cancelled or failed run, so re-running the
+/// same massadd picks up where it stopped.
+static MASSADD_CURSORS: LazyLock<Mutex<HashMap<MassaddKey, u64>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static MASSADD_RUNNING: LazyLock<Mutex<HashSet<u64>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+#[poise::command(
+    slash_command,
+    subcommands("massadd", "toggle", "resetcooldown"),
+    guild_only
+)]
+pub async fn role(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Members from `roster` that hold `source` but not yet `target`.
+pub fn filter_massadd_targets<'a>(
+    roster: impl IntoIterator<Item = (UserId, &'a [RoleId])>,
+    source: RoleId,
+    target: RoleId,
+) -> Vec<UserId> {
+    roster
+        .into_iter()
+        .filter(|(_, roles)| roles.contains(&source) && !roles.contains(&target))
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// What using a self-assignable role from the command does besides toggling it.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SelfAssignable {
+    /// Roles dropped when this one is added.
+    pub displaces: Vec<RoleId>,
+    /// Cooldown afterwards, 0 for none.
+    pub cooldown_secs: i64,
+}
+
+/// Roles members may give themselves: every role on one of the guild's
+/// self-role panels, given as `(selection type, roles, cooldown secs)`.
+/// Adding a role from a radio panel drops the panel's other roles and starts
+/// the panel's cooldown, same as pressing its button; a role on several
+/// panels takes the longest of their cooldowns.
+pub fn self_assignable_roles<'a>(
+    panels: impl IntoIterator<Item = (&'a str, &'a [RoleId], i64)>,
+) -> HashMap<RoleId, SelfAssignable> {
+    let mut assignable: HashMap<RoleId, SelfAssignable> = HashMap::new();
+    for (selection_type, roles, cooldown_secs) in panels {
+        for role in roles {
+            let entry = assignable.entry(*role).or_default();
+            entry.cooldown_secs = entry.cooldown_secs.max(cooldown_secs);
+            if selection_type != "radio" {
+                continue;
+            }
+            for other in roles {
+                if other != role && !entry.displaces.contains(other) {
+                    entry.displaces.push(*other);
+                }
+            }
+        }
+    }
+    assignable
+}
+
+/// Matches `query` against role names, ignoring case and a leading `@`.
+pub fn find_role_by_name<'a>(
+    query: &str,
+    roles: impl IntoIterator<Item = (RoleId, &'a str)>,
+) -> Option<RoleId> {
+    let query = query.trim().trim_start_matches('@');
+    roles
+        .into_iter()
+        .find(|(_, name)| name.eq_ignore_ascii_case(query))
+        .map(|(id, _)| id)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RoleToggle {
+    /// Give the role, dropping these displaced roles the member holds.
+    Add {
+        displaced: Vec<RoleId>,
+    },
+    Remove,
+}
+
+pub fn plan_role_toggle(member_roles: &[RoleId], role: RoleId, displaces: &[RoleId]) -> RoleToggle {
+    if member_roles.contains(&role) {
+        return RoleToggle::Remove;
+    }
+    RoleToggle::Add {
+        displaced: displaces
+            .iter()
+            .filter(|r| member_roles.contains(r))
+            .copied()
+            .collect(),
+    }
+}
+
+async fn load_self_assignable(
+    pool: &sqlx::SqlitePool,
+    guild_id: serenity::GuildId,
+) -> Result<HashMap<RoleId, SelfAssignable>> {
+    let mut panels = Vec::new();
+    for config in SelfRoleConfig::get_by_guild(pool, &guild_id.to_string()).await? {
+        let roles: Vec<RoleId> = config
+            .get_roles(pool)
+            .await?
+            .iter()
+            .filter_map(|r| r.role_id.parse().ok().map(RoleId::new))
+            .collect();
+        panels.push((config.selection_type, roles, config.cooldown_secs));
+    }
+    Ok(self_assignable_roles(panels.iter().map(
+        |(t, roles, secs)| (t.as_str(), roles.as_slice(), *secs),
+    )))
+}
+
+/// Names of the cached roles among `ids`, sorted for display.
+fn role_names(ctx: &Context<'_>, ids: impl IntoIterator<Item = RoleId>) -> Vec<(RoleId, String)> {
+    let Some(guild) = ctx.guild() else {
+        return Vec::new();
+    };
+    let mut names: Vec<(RoleId, String)> = ids
+        .into_iter()
+        .filter_map(|id| guild.roles.get(&id).map(|r| (id, r.name.clone())))
+        .collect();
+    names.sort_by_key(|(_, name)| name.to_lowercase());
+    names
+}
+
+async fn autocomplete_self_assignable(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+    let assignable = match load_self_assignable(&ctx.data().db, guild_id).await {
+        Ok(assignable) => assignable,
+        Err(e) => {
+            warn!("self-assignable roles for {}: {}", guild_id, e);
+            return Vec::new();
+        }
+    };
+    let partial = partial.to_lowercase();
+    role_names(&ctx, assignable.into_keys())
+        .into_iter()
+        .map(|(_, name)| name)
+        .filter(|name| name.to_lowercase().contains(&partial))
+        .take(25)
+        .collect()
+}
+
+/// Checks the target role against the bot's highest role and, when
+/// `check_author` is set, the invoker's too, so the command can't be used to
+/// hand out roles above the caller.
+fn check_hierarchy(ctx: &Context<'_>, target: RoleId, check_author: bool) -> Result<(), String> {
+    let guild = ctx
+        .guild()
+        .ok_or_else(|| "server info isn't cached yet, try again in a moment".to_string())?;
+    let role = guild
+        .roles
+        .get(&target)
+        .ok_or_else(|| "that role doesn't exist".to_string())?;
+
+    let positions_of = |user: UserId| -> Option<Vec<u16>> {
+        let member = guild.members.get(&user)?;
+        Some(
+            member
+                .roles
+                .iter()
+                .filter_map(|r| guild.roles.get(r))
+                .map(|r| r.position)
+                .collect(),
+        )
+    };
+
+    let bot_positions = positions_of(ctx.cache().current_user().id).unwrap_or_default();
+    if !can_bot_manage_role(bot_positions, role.position, role.managed) {
+        return Err(format!(
+            "i can't assign {} — it's managed or above my highest role",
+            role.mention()
+        ));
+    }
+
+    if check_author && guild.owner_id != ctx.author().id {
+        let author_positions = positions_of(ctx.author().id).unwrap_or_default();
+        if !can_bot_manage_role(author_positions, role.position, role.managed) {
+            return Err(format!("{} is above your highest role", role.mention()));
+        }
+    }
+
+    Ok(())
+}
+
+fn cancel_row(cancel_id: &str) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(cancel_id)
+            .label("cancel")
+            .style(ButtonStyle::Secondary),
+    ])]
+}
+
+/// give a role to everyone who has another role
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_ROLES",
+    required_bot_permissions = "MANAGE_ROLES",
+    guild_only,
+    ephemeral
+)]
+async fn massadd(
+    ctx: Context<'_>,
+    #[description = "members with this role..."] source: serenity::Role,
+    #[description = "...get this role"] target: serenity::Role,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command");
+
+    if source.id == target.id {
+        ctx.say("source and target must be different roles").await?;
+        return Ok(());
+    }
+    if let Err(msg) = check_hierarchy(&ctx, target.id, true) {
+        ctx.say(msg).await?;
+        return Ok(());
+    }
+
+    if !MASSADD_RUNNING
+        .lock()
+        .expect("massadd lock poisoned")
+        .insert(guild_id.get())
+    {
+        ctx.say("a massadd is already running in this server")
+            .await?;
+        return Ok(());
+    }
+
+    let result = run_massadd(&ctx, guild_id, &source, &target).await;
+    MASSADD_RUNNING
+        .lock()
+        .expect("massadd lock poisoned")
+        .remove(&guild_id.get());
+    result
+}
+
+/// add or remove one of the server's self-assignable roles
+#[poise::command(
+    slash_command,
+    required_bot_permissions = "MANAGE_ROLES",
+    guild_only,
+    ephemeral
+)]
+async fn toggle(
+    ctx: Context<'_>,
+    #[description = "role to add or remove"]
+    #[autocomplete = "autocomplete_self_assignable"]
+    name: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let assignable = load_self_assignable(db, guild_id).await?;
+    if assignable.is_empty() {
+        ctx.say("this server has no self-assignable roles. they come from the self-role panels set up on the dashboard")
+            .await?;
+        return Ok(());
+    }
+    let names = role_names(&ctx, assignable.keys().copied());
+    let Some(role) = find_role_by_name(&name, names.iter().map(|(id, n)| (*id, n.as_str()))) else {
+        ctx.say(format!("`{}` isn't a self-assignable role here", name))
+            .await?;
+        return Ok(());
+    };
+
+    let user_id = ctx.author().id.to_string();
+    let role_str = role.to_string();
+    let guild_str = guild_id.to_string();
+    if SelfRoleCooldown::check_cooldown(db, &user_id, &role_str, &guild_str).await? {
+        ctx.say("You're doing that too quickly! Try again in a few seconds.")
+            .await?;
+        return Ok(());
+    }
+    if let Err(msg) = check_hierarchy(&ctx, role, false) {
+        ctx.say(msg).await?;
+        return Ok(());
+    }
+
+    let Some(member) = ctx.author_member().await else {
+        ctx.say("failed to retrieve your member info.").await?;
+        return Ok(());
+    };
+    let Some(entry) = assignable.get(&role) else {
+        return Ok(());
+    };
+    let reply = match plan_role_toggle(&member.roles, role, &entry.displaces) {
+        RoleToggle::Remove => {
+            ctx.http()
+                .remove_member_role(guild_id, ctx.author().id, role, Some("Self-role command"))
+                .await?;
+            format!("removed {}", role.mention())
+        }
+        RoleToggle::Add { displaced } => {
+            for other in displaced {
+                if let Err(e) = ctx
+                    .http()
+                    .remove_member_role(
+                        guild_id,
+                        ctx.author().id,
+                        other,
+                        Some("Self-role radio mode"),
+                    )
+                    .await
+                {
+                    warn!("remove role {} from {}: {}", other, ctx.author().id, e);
+                }
+            }
+            ctx.http()
+                .add_member_role(guild_id, ctx.author().id, role, Some("Self-role command"))
+                .await?;
+            format!("added {}", role.mention())
+        }
+    };
+
+    if entry.cooldown_secs > 0 {
+        let expires_at = Utc::now() + chrono::Duration::seconds(entry.cooldown_secs);
+        SelfRoleCooldown::create(db, &user_id, &role_str, &guild_str, expires_at).await?;
+    }
+    ctx.say(reply).await?;
+    Ok(())
+}
+
+/// clear a member's self-role cooldowns in this server
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_ROLES",
+    guild_only,
+    ephemeral
+)]
+async fn resetcooldown(
+    ctx: Context<'_>,
+    #[description = "member whose cooldowns to clear"] user: serenity::User,
+    #[description = "only clear the cooldown for this role"] role: Option<serenity::Role>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command").to_string();
+    let role_id = role.as_ref().map(|r| r.id.to_string());
+    let cleared = SelfRoleCooldown::delete_for_user(
+        &ctx.data().db,
+        &user.id.to_string(),
+        Some(&guild_id),
+        role_id.as_deref(),
+    )
+    .await?;
+
+    info!(
+        "{} cleared {} self-role cooldown(s) for {} in guild {}",
+        ctx.author().id,
+        cleared,
+        user.id,
+        guild_id
+    );
+    let scope = match &role {
+        Some(role) => format!(" for {}", role.mention()),
+        None => String::new(),
+    };
+    let reply = if cleared == 0 {
+        format!("{} has no self-role cooldowns{}", user.mention(), scope)
+    } else {
+        format!(
+            "cleared {} self-role cooldown{}{} for {}",
+            cleared,
+            if cleared == 1 { "" } else { "s" },
+            scope,
+            user.mention()
+        )
+    };
+    ctx.say(reply).await?;
+    Ok(())
+}
+
+async fn run_massadd(
+    ctx: &Context<'_>,
+    guild_id: serenity::GuildId,
+    source: &serenity::Role,
+    target: &serenity::Role,
+) -> Result<(), Error> {
+    let key = (guild_id.get(), source.id.get(), target.id.get());
+    let resume_after = MASSADD_CURSORS
+        .lock()
+        .expect("massadd lock poisoned")
+        .get(&key)
+        .copied();
+
+    let cancel_id = format!("massadd_cancel_{}", ctx.id());
+    let header = format!("{} → {}", source.mention(), target.mention());
+    let resumed = if resume_after.is_some() {
+        " (resuming)"
+    } else {
+        ""
+    };
+
+    let reply = ctx
+        .send(
+            poise::CreateReply::default()
+                .content(format!("{}{}: starting…", header, resumed))
+                .components(cancel_row(&cancel_id)),
+        )
+        .await?;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let cancel_task = {
+        let flag = cancelled.clone();
+        let serenity_ctx = ctx.serenity_context().clone();
+        let author = ctx.author().id;
+        let cancel_id = cancel_id.clone();
+        tokio::spawn(async move {
+            let filter_id = cancel_id.clone();
+            if let Some(i) = ComponentInteractionCollector::new(&serenity_ctx)
+                .author_id(author)
+                .filter(move |i| i.data.custom_id == filter_id)
+                .timeout(CANCEL_TIMEOUT)
+                .await
+            {
+                flag.store(true, Ordering::Relaxed);
+                let _ = i
+                    .create_response(&serenity_ctx.http, CreateInteractionResponse::Acknowledge)
+                    .await;
+            }
+        })
+    };
+
+    let mut after = resume_after;
+    let mut granted = 0usize;
+    let mut failed = 0usize;
+    let mut scanned = 0usize;
+    let mut outcome = "done";
+
+    'pages: loop {
+        let members = match ctx
+            .http()
+            .get_guild_members(guild_id, Some(MEMBERS_PAGE_SIZE), after)
+            .await
+        {
+            Ok(m) => m,
+            Err(e) => {
+                warn!("massadd list members in {}: {}", guild_id, e);
+                outcome = "stopped: couldn't list members, run again to resume";
+                break;
+            }
+        };
+        let is_last_page = (members.len() as u64) < MEMBERS_PAGE_SIZE;
+        scanned += members.len();
+
+        let roster = members.iter().map(|m| (m.user.id, m.roles.as_slice()));
+        for user_id in filter_massadd_targets(roster, source.id, target.id) {
+            if cancelled.load(Ordering::Relaxed) {
+                outcome = "cancelled, run again to resume";
+                break 'pages;
+            }
+            // serenity's ratelimiter queues these, so no extra pacing here
+            match ctx
+                .http()
+                .add_member_role(guild_id, user_id, target.id, Some("role massadd"))
+                .await
+            {
+                Ok(_) => granted += 1,
+                Err(e) => {
+                    warn!("massadd {} to {}: {}", target.id, user_id, e);
+                    failed += 1;
+                }
+            }
+            after = Some(user_id.get());
+        }
+        after = members.last().map(|m| m.user.id.get()).or(after);
+
+        if is_last_page || members.is_empty() {
+            break;
+        }
+
+        reply
+            .edit(
+                *ctx,
+                poise::CreateReply::default()
+                    .content(format!(
+                        "{}{}: scanned {}, granted {}, failed {}…",
+                        header, resumed, scanned, granted, failed
+                    ))
+                    .components(cancel_row(&cancel_id)),
+            )
+            .await
+            .ok();
+    }
+
+    cancel_task.abort();
+
+    {
+        let mut cursors = MASSADD_CURSORS.lock().expect("massadd lock poisoned");
+        match (outcome, after) {
+            ("done", _) | (_, None) => {
+                cursors.remove(&key);
+            }
+            (_, Some(cursor)) => {
+                cursors.insert(key, cursor);
+            }
+        }
+    }
+
+    info!(
+        "massadd {} -> {} in {}: granted {}, failed {} ({})",
+        source.id, target.id, guild_id, granted, failed, outcome
+    );
+
+    let summary = format!(
+        "{}{}: {}. scanned {}, granted {}, failed {}",
+        header, resumed, outcome, scanned, granted, failed
+    );
+    // a long run outlives the interaction token, so post the result instead
+    if let Err(e) = reply
+        .edit(
+            *ctx,
+            poise::CreateReply::default()
+                .content(summary.clone())
+                .components(vec![]),
+        )
+        .await
+    {
+        warn!("massadd result edit in {}: {}", guild_id, e);
+        // the summary mentions both roles; don't ping them in the channel
+        ctx.channel_id()
+            .send_message(
+                ctx.http(),
+                CreateMessage::new()
+                    .content(summary)
+                    .allowed_mentions(CreateAllowedMentions::new()),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
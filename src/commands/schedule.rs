@@ -0,0 +1,132 @@
+use anyhow::Result;
+use clouder_core::config::AppState;
+use clouder_core::database::scheduled_messages::ScheduledMessage;
+use clouder_core::shared::{create_scheduled_message, delete_scheduled_message};
+use clouder_core::utils::{discord_timestamp, get_embed_color, truncate};
+use poise::serenity_prelude as serenity;
+use serde_json::json;
+use serenity::{CreateEmbed, Mentionable};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Context<'a> = poise::Context<'a, AppState, Error>;
+
+#[poise::command(slash_command, subcommands("add", "list", "remove"), guild_only)]
+pub async fn schedule(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// schedule a one-off or recurring post
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_GUILD",
+    guild_only,
+    ephemeral
+)]
+async fn add(
+    ctx: Context<'_>,
+    #[description = "channel to post in"] channel: serenity::GuildChannel,
+    #[description = "message to post"] content: String,
+    #[description = "recurring cron spec, e.g. 0 9 * * 1-5"] cron: Option<String>,
+    #[description = "one-off time, YYYY-MM-DD HH:MM"] at: Option<String>,
+    #[description = "timezone (defaults to the server's)"] timezone: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command");
+    let payload = json!({
+        "channel_id": channel.id.to_string(),
+        "content": content,
+        "cron": cron,
+        "run_at": at,
+        "timezone": timezone,
+    });
+
+    match create_scheduled_message(
+        ctx.data(),
+        guild_id.get(),
+        &ctx.author().id.to_string(),
+        &payload,
+    )
+    .await
+    {
+        Ok(result) => {
+            let next = result["next_run_at"].as_i64().unwrap_or_default();
+            ctx.say(format!(
+                "scheduled #{} in {}, next post {}",
+                result["id"],
+                channel.mention(),
+                discord_timestamp(next, 'R')
+            ))
+            .await?;
+        }
+        Err(e) => {
+            ctx.say(e).await?;
+        }
+    }
+    Ok(())
+}
+
+/// list scheduled posts in this server
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_GUILD",
+    guild_only,
+    ephemeral
+)]
+async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command");
+    let messages = ScheduledMessage::get_by_guild(&ctx.data().db, &guild_id.to_string()).await?;
+
+    let description = if messages.is_empty() {
+        "nothing scheduled".to_string()
+    } else {
+        messages
+            .iter()
+            .map(|m| {
+                let when = match (m.enabled, m.next_run_at) {
+                    (true, Some(ts)) => discord_timestamp(ts, 'R'),
+                    _ => "done".to_string(),
+                };
+                let repeat = m
+                    .cron_spec
+                    .as_deref()
+                    .map(|c| format!("`{}` ({})", c, m.timezone))
+                    .unwrap_or_else(|| "once".to_string());
+                format!(
+                    "**#{}** <#{}> · {} · next {}\n{}",
+                    m.id,
+                    m.channel_id,
+                    repeat,
+                    when,
+                    truncate(&m.content, 80)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = CreateEmbed::new()
+        .title("scheduled messages")
+        .description(description)
+        .color(get_embed_color(ctx.data(), Some(guild_id.get())).await);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// remove a scheduled post
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_GUILD",
+    guild_only,
+    ephemeral
+)]
+async fn remove(
+    ctx: Context<'_>,
+    #[description = "id from /schedule list"] id: i64,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command");
+    let msg = match delete_scheduled_message(ctx.data(), guild_id.get(), id).await {
+        Ok(_) => format!("removed #{}", id),
+        Err(e) => e,
+    };
+    ctx.say(msg).await?;
+    Ok(())
+}
@@ -1,12 +1,21 @@
 use clouder_core::config::AppState;
+use clouder_core::database::selfroles::SelfRoleConfig;
+use clouder_core::shared::build_selfrole_panel;
 use clouder_core::utils::get_embed_color;
 use poise::serenity_prelude as serenity;
+use tracing::{error, warn};
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, AppState, Error>;
 
+#[poise::command(slash_command, subcommands("dashboard", "dm"), guild_only)]
+pub async fn selfroles(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// link to the dashboard's self-role setup
 #[poise::command(slash_command, required_permissions = "MANAGE_ROLES", guild_only)]
-pub async fn selfroles(ctx: Context<'_>) -> Result<(), Error> {
+pub async fn dashboard(ctx: Context<'_>) -> Result<(), Error> {
     let dashboard_url = &ctx.data().config.web.api_base;
     let guild_id = ctx.guild_id().expect("guild_only command").to_string();
 
@@ -28,3 +37,74 @@ pub async fn selfroles(ctx: Context<'_>) -> Result<(), Error> {
 
     Ok(())
 }
+
+async fn autocomplete_panel(ctx: Context<'_>, partial: &str) -> Vec<serenity::AutocompleteChoice> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+    let configs = match SelfRoleConfig::get_by_guild(&ctx.data().db, &guild_id.to_string()).await {
+        Ok(configs) => configs,
+        Err(e) => {
+            warn!("self-role panels for {}: {}", guild_id, e);
+            return Vec::new();
+        }
+    };
+    let partial = partial.to_lowercase();
+    configs
+        .into_iter()
+        .filter(|c| !c.attached && c.title.to_lowercase().contains(&partial))
+        .take(25)
+        .map(|c| serenity::AutocompleteChoice::new(c.title, c.id))
+        .collect()
+}
+
+/// get a private copy of a self-role panel in your DMs
+#[poise::command(slash_command, guild_only)]
+pub async fn dm(
+    ctx: Context<'_>,
+    #[description = "Panel to send"]
+    #[autocomplete = "autocomplete_panel"]
+    panel: i64,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("guild_only command").to_string();
+    let config = SelfRoleConfig::get_by_id(&ctx.data().db, panel)
+        .await?
+        .filter(|c| c.guild_id == guild_id && !c.attached);
+    let Some(config) = config else {
+        ctx.send(
+            poise::CreateReply::default()
+                .content("that self-role panel doesn't exist in this server.")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let (embed, action_rows) = build_selfrole_panel(ctx.data(), &config).await?;
+    let sent = ctx
+        .author()
+        .direct_message(
+            ctx.http(),
+            serenity::CreateMessage::new()
+                .embed(embed)
+                .components(action_rows),
+        )
+        .await;
+
+    let reply = match sent {
+        Ok(_) => "sent the panel to your DMs.",
+        Err(e) => {
+            error!(
+                "dm selfrole panel {} to {}: {}",
+                config.id,
+                ctx.author().id,
+                e
+            );
+            "i couldn't DM you. allow direct messages from server members and try again."
+        }
+    };
+    ctx.send(poise::CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use crate::serenity;
+use clouder_core::config::AppState;
+use clouder_core::database::guild_cache::CachedGuild;
+use clouder_core::shared::{archive_guild_selfroles, restore_guild_selfroles};
+use tracing::{error, info};
+
+/// Restores self-role panels archived when the bot last left this guild.
+/// Discord sends this for every guild on startup too, where it's a no-op.
+pub async fn guild_create(guild: &serenity::Guild, data: &AppState) {
+    match restore_guild_selfroles(data, guild.id.get()).await {
+        Ok(result) if result["restored"].as_u64().unwrap_or(0) > 0 => {
+            info!(
+                "restored {} archived selfrole panels in {}",
+                result["restored"], guild.id
+            );
+        }
+        Ok(_) => {}
+        Err(e) => error!("restore selfrole panels in {}: {}", guild.id, e),
+    }
+}
+
+/// Archives the guild's self-role panels once the bot has actually left it.
+/// An outage also sends this, flagged `unavailable`, and leaves data alone.
+pub async fn guild_delete(incomplete: &serenity::UnavailableGuild, data: &AppState) {
+    if incomplete.unavailable {
+        return;
+    }
+    let guild_id = incomplete.id;
+    if let Err(e) = CachedGuild::delete_for_guild(&data.db, &guild_id.to_string()).await {
+        error!("clear guild cache for {}: {}", guild_id, e);
+    }
+    match archive_guild_selfroles(data, guild_id.get()).await {
+        Ok(result) => info!(
+            "left guild {}: archived {} selfrole panels, deleted {}",
+            guild_id, result["archived"], result["deleted"]
+        ),
+        Err(e) => error!("archive selfrole panels in {}: {}", guild_id, e),
+    }
+}
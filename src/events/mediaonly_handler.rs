@@ -1,10 +1,10 @@
 use clouder_core::config::AppState;
-use clouder_core::database::mediaonly::MediaOnlyConfig;
-use clouder_core::utils::content_detection::has_allowed_content;
+use clouder_core::database::mediaonly::{MediaOnlyConfig, MediaOnlyStats};
+use clouder_core::utils::content_detection::classify_violation;
 use clouder_core::utils::get_embed_color;
 use poise::serenity_prelude as serenity;
 use std::time::Duration;
-use tracing::{error, warn};
+use tracing::{debug, error, warn};
 
 pub async fn handle_media_only_message(
     ctx: &serenity::Context,
@@ -36,15 +36,20 @@ pub async fn handle_media_only_message(
         }
     };
 
-    if has_allowed_content(
+    let Some(reason) = classify_violation(
         message,
         config.allow_links,
         config.allow_attachments,
         config.allow_gifs,
         config.allow_stickers,
-    ) {
+    ) else {
         return;
-    }
+    };
+
+    debug!(
+        "media-only violation in {} by {}: {}",
+        channel_id, message.author.id, reason
+    );
 
     let message_id = message.id;
     let author_id = message.author.id;
@@ -53,14 +58,31 @@ pub async fn handle_media_only_message(
     let embed_color = get_embed_color(data, Some(guild_id.get())).await;
     let allowed_types = build_allowed_types(&config);
     let footer = crate::serenity::CreateEmbedFooter::new(format!("allowed types: {allowed_types}"));
+    let track_stats = data.config.mediaonly_stats_retention_days > 0;
+    let db = data.db.clone();
 
     const AUTO_DELETE_DELAY: Duration = Duration::from_secs(5);
 
     tokio::spawn(async move {
         match http.delete_message(channel_id, message_id, None).await {
             Ok(_) => {
+                if track_stats
+                    && let Err(e) = MediaOnlyStats::record_deletion(
+                        &db,
+                        &guild_id.to_string(),
+                        &channel_id.to_string(),
+                        &author_id.to_string(),
+                        chrono::Utc::now().date_naive(),
+                    )
+                    .await
+                {
+                    warn!("record media-only stats: {}", e);
+                }
+
                 let embed = serenity::builder::CreateEmbed::new()
-                    .description(format!("<@{author_id}> this channel is media-only"))
+                    .description(format!(
+                        "<@{author_id}> this channel is media-only ({reason})"
+                    ))
                     .footer(footer)
                     .color(embed_color);
                 let message = serenity::builder::CreateMessage::new().embed(embed);
@@ -1,10 +1,16 @@
 use clouder_core::config::AppState;
-use clouder_core::database::welcome_goodbye::{WelcomeGoodbyeConfig, get_member_placeholders};
-use clouder_core::utils::welcome_goodbye::{EmbedConfig, build_embed, replace_placeholders};
+use clouder_core::database::welcome_goodbye::{
+    MemberMessageKind, PendingWelcome, WelcomeGoodbyeConfig, get_member_placeholders,
+};
+use clouder_core::utils::welcome_goodbye::{
+    EmbedConfig, NamePlaceholderSource, RotationMode, build_embed, completed_screening,
+    replace_placeholders, select_variant_index,
+};
 use serenity::{
     builder::CreateMessage,
     client::Context,
     model::{
+        event::GuildMemberUpdateEvent,
         guild::Member,
         id::{ChannelId, GuildId},
         user::User,
@@ -46,6 +52,57 @@ async fn fetch_config(
     }
 }
 
+/// Guild name and member count from the cache, if the guild is cached.
+fn cached_guild_info(ctx: &Context, guild_id: &GuildId) -> (Option<String>, Option<u64>) {
+    match ctx.cache.guild(guild_id) {
+        Some(guild) => (Some(guild.name.clone()), Some(guild.member_count)),
+        None => {
+            warn!("guild {} not in cache", guild_id);
+            (None, None)
+        }
+    }
+}
+
+fn name_source(config: &WelcomeGoodbyeConfig) -> NamePlaceholderSource {
+    NamePlaceholderSource::parse(&config.name_placeholder_source).unwrap_or_default()
+}
+
+/// Picks a message variant per the guild's rotation mode. `None` means send the
+/// configured message as-is. A variant stands in for the text content or, for
+/// embeds, the description.
+async fn pick_variant(
+    pool: &SqlitePool,
+    guild_id: &GuildId,
+    kind: MemberMessageKind,
+    rotation: &str,
+) -> Option<String> {
+    let mode = RotationMode::parse(rotation).unwrap_or(RotationMode::Off);
+    if mode == RotationMode::Off {
+        return None;
+    }
+    let guild_id = guild_id.to_string();
+    let mut variants = match WelcomeGoodbyeConfig::get_variants(pool, &guild_id, kind).await {
+        Ok(variants) => variants,
+        Err(e) => {
+            error!("get {} variants: {}", kind.as_str(), e);
+            return None;
+        }
+    };
+    let counter = if mode == RotationMode::RoundRobin && !variants.is_empty() {
+        match WelcomeGoodbyeConfig::next_rotation_counter(pool, &guild_id, kind).await {
+            Ok(counter) => counter.max(0) as u64,
+            Err(e) => {
+                warn!("bump {} rotation counter: {}", kind.as_str(), e);
+                0
+            }
+        }
+    } else {
+        0
+    };
+    let index = select_variant_index(mode, variants.len(), counter, rand::random())?;
+    Some(variants.swap_remove(index))
+}
+
 async fn send_member_message(
     ctx: &Context,
     channel_id: &ChannelId,
@@ -100,6 +157,87 @@ pub async fn member_addition(ctx: &Context, guild_id: &GuildId, new_member: &Mem
         None => return,
     };
 
+    // wait for screening so people who never finish it aren't greeted
+    if config.welcome_after_screening && new_member.pending {
+        if let Some(pool) = data.get::<Database>()
+            && let Err(e) =
+                PendingWelcome::record(pool, &guild_id.to_string(), &new_member.user.id.to_string())
+                    .await
+        {
+            error!("record pending welcome: {}", e);
+        }
+        return;
+    }
+
+    send_welcome(
+        ctx,
+        &data,
+        guild_id,
+        &config,
+        &new_member.user,
+        Some(new_member),
+    )
+    .await;
+}
+
+/// Sends the held-back welcome once a member passes membership screening.
+pub async fn member_update(
+    ctx: &Context,
+    old_if_available: &Option<Member>,
+    new: &Option<Member>,
+    event: &GuildMemberUpdateEvent,
+) {
+    if event.pending {
+        return;
+    }
+
+    let data = ctx.data.read().await;
+    // the old member state is often uncached, so the recorded join decides
+    let recorded = match data.get::<Database>() {
+        Some(pool) => PendingWelcome::take(
+            pool,
+            &event.guild_id.to_string(),
+            &event.user.id.to_string(),
+        )
+        .await
+        .unwrap_or_else(|e| {
+            error!("take pending welcome: {}", e);
+            false
+        }),
+        None => false,
+    };
+    let was_pending = old_if_available.as_ref().map(|m| m.pending);
+    if !completed_screening(recorded, was_pending, event.pending) {
+        return;
+    }
+
+    let config = match fetch_config(&data, &event.guild_id).await {
+        Some(config) => config,
+        None => return,
+    };
+    if !config.welcome_after_screening {
+        return;
+    }
+
+    send_welcome(
+        ctx,
+        &data,
+        &event.guild_id,
+        &config,
+        &event.user,
+        new.as_ref(),
+    )
+    .await;
+}
+
+async fn send_welcome(
+    ctx: &Context,
+    data: &tokio::sync::RwLockReadGuard<'_, TypeMap>,
+    guild_id: &GuildId,
+    config: &WelcomeGoodbyeConfig,
+    user: &User,
+    member: Option<&Member>,
+) {
     if !config.welcome_enabled || config.welcome_channel_id.is_none() {
         return;
     }
@@ -115,19 +253,13 @@ pub async fn member_addition(ctx: &Context, guild_id: &GuildId, new_member: &Mem
         }
     };
 
-    let (guild_name, member_count) = match ctx.cache.guild(guild_id) {
-        Some(guild) => (guild.name.clone(), guild.member_count),
-        None => {
-            warn!("guild {} not in cache", guild_id);
-            return;
-        }
-    };
-
+    let (guild_name, member_count) = cached_guild_info(ctx, guild_id);
     let placeholders = get_member_placeholders(
-        &new_member.user,
-        &guild_name,
+        user,
+        guild_name.as_deref(),
         member_count,
-        Some(new_member),
+        member,
+        name_source(config),
     );
 
     let Some(state) = data.get::<AppStateKey>().cloned() else {
@@ -137,9 +269,20 @@ pub async fn member_addition(ctx: &Context, guild_id: &GuildId, new_member: &Mem
     let default_color = clouder_core::utils::get_embed_color(&state, Some(guild_id.get()))
         .await
         .0 as u64;
+    let variant = pick_variant(
+        &state.db,
+        guild_id,
+        MemberMessageKind::Welcome,
+        &config.welcome_rotation,
+    )
+    .await;
     let embed_cfg = EmbedConfig {
         title: &config.welcome_embed_title,
-        description: &config.welcome_embed_description,
+        description: if variant.is_some() {
+            &variant
+        } else {
+            &config.welcome_embed_description
+        },
         color: config.welcome_embed_color,
         footer: &config.welcome_embed_footer,
         thumbnail: &config.welcome_embed_thumbnail,
@@ -153,7 +296,9 @@ pub async fn member_addition(ctx: &Context, guild_id: &GuildId, new_member: &Mem
         &channel_id,
         &config.welcome_message_type,
         embed_cfg,
-        config.welcome_message_content.as_deref(),
+        variant
+            .as_deref()
+            .or(config.welcome_message_content.as_deref()),
         &placeholders,
     )
     .await
@@ -183,6 +328,14 @@ pub async fn member_removal(
         warn!("guild cache invalidation on removal failed: {}", e);
     }
 
+    // left before passing screening, so there's no welcome to send anymore
+    if let Some(pool) = data.get::<Database>()
+        && let Err(e) =
+            PendingWelcome::take(pool, &guild_id.to_string(), &user.id.to_string()).await
+    {
+        warn!("drop pending welcome on removal failed: {}", e);
+    }
+
     let config = match fetch_config(&data, guild_id).await {
         Some(config) => config,
         None => return,
@@ -203,19 +356,15 @@ pub async fn member_removal(
         }
     };
 
-    let (guild_name, member_count) = match ctx.cache.guild(guild_id) {
-        Some(guild) => (guild.name.clone(), guild.member_count),
-        None => {
-            error!("guild {} not in cache", guild_id);
-            return;
-        }
-    };
-
+    // The member is often uncached by the time they leave; the goodbye still
+    // goes out with whatever the `User` alone can fill in.
+    let (guild_name, member_count) = cached_guild_info(ctx, guild_id);
     let placeholders = get_member_placeholders(
         user,
-        &guild_name,
+        guild_name.as_deref(),
         member_count,
         member_data_if_available.as_ref(),
+        name_source(&config),
     );
 
     let Some(state) = data.get::<AppStateKey>().cloned() else {
@@ -225,9 +374,20 @@ pub async fn member_removal(
     let default_color = clouder_core::utils::get_embed_color(&state, Some(guild_id.get()))
         .await
         .0 as u64;
+    let variant = pick_variant(
+        &state.db,
+        guild_id,
+        MemberMessageKind::Goodbye,
+        &config.goodbye_rotation,
+    )
+    .await;
     let embed_cfg = EmbedConfig {
         title: &config.goodbye_embed_title,
-        description: &config.goodbye_embed_description,
+        description: if variant.is_some() {
+            &variant
+        } else {
+            &config.goodbye_embed_description
+        },
         color: config.goodbye_embed_color,
         footer: &config.goodbye_embed_footer,
         thumbnail: &config.goodbye_embed_thumbnail,
@@ -241,7 +401,9 @@ pub async fn member_removal(
         &channel_id,
         &config.goodbye_message_type,
         embed_cfg,
-        config.goodbye_message_content.as_deref(),
+        variant
+            .as_deref()
+            .or(config.goodbye_message_content.as_deref()),
         &placeholders,
     )
     .await
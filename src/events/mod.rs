@@ -7,10 +7,11 @@ use clouder_core::config::AppState;
 use tracing::info;
 
 mod bot_mentioned;
+pub mod guild_events;
 mod mediaonly_handler;
 pub mod member_events;
 mod message_handler;
-mod selfroles;
+pub mod selfroles;
 
 pub async fn event_handler(
     ctx: &serenity::Context,
@@ -42,9 +43,22 @@ pub async fn event_handler(
             handle_media_only_message(ctx, new_message, data).await;
             handle_uwufy_message(ctx, new_message, data).await;
         }
+        serenity::FullEvent::GuildCreate { guild, .. } => {
+            guild_events::guild_create(guild, data).await;
+        }
+        serenity::FullEvent::GuildDelete { incomplete, .. } => {
+            guild_events::guild_delete(incomplete, data).await;
+        }
         serenity::FullEvent::GuildMemberAddition { new_member } => {
             member_events::member_addition(ctx, &new_member.guild_id, new_member).await;
         }
+        serenity::FullEvent::GuildMemberUpdate {
+            old_if_available,
+            new,
+            event,
+        } => {
+            member_events::member_update(ctx, old_if_available, new, event).await;
+        }
         serenity::FullEvent::GuildMemberRemoval {
             guild_id,
             user,
@@ -1,8 +1,11 @@
 use crate::serenity;
 use chrono::{Duration, Utc};
 use clouder_core::config::AppState;
-use clouder_core::database::selfroles::{SelfRoleConfig, SelfRoleCooldown};
+use clouder_core::database::selfroles::{
+    SelfRoleConfig, SelfRoleCooldown, SelfRoleFeedback, SelfRoleRole, SelfRoleSettings,
+};
 use clouder_core::shared::check_interaction_expired;
+use clouder_core::utils::can_bot_manage_role;
 use serenity::all::{CreateInteractionResponse, CreateInteractionResponseMessage, Mentionable};
 use tracing::{error, info, warn};
 
@@ -42,6 +45,116 @@ async fn reply_ephemeral(
     }
 }
 
+/// How long a feedback reaction stays on the panel before the bot takes it back.
+const FEEDBACK_REACTION_SECS: u64 = 3;
+
+/// Confirms a role change with a short-lived reaction on the panel instead of
+/// a reply. Returns false when the reaction couldn't be added (e.g. no Add
+/// Reactions permission) so the caller can fall back to a reply.
+async fn react_feedback(
+    interaction: &serenity::ComponentInteraction,
+    ctx: &serenity::Context,
+    added: bool,
+) -> bool {
+    let reaction = serenity::ReactionType::Unicode(if added { "✅" } else { "➖" }.to_string());
+    let message = &interaction.message;
+    if let Err(e) = message.react(&ctx.http, reaction.clone()).await {
+        warn!("react to selfrole panel {}: {}", message.id, e);
+        return false;
+    }
+    if let Err(e) = interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        check_interaction_expired(&e);
+    }
+
+    let http = ctx.http.clone();
+    let (channel_id, message_id) = (message.channel_id, message.id);
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(FEEDBACK_REACTION_SECS)).await;
+        if let Err(e) = http
+            .delete_reaction_me(channel_id, message_id, &reaction)
+            .await
+        {
+            warn!("remove feedback reaction on {}: {}", message_id, e);
+        }
+    });
+    true
+}
+
+fn hierarchy_message(role: &str) -> String {
+    format!(
+        "i can no longer manage {} because it's above my highest role. ask an admin to move my role higher.",
+        role
+    )
+}
+
+/// Guild a self-role click applies to. Panels sent by `/selfroles dm` live in
+/// DMs, where the interaction has no guild, so the panel's own guild is used.
+pub fn selfrole_guild_id(
+    interaction_guild: Option<serenity::GuildId>,
+    config: &SelfRoleConfig,
+) -> Option<u64> {
+    match interaction_guild {
+        Some(id) => Some(id.get()),
+        None => config.guild_id.parse().ok(),
+    }
+}
+
+/// A DM copy is a snapshot of the panel when it was sent, so its buttons can
+/// name roles an admin has since taken off the panel. Only current roles count.
+pub fn role_on_panel(panel_roles: &[SelfRoleRole], role_id: &str) -> bool {
+    panel_roles.iter().any(|r| r.role_id == role_id)
+}
+
+/// Role mentions don't resolve in DMs, so there the role is named instead.
+fn role_display(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    role: serenity::RoleId,
+    in_dm: bool,
+) -> String {
+    if !in_dm {
+        return role.mention().to_string();
+    }
+    ctx.cache
+        .guild(guild_id)
+        .and_then(|g| g.roles.get(&role).map(|r| format!("**{}**", r.name)))
+        .unwrap_or_else(|| "the role".to_string())
+}
+
+/// Checks the cached role hierarchy. `None` when the cache can't answer
+/// (guild, bot member or role missing), in which case we just try the request.
+fn bot_can_manage(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    role: serenity::RoleId,
+) -> Option<bool> {
+    let bot_id = ctx.cache.current_user().id;
+    let guild = ctx.cache.guild(guild_id)?;
+    let bot_member = guild.members.get(&bot_id)?;
+    let target = guild.roles.get(&role)?;
+    let positions = bot_member
+        .roles
+        .iter()
+        .filter_map(|r| guild.roles.get(r))
+        .map(|r| r.position);
+    Some(can_bot_manage_role(
+        positions,
+        target.position,
+        target.managed,
+    ))
+}
+
+fn is_missing_permissions(e: &serenity::Error) -> bool {
+    matches!(
+        e,
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(resp))
+            if resp.status_code == 403
+    )
+}
+
 fn parse_selfrole_custom_id(custom_id: &str) -> Option<(i64, &str)> {
     let parts: Vec<&str> = custom_id.split('_').collect();
     if parts.len() != 3 {
@@ -63,73 +176,87 @@ pub async fn handle_selfrole_interaction(
     interaction: &serenity::ComponentInteraction,
     data: &AppState,
 ) {
-    let (_, role_id) = match parse_selfrole_custom_id(&interaction.data.custom_id) {
+    let (config_id, role_id) = match parse_selfrole_custom_id(&interaction.data.custom_id) {
         Some(parsed) => parsed,
         None => return,
     };
 
     let user_id = interaction.user.id.to_string();
-    let guild_id = match interaction.guild_id {
-        Some(id) => id.to_string(),
-        None => {
-            error!("selfrole outside guild");
-            return;
-        }
-    };
+    let in_dm = interaction.guild_id.is_none();
 
-    match SelfRoleCooldown::check_cooldown(&data.db, &user_id, role_id, &guild_id).await {
-        Ok(true) => {
+    // guild panels are tracked by message; DM copies only by their config
+    let lookup = if in_dm {
+        SelfRoleConfig::get_by_id(&data.db, config_id).await
+    } else {
+        SelfRoleConfig::get_by_message_id(&data.db, &interaction.message.id.to_string()).await
+    };
+    let config = match lookup {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            error!("no selfrole config for message: {}", interaction.message.id);
             reply_ephemeral(
                 interaction,
                 ctx,
-                "You're doing that too quickly! Try again in a few seconds.",
+                "this self-role message is no longer valid.",
             )
             .await;
             return;
         }
-        Ok(false) => {}
         Err(e) => {
-            error!("check cooldown: {}", e);
+            error!("get selfrole config: {}", e);
+            return;
+        }
+    };
+
+    let guild_id_u64 = match selfrole_guild_id(interaction.guild_id, &config) {
+        Some(id) => id,
+        None => {
+            error!("invalid guild_id: {}", config.guild_id);
+            return;
+        }
+    };
+    let guild_id = guild_id_u64.to_string();
+
+    let config_roles = match config.get_roles(&data.db).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            error!("get config roles: {}", e);
             reply_ephemeral(
                 interaction,
                 ctx,
-                "an error occurred while processing your request. please try again.",
+                "an error occurred while processing your request.",
             )
             .await;
             return;
         }
+    };
+    if !role_on_panel(&config_roles, role_id) {
+        reply_ephemeral(interaction, ctx, "this role is no longer on the panel.").await;
+        return;
     }
 
-    let config = match SelfRoleConfig::get_by_message_id(
-        &data.db,
-        &interaction.message.id.to_string(),
-    )
-    .await
-    {
-        Ok(Some(config)) => config,
-        Ok(None) => {
-            error!("no selfrole config for message: {}", interaction.message.id);
+    match SelfRoleCooldown::check_cooldown(&data.db, &user_id, role_id, &guild_id).await {
+        Ok(true) => {
             reply_ephemeral(
                 interaction,
                 ctx,
-                "this self-role message is no longer valid.",
+                "You're doing that too quickly! Try again in a few seconds.",
             )
             .await;
             return;
         }
+        Ok(false) => {}
         Err(e) => {
-            error!("get selfrole config: {}", e);
-            return;
-        }
-    };
-
-    let guild_id_u64: u64 = match guild_id.parse() {
-        Ok(id) => id,
-        Err(_) => {
-            error!("invalid guild_id: {}", guild_id);
+            error!("check cooldown: {}", e);
+            reply_ephemeral(
+                interaction,
+                ctx,
+                "an error occurred while processing your request. please try again.",
+            )
+            .await;
             return;
         }
-    };
+    }
 
     let role_id_u64: u64 = match role_id.parse() {
         Ok(id) => id,
@@ -153,24 +280,20 @@ pub async fn handle_selfrole_interaction(
     };
 
     let role = serenity::RoleId::new(role_id_u64);
+    let role_name = role_display(ctx, guild_id_u64.into(), role, in_dm);
     let has_role = member.roles.contains(&role);
 
+    if bot_can_manage(ctx, guild_id_u64.into(), role) == Some(false) {
+        warn!(
+            "selfrole {} in guild {} is above the bot's highest role",
+            role_id_u64, guild_id
+        );
+        reply_ephemeral(interaction, ctx, &hierarchy_message(&role_name)).await;
+        return;
+    }
+
     // Handle radio mode - remove other roles from this config first
     if config.selection_type == "radio" && !has_role {
-        let config_roles = match config.get_roles(&data.db).await {
-            Ok(roles) => roles,
-            Err(e) => {
-                error!("get config roles: {}", e);
-                reply_ephemeral(
-                    interaction,
-                    ctx,
-                    "an error occurred while processing your request.",
-                )
-                .await;
-                return;
-            }
-        };
-
         for config_role in &config_roles {
             let config_role_id_u64: u64 = match config_role.role_id.parse() {
                 Ok(id) => id,
@@ -210,7 +333,14 @@ pub async fn handle_selfrole_interaction(
             )
             .await
         {
-            Ok(_) => (true, format!("removed {}", role.mention())),
+            Ok(_) => (true, format!("removed {}", role_name)),
+            Err(e) if is_missing_permissions(&e) => {
+                warn!(
+                    "remove role {} in guild {}: missing permissions ({})",
+                    role_id_u64, guild_id, e
+                );
+                (false, hierarchy_message(&role_name))
+            }
             Err(e) => {
                 error!(
                     "remove role {} from {}: {}",
@@ -220,7 +350,7 @@ pub async fn handle_selfrole_interaction(
                     false,
                     format!(
                         "failed to remove {}. i might not have permission or the role might not exist anymore.",
-                        role.mention()
+                        role_name
                     ),
                 )
             }
@@ -236,14 +366,21 @@ pub async fn handle_selfrole_interaction(
             )
             .await
         {
-            Ok(_) => (true, format!("added {}", role.mention())),
+            Ok(_) => (true, format!("added {}", role_name)),
+            Err(e) if is_missing_permissions(&e) => {
+                warn!(
+                    "add role {} in guild {}: missing permissions ({})",
+                    role_id_u64, guild_id, e
+                );
+                (false, hierarchy_message(&role_name))
+            }
             Err(e) => {
                 error!("add role {} to {}: {}", role_id_u64, interaction.user.id, e);
                 (
                     false,
                     format!(
                         "failed to assign {}. the role may be managed by another bot, or is higher than my highest role in the server hierarchy.",
-                        role.mention()
+                        role_name
                     ),
                 )
             }
@@ -251,11 +388,22 @@ pub async fn handle_selfrole_interaction(
     };
 
     if ok {
-        let expires_at = Utc::now() + Duration::seconds(5);
-        if let Err(e) =
-            SelfRoleCooldown::create(&data.db, &user_id, role_id, &guild_id, expires_at).await
+        if config.cooldown_secs > 0 {
+            let expires_at = Utc::now() + Duration::seconds(config.cooldown_secs);
+            if let Err(e) =
+                SelfRoleCooldown::create(&data.db, &user_id, role_id, &guild_id, expires_at).await
+            {
+                error!("create cooldown: {}", e);
+            }
+        }
+
+        let feedback = SelfRoleSettings::get_or_default(&data.db, &guild_id)
+            .await
+            .map(|s| s.feedback())
+            .unwrap_or_default();
+        if feedback == SelfRoleFeedback::React && react_feedback(interaction, ctx, !has_role).await
         {
-            error!("create cooldown: {}", e);
+            return;
         }
     }
 
@@ -14,6 +14,7 @@ mod logging;
 pub mod scheduler;
 
 pub use crate::commands::about::about;
+pub use crate::commands::alias::alias;
 pub use crate::commands::channel::channel;
 pub use crate::commands::github::github;
 pub use crate::commands::github_trending::gh_trending;
@@ -23,13 +24,19 @@ pub use crate::commands::mediaonly::mediaonly;
 pub use crate::commands::purge::purge;
 pub use crate::commands::random::random;
 pub use crate::commands::reminders::reminders;
+pub use crate::commands::role::role;
+pub use crate::commands::schedule::schedule;
 pub use crate::commands::selfroles::selfroles;
 pub use crate::commands::tinyfox::tinyfox;
 pub use crate::commands::uwufy::uwufy;
 pub use crate::events::event_handler;
+pub use crate::events::selfroles::{role_on_panel, selfrole_guild_id};
 use tracing::{debug, error, info};
 
 use anyhow::Result;
+use chrono::Utc;
+use clouder_core::database::ai_usage;
+use clouder_core::database::mediaonly::MediaOnlyStats;
 use poise::serenity_prelude as serenity;
 use std::path::Path;
 use std::sync::Arc;
@@ -88,6 +95,9 @@ async fn async_main() -> Result<()> {
                 github(),
                 gh_trending(),
                 tinyfox(),
+                alias(),
+                schedule(),
+                role(),
             ],
             event_handler: |ctx, event, framework, data| {
                 Box::pin(event_handler(ctx, event, framework, data))
@@ -155,12 +165,53 @@ fn start_cleanup_task(app_state: AppState) {
         loop {
             sleep(Duration::from_secs(300)).await;
 
-            if let Err(e) = SelfRoleCooldown::cleanup_expired(&app_state.db).await {
+            let cleaned = SelfRoleCooldown::cleanup_expired_batched(
+                &app_state.db,
+                app_state.config.selfrole_cooldown_cleanup_batch_size,
+            )
+            .await;
+            if let Err(e) = cleaned {
                 error!("cleanup expired cooldowns: {}", e);
             } else {
                 debug!("cleaned expired cooldowns");
             }
 
+            let retention = app_state.config.mediaonly_stats_retention_days;
+            if retention > 0 {
+                let before = Utc::now().date_naive() - chrono::Days::new(retention as u64);
+                match MediaOnlyStats::purge_before(&app_state.db, before).await {
+                    Ok(0) => {}
+                    Ok(n) => debug!("purged {} old media-only stat rows", n),
+                    Err(e) => error!("purge media-only stats: {}", e),
+                }
+            }
+
+            let retention = app_state.config.llm.usage_retention_days;
+            if retention > 0 {
+                let before = ai_usage::retention_cutoff(Utc::now(), retention);
+                match ai_usage::purge_before(&app_state.db, before).await {
+                    Ok(0) => {}
+                    Ok(n) => debug!("purged {} old ai usage rows", n),
+                    Err(e) => error!("purge ai usage: {}", e),
+                }
+            }
+
+            let retention = app_state.config.selfrole_archive_retention_days;
+            if retention > 0 {
+                let before = Utc::now() - chrono::Duration::days(retention);
+                match SelfRoleConfig::purge_archived_before(&app_state.db, before).await {
+                    Ok(0) => {}
+                    Ok(n) => info!("purged {} archived selfrole panels", n),
+                    Err(e) => error!("purge archived selfrole panels: {}", e),
+                }
+            }
+
+            match SelfRoleRole::delete_orphaned(&app_state.db).await {
+                Ok(0) => {}
+                Ok(n) => info!("removed {} orphaned selfrole roles", n),
+                Err(e) => error!("cleanup orphaned selfrole roles: {}", e),
+            }
+
             if let Err(e) =
                 clouder_core::database::dashboard_sessions::DashboardSession::delete_expired(
                     &app_state.db,
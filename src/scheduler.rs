@@ -1,4 +1,4 @@
-use chrono::{Datelike, Duration, NaiveTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
 use chrono_tz::Tz;
 use clouder_core::{
     config::AppState,
@@ -6,7 +6,8 @@ use clouder_core::{
         CustomReminder, CustomReminderLog, CustomReminderPingRole, CustomReminderSubscription,
         ReminderConfig, ReminderLog, ReminderSubscription, ReminderType, UserSettings,
     },
-    utils::parse_hhmm,
+    database::scheduled_messages::ScheduledMessage,
+    utils::{cron::CronSpec, parse_hhmm},
 };
 use serde_json::json;
 use serenity::all::{ChannelId, CreateMessage};
@@ -250,9 +251,50 @@ async fn run_due_reminders(state: &AppState) -> anyhow::Result<()> {
         .await;
     }
 
+    run_due_scheduled_messages(state, now_utc).await?;
+
+    Ok(())
+}
+
+async fn run_due_scheduled_messages(
+    state: &AppState,
+    now_utc: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let due = ScheduledMessage::get_due(&state.db, now_utc.timestamp()).await?;
+
+    for message in due {
+        // advance before sending so a slow or failed send can't double-post next tick
+        let next = next_scheduled_run(&message, now_utc);
+        ScheduledMessage::set_next_run(&state.db, message.id, next).await?;
+
+        let Ok(channel_id) = message.channel_id.parse::<u64>() else {
+            continue;
+        };
+        let msg = CreateMessage::new().content(&message.content);
+        match state
+            .http
+            .send_message(ChannelId::new(channel_id), vec![], &msg)
+            .await
+        {
+            Ok(_) => info!(
+                "scheduled message {} fired in guild {}",
+                message.id, message.guild_id
+            ),
+            Err(e) => error!("scheduled message {} send failed: {}", message.id, e),
+        }
+    }
+
     Ok(())
 }
 
+/// Next run for a scheduled message after `now`, or `None` for one-offs and
+/// specs that no longer parse (which disables the message).
+pub fn next_scheduled_run(message: &ScheduledMessage, now: DateTime<Utc>) -> Option<i64> {
+    let spec = CronSpec::parse(message.cron_spec.as_deref()?).ok()?;
+    let tz: Tz = message.timezone.parse().ok()?;
+    spec.next_after(now, tz).map(|t| t.timestamp())
+}
+
 async fn send_dms(
     state: &AppState,
     config: &ReminderConfig,
@@ -1,4 +1,7 @@
-use clouder::commands::about::BOT_START_TIME;
+use clouder::commands::about::{
+    BOT_START_TIME, BotDbStats, BotStatus, CpuStats, DiscordStats, LatencyStats, MemoryStats,
+    SystemStats, add_private_user_fields, shows_private_user_fields, user_info_reply,
+};
 use clouder_core::utils::format_duration;
 use poise::serenity_prelude as serenity;
 use sysinfo::System;
@@ -177,3 +180,88 @@ fn test_system_info_collection() {
         assert!(!version.is_empty());
     }
 }
+
+#[test]
+fn test_private_user_fields_are_owner_only_and_ephemeral() {
+    let owner = 12345;
+    let private_user: serenity::User = serde_json::from_value(serde_json::json!({
+        "id": "1", "username": "clouder", "discriminator": "0000", "global_name": null, "avatar": null,
+        "locale": "en-US", "verified": true, "email": "bot@example.com"
+    }))
+    .unwrap();
+    let public_user: serenity::User = serde_json::from_value(serde_json::json!({
+        "id": "2", "username": "someone", "discriminator": "0000", "global_name": null, "avatar": null
+    }))
+    .unwrap();
+
+    for (invoker, user, private) in [
+        (owner, &private_user, true),
+        // nobody else sees them, and a user without them stays public for the owner too
+        (67890, &private_user, false),
+        (owner, &public_user, false),
+    ] {
+        let show = shows_private_user_fields(invoker, owner);
+        let (embed, included) =
+            add_private_user_fields(serenity::CreateEmbed::new().title("info"), user, show);
+        let reply = user_info_reply(embed, included);
+
+        assert_eq!(reply.ephemeral, Some(private), "{} on {}", invoker, user.id);
+        let sent = serde_json::to_string(&reply.embeds).unwrap();
+        assert_eq!(sent.contains("bot@example.com"), private, "{}", sent);
+        assert_eq!(sent.contains("en-US"), private, "{}", sent);
+    }
+}
+
+#[test]
+fn test_bot_status_serializes_to_json() {
+    let status = BotStatus {
+        bot_id: 42,
+        version: "1.2.3",
+        uptime_secs: 3661,
+        latency: LatencyStats {
+            api_ms: 120,
+            gateway_ms: 45,
+        },
+        discord: DiscordStats {
+            guilds: 3,
+            cached_users: 50,
+            cached_channels: 20,
+        },
+        database: BotDbStats {
+            selfrole_configs: 5,
+            selfrole_roles: 15,
+            active_cooldowns: 2,
+            guilds: 3,
+            recent_configs: 1,
+            expired_cooldowns: 0,
+        },
+        cpu: CpuStats {
+            usage_percent: 12.5,
+            cores: 4,
+            arch: "x86_64",
+        },
+        memory: MemoryStats {
+            used_mb: 512,
+            available_mb: 1536,
+            total_mb: 2048,
+            used_percent: 25.0,
+        },
+        system: SystemStats {
+            os: "Linux 6.1".to_string(),
+            kernel: "6.1.0".to_string(),
+            pid: 1234,
+        },
+    };
+
+    let json: serde_json::Value = serde_json::from_str(&status.to_json()).unwrap();
+    assert_eq!(json["bot_id"], 42);
+    assert_eq!(json["version"], "1.2.3");
+    assert_eq!(json["uptime_secs"], 3661);
+    assert_eq!(json["latency"]["api_ms"], 120);
+    assert_eq!(json["latency"]["gateway_ms"], 45);
+    assert_eq!(json["discord"]["cached_channels"], 20);
+    assert_eq!(json["database"]["selfrole_roles"], 15);
+    assert_eq!(json["cpu"]["cores"], 4);
+    assert_eq!(json["memory"]["used_percent"], 25.0);
+    assert_eq!(json["system"]["pid"], 1234);
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::create_test_db;
+    use clouder::commands::alias::{EMBED_FIELD_LIMIT, format_alias_list, validate_alias};
+    use clouder_core::database::command_aliases::CommandAlias;
+
+    fn names() -> Vec<String> {
+        ["about bot", "about server", "help", "purge"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_alias_normalizes() {
+        let (alias, command) = validate_alias(" Server ", "/about  server", &names()).unwrap();
+        assert_eq!(alias, "server");
+        assert_eq!(command, "about server");
+    }
+
+    #[test]
+    fn test_validate_alias_rejects_collision() {
+        assert!(validate_alias("help", "purge", &names()).is_err());
+        // a parent command name also counts as taken
+        assert!(validate_alias("about", "purge", &names()).is_err());
+    }
+
+    #[test]
+    fn test_validate_alias_rejects_unknown_or_malformed() {
+        assert!(validate_alias("nuke", "channel nuke", &names()).is_err());
+        assert!(validate_alias("two words", "purge", &names()).is_err());
+        assert!(validate_alias("", "purge", &names()).is_err());
+        assert!(validate_alias(&"a".repeat(33), "purge", &names()).is_err());
+    }
+
+    #[test]
+    fn test_format_alias_list_fits_an_embed_field() {
+        let aliases: Vec<CommandAlias> = (0..25)
+            .map(|i| CommandAlias {
+                guild_id: "guild1".to_string(),
+                alias: format!("{:0>32}", i),
+                command: "about server".to_string(),
+            })
+            .collect();
+
+        let list = format_alias_list(&aliases, EMBED_FIELD_LIMIT);
+        assert!(list.chars().count() <= EMBED_FIELD_LIMIT);
+        let shown = list.lines().filter(|l| l.contains('→')).count();
+        assert!(shown > 0 && shown < 25);
+        assert_eq!(
+            list.lines().last().unwrap(),
+            format!("…and {} more", 25 - shown)
+        );
+
+        // a list that fits comes back whole
+        let short = format_alias_list(&aliases[..2], EMBED_FIELD_LIMIT);
+        assert_eq!(short.lines().count(), 2);
+        assert!(!short.contains("more"));
+    }
+
+    #[tokio::test]
+    async fn test_alias_resolution() {
+        let db = create_test_db().await;
+
+        CommandAlias::upsert(&db, "guild1", "server", "about server")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            CommandAlias::resolve(&db, "guild1", "SERVER")
+                .await
+                .unwrap(),
+            Some("about server".to_string())
+        );
+        assert_eq!(
+            CommandAlias::resolve(&db, "guild2", "server")
+                .await
+                .unwrap(),
+            None
+        );
+
+        // re-pointing an alias replaces it instead of duplicating
+        CommandAlias::upsert(&db, "guild1", "server", "about bot")
+            .await
+            .unwrap();
+        let aliases = CommandAlias::get_by_guild(&db, "guild1").await.unwrap();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[0].command, "about bot");
+
+        assert!(CommandAlias::delete(&db, "guild1", "server").await.unwrap());
+        assert!(!CommandAlias::delete(&db, "guild1", "server").await.unwrap());
+    }
+}
@@ -66,4 +66,81 @@ mod tests {
             // In real implementation, you might check for valid Unicode emoji
         }
     }
+
+    #[test]
+    fn test_massadd_member_filtering() {
+        use clouder::commands::role::filter_massadd_targets;
+        use serenity::all::{RoleId, UserId};
+
+        let source = RoleId::new(10);
+        let target = RoleId::new(20);
+        let other = RoleId::new(30);
+
+        let roster: Vec<(UserId, Vec<RoleId>)> = vec![
+            (UserId::new(1), vec![source]),
+            (UserId::new(2), vec![source, target]), // already has it
+            (UserId::new(3), vec![other]),
+            (UserId::new(4), vec![]),
+            (UserId::new(5), vec![other, source]),
+        ];
+
+        let picked = filter_massadd_targets(
+            roster.iter().map(|(id, roles)| (*id, roles.as_slice())),
+            source,
+            target,
+        );
+        assert_eq!(picked, vec![UserId::new(1), UserId::new(5)]);
+    }
+
+    #[test]
+    fn test_self_assignable_lookup() {
+        use clouder::commands::role::{find_role_by_name, self_assignable_roles};
+        use serenity::all::RoleId;
+
+        let (he, she, they) = (RoleId::new(1), RoleId::new(2), RoleId::new(3));
+        let news = RoleId::new(10);
+        let pronouns = [he, she, they];
+        let pings = [news, they];
+
+        let assignable = self_assignable_roles([
+            ("radio", pronouns.as_slice(), 0),
+            ("multiple", pings.as_slice(), 30),
+        ]);
+        assert_eq!(assignable.len(), 4);
+        assert_eq!(assignable[&he].displaces, vec![she, they]);
+        assert_eq!(assignable[&they].displaces, vec![he, she]);
+        assert!(assignable[&news].displaces.is_empty());
+        // each role takes its panel's cooldown, the longest one when it's on several
+        assert_eq!(assignable[&he].cooldown_secs, 0);
+        assert_eq!(assignable[&news].cooldown_secs, 30);
+        assert_eq!(assignable[&they].cooldown_secs, 30);
+
+        let names = [(he, "he/him"), (news, "News")];
+        assert_eq!(find_role_by_name("news", names), Some(news));
+        assert_eq!(find_role_by_name(" @He/Him ", names), Some(he));
+        assert_eq!(find_role_by_name("admin", names), None);
+    }
+
+    #[test]
+    fn test_self_assign_toggle_plan() {
+        use clouder::commands::role::{RoleToggle, plan_role_toggle};
+        use serenity::all::RoleId;
+
+        let (he, she, they) = (RoleId::new(1), RoleId::new(2), RoleId::new(3));
+
+        assert_eq!(
+            plan_role_toggle(&[he], he, &[she, they]),
+            RoleToggle::Remove
+        );
+        assert_eq!(
+            plan_role_toggle(&[she], he, &[she, they]),
+            RoleToggle::Add {
+                displaced: vec![she]
+            }
+        );
+        assert_eq!(
+            plan_role_toggle(&[], he, &[]),
+            RoleToggle::Add { displaced: vec![] }
+        );
+    }
 }
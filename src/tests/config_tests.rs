@@ -108,4 +108,23 @@ mod tests {
         assert!(!config.database.url.is_empty());
         assert_eq!(config.database.url, ":memory:");
     }
+
+    #[cfg(feature = "llm")]
+    #[test]
+    fn test_retry_right_after_a_prompt_is_rejected_by_default() {
+        use clouder_core::config::LLM_COOLDOWN_SECS;
+        use clouder_llm::LlmClient;
+        use std::time::Duration;
+
+        let config = Config::test_config();
+        let client = LlmClient::new(
+            "https://api.openai.com/v1".to_string(),
+            "test-key".to_string(),
+            30,
+        );
+
+        // one provider call per cooldown, whether it's a prompt or its retry
+        assert!(client.check_and_update_cooldown(7, Duration::from_secs(LLM_COOLDOWN_SECS)));
+        assert!(!client.check_and_update_retry_cooldown(7, config.llm.retry_cooldown()));
+    }
 }
@@ -0,0 +1,341 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::create_test_app_state;
+    use chrono::{Duration, Utc};
+    use clouder_core::database::ai_usage;
+    use clouder_core::database::command_aliases::CommandAlias;
+    use clouder_core::database::dashboard_sessions::DashboardSession;
+    use clouder_core::database::guild_cache::CachedGuild;
+    use clouder_core::database::guild_configs::GuildConfig;
+    use clouder_core::database::mediaonly::{MediaOnlyConfig, MediaOnlyStats};
+    use clouder_core::database::reminders::{
+        CustomReminder, CustomReminderPingRole, CustomReminderSubscription, ReminderSubscription,
+        UserSettings,
+    };
+    use clouder_core::database::scheduled_messages::ScheduledMessage;
+    use clouder_core::database::selfroles::{
+        SelfRoleConfig, SelfRoleCooldown, SelfRoleEmojiPolicy, SelfRoleLabel, SelfRoleRole,
+    };
+    use clouder_core::database::uwufy::UwufyToggle;
+    use clouder_core::database::welcome_goodbye::{PendingWelcome, WelcomeGoodbyeConfig};
+    use clouder_core::shared::{
+        delete_guild_data, delete_user_data, export_guild_data, export_user_data,
+        get_guild_overview,
+    };
+    use sqlx::SqlitePool;
+
+    const GUILD: u64 = 111;
+    const OTHER_GUILD: u64 = 222;
+    // seed_guild leaves a cooldown, uwufy toggle and scheduled message for this user
+    const USER: &str = "30";
+
+    async fn seed_guild(db: &SqlitePool, guild_id: &str) {
+        GuildConfig::upsert(db, guild_id, "Europe/Berlin", "?", Some("#ff00ff"))
+            .await
+            .unwrap();
+
+        let config = SelfRoleConfig::create(db, guild_id, "10", "Roles", "pick", "multiple")
+            .await
+            .unwrap();
+        SelfRoleRole::create(db, config.id, "20", "🎮")
+            .await
+            .unwrap();
+        SelfRoleLabel::upsert(db, guild_id, "20", "gamer")
+            .await
+            .unwrap();
+        SelfRoleEmojiPolicy::upsert(db, guild_id, "deny", &["🍆".to_string()])
+            .await
+            .unwrap();
+        SelfRoleCooldown::create(db, "30", "20", guild_id, Utc::now() + Duration::hours(1))
+            .await
+            .unwrap();
+
+        sqlx::query("INSERT INTO reminder_configs (guild_id, reminder_type) VALUES (?, 'wysi')")
+            .bind(guild_id)
+            .execute(db)
+            .await
+            .unwrap();
+        let reminder_id = CustomReminder::create(
+            db, guild_id, "water", None, "12:00", "mon", "UTC", "text", None, None, None, None,
+        )
+        .await
+        .unwrap();
+        CustomReminderPingRole::set_roles(db, reminder_id, &["40".to_string()])
+            .await
+            .unwrap();
+
+        ScheduledMessage::create(db, guild_id, "10", "hi", None, "UTC", 0, "30")
+            .await
+            .unwrap();
+        WelcomeGoodbyeConfig::upsert_config(
+            db,
+            &WelcomeGoodbyeConfig {
+                guild_id: guild_id.to_string(),
+                welcome_enabled: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        MediaOnlyConfig::upsert(db, guild_id, "50", true)
+            .await
+            .unwrap();
+        UwufyToggle::toggle(db, guild_id, "30").await.unwrap();
+        CommandAlias::upsert(db, guild_id, "srv", "about server")
+            .await
+            .unwrap();
+        ai_usage::record(db, guild_id, "30", 12, 3).await.unwrap();
+        MediaOnlyStats::record_deletion(db, guild_id, "50", "30", Utc::now().date_naive())
+            .await
+            .unwrap();
+        PendingWelcome::record(db, guild_id, "30").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_guild_data_bundles_every_module() {
+        let app_state = create_test_app_state().await;
+        seed_guild(&app_state.db, &GUILD.to_string()).await;
+        seed_guild(&app_state.db, &OTHER_GUILD.to_string()).await;
+
+        let export = export_guild_data(&app_state, GUILD).await.unwrap();
+
+        assert_eq!(export["guild_id"], GUILD.to_string());
+        assert_eq!(export["guild_config"]["command_prefix"], "?");
+        assert_eq!(export["selfroles"][0]["title"], "Roles");
+        assert_eq!(export["selfroles"][0]["roles"][0]["emoji"], "🎮");
+        assert_eq!(export["selfrole_labels"]["20"], "gamer");
+        assert_eq!(export["selfrole_emoji_policy"]["mode"], "deny");
+        assert_eq!(export["selfrole_cooldowns"][0]["user_id"], "30");
+        assert_eq!(export["reminders"][0]["reminder_type"], "Wysi");
+        assert!(export["reminders"][0]["ping_roles"].is_array());
+        assert_eq!(export["custom_reminders"][0]["name"], "water");
+        assert_eq!(
+            export["custom_reminders"][0]["ping_roles"][0]["role_id"],
+            "40"
+        );
+        assert_eq!(export["scheduled_messages"][0]["content"], "hi");
+        assert_eq!(export["welcome_goodbye"]["welcome_enabled"], true);
+        assert_eq!(export["mediaonly"][0]["channel_id"], "50");
+        assert_eq!(export["uwufy"][0]["user_id"], "30");
+        assert_eq!(export["command_aliases"][0]["alias"], "srv");
+        assert_eq!(export["ai_usage"][0]["user_id"], "30");
+        assert_eq!(export["ai_usage"][0]["prompt_tokens"], 12);
+        assert_eq!(export["mediaonly_stats"][0]["channel_id"], "50");
+        assert_eq!(export["mediaonly_stats"][0]["deletions"], 1);
+        assert_eq!(export["pending_welcomes"][0]["user_id"], "30");
+
+        // only this guild's rows
+        for module in [
+            "selfroles",
+            "custom_reminders",
+            "mediaonly",
+            "command_aliases",
+            "ai_usage",
+            "mediaonly_stats",
+            "pending_welcomes",
+        ] {
+            assert_eq!(export[module].as_array().unwrap().len(), 1, "{}", module);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_guild_data_removes_everything() {
+        let app_state = create_test_app_state().await;
+        let db = &app_state.db;
+        seed_guild(db, &GUILD.to_string()).await;
+        seed_guild(db, &OTHER_GUILD.to_string()).await;
+
+        let result = delete_guild_data(&app_state, GUILD).await.unwrap();
+        assert!(result["removed"].as_u64().unwrap() >= 14);
+
+        let export = export_guild_data(&app_state, GUILD).await.unwrap();
+        assert!(export["guild_config"].is_null());
+        assert!(export["selfrole_labels"].as_object().unwrap().is_empty());
+        assert!(export["selfrole_emoji_policy"].is_null());
+        assert!(export["welcome_goodbye"].is_null());
+        for module in [
+            "selfroles",
+            "selfrole_cooldowns",
+            "reminders",
+            "custom_reminders",
+            "scheduled_messages",
+            "mediaonly",
+            "uwufy",
+            "command_aliases",
+            "ai_usage",
+            "mediaonly_stats",
+            "pending_welcomes",
+        ] {
+            assert!(export[module].as_array().unwrap().is_empty(), "{}", module);
+        }
+
+        // child rows go with their parents; the other guild keeps its own
+        let children: i64 = sqlx::query_scalar(
+            "SELECT (SELECT COUNT(*) FROM selfrole_roles) + (SELECT COUNT(*) FROM custom_reminder_ping_roles)",
+        )
+        .fetch_one(db.as_ref())
+        .await
+        .unwrap();
+        assert_eq!(children, 2);
+
+        let other = export_guild_data(&app_state, OTHER_GUILD).await.unwrap();
+        assert_eq!(other["selfroles"].as_array().unwrap().len(), 1);
+        assert_eq!(other["command_aliases"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_guild_overview_summarizes_seeded_modules() {
+        let app_state = create_test_app_state().await;
+        let db = &app_state.db;
+        seed_guild(db, &GUILD.to_string()).await;
+        seed_guild(db, &OTHER_GUILD.to_string()).await;
+        MediaOnlyConfig::upsert(db, &GUILD.to_string(), "51", false)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE reminder_configs SET enabled = TRUE WHERE guild_id = ?")
+            .bind(GUILD.to_string())
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+
+        let overview = get_guild_overview(&app_state, GUILD).await.unwrap();
+
+        assert_eq!(overview["selfroles"]["panels"], 1);
+        assert_eq!(overview["selfroles"]["emoji_policy"], "deny");
+        assert_eq!(overview["welcome"]["enabled"], true);
+        assert_eq!(overview["goodbye"]["enabled"], false);
+        assert_eq!(overview["mediaonly"]["channels"], 2);
+        assert_eq!(overview["mediaonly"]["enabled"], 1);
+        assert_eq!(overview["reminders"]["wysi_enabled"], true);
+        assert_eq!(overview["reminders"]["custom"], 1);
+        assert_eq!(overview["scheduled_messages"], 1);
+        assert_eq!(overview["uwufy_members"], 1);
+        assert_eq!(overview["command_aliases"], 1);
+        assert_eq!(overview["ai"]["enabled"], false);
+        assert_eq!(overview["ai"]["tokens_this_month"], 15);
+
+        let empty = get_guild_overview(&app_state, 999).await.unwrap();
+        assert_eq!(empty["selfroles"]["panels"], 0);
+        assert!(empty["selfroles"]["emoji_policy"].is_null());
+        assert_eq!(empty["welcome"]["enabled"], false);
+        assert_eq!(empty["reminders"]["wysi_enabled"], false);
+    }
+
+    async fn seed_user(db: &SqlitePool, user_id: &str) -> DashboardSession {
+        UserSettings::upsert(db, user_id, "Asia/Tokyo", false)
+            .await
+            .unwrap();
+        let config_id: i64 = sqlx::query_scalar("SELECT id FROM reminder_configs LIMIT 1")
+            .fetch_one(db)
+            .await
+            .unwrap();
+        ReminderSubscription::subscribe(db, user_id, config_id)
+            .await
+            .unwrap();
+        let reminder_id: i64 = sqlx::query_scalar("SELECT id FROM custom_reminders LIMIT 1")
+            .fetch_one(db)
+            .await
+            .unwrap();
+        CustomReminderSubscription::subscribe(db, user_id, reminder_id)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO dashboard_users (user_id, api_key_hash, oauth_token, username) VALUES (?, 'hash', 'secret', 'someone')",
+        )
+        .bind(user_id)
+        .execute(db)
+        .await
+        .unwrap();
+        CachedGuild::replace_for_user(
+            db,
+            user_id,
+            &[(GUILD.to_string(), "test guild".to_string(), None, 8)],
+        )
+        .await
+        .unwrap();
+        DashboardSession::create(db, user_id, 3600).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_export_user_data_shape() {
+        let app_state = create_test_app_state().await;
+        let db = &app_state.db;
+        seed_guild(db, &GUILD.to_string()).await;
+        seed_user(db, USER).await;
+
+        let export = export_user_data(&app_state, USER).await.unwrap();
+
+        assert_eq!(export["user_id"], USER);
+        assert_eq!(export["profile"]["username"], "someone");
+        assert_eq!(export["profile"]["has_api_key"], true);
+        assert_eq!(export["profile"]["has_oauth_token"], true);
+        // credentials never leave the database
+        let raw = export.to_string();
+        assert!(!raw.contains("secret") && !raw.contains("\"hash\""));
+        assert_eq!(export["settings"]["timezone"], "Asia/Tokyo");
+        assert_eq!(export["reminder_subscriptions"][0]["user_id"], USER);
+        assert_eq!(export["custom_reminder_subscriptions"][0]["user_id"], USER);
+        assert_eq!(export["selfrole_cooldowns"][0]["role_id"], "20");
+        assert_eq!(export["uwufy"][0]["guild_id"], GUILD.to_string());
+        assert_eq!(export["guilds"][0]["name"], "test guild");
+        assert_eq!(export["scheduled_messages_created"][0]["content"], "hi");
+        assert_eq!(export["ai_usage"][0]["guild_id"], GUILD.to_string());
+        assert_eq!(export["ai_usage"][0]["completion_tokens"], 3);
+        assert_eq!(export["mediaonly_stats"][0]["guild_id"], GUILD.to_string());
+        assert_eq!(export["mediaonly_stats"][0]["user_id"], USER);
+        assert_eq!(export["pending_welcomes"][0]["guild_id"], GUILD.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_data_removes_rows_and_sessions() {
+        let app_state = create_test_app_state().await;
+        let db = &app_state.db;
+        seed_guild(db, &GUILD.to_string()).await;
+        let session = seed_user(db, USER).await;
+        let other_session = seed_user(db, "31").await;
+
+        let result = delete_user_data(&app_state, USER).await.unwrap();
+        assert!(result["removed"].as_u64().unwrap() >= 8);
+
+        assert!(
+            DashboardSession::get_active(db, &session.session_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let export = export_user_data(&app_state, USER).await.unwrap();
+        assert!(export["profile"].is_null());
+        assert!(export["settings"].is_null());
+        for key in [
+            "reminder_subscriptions",
+            "custom_reminder_subscriptions",
+            "selfrole_cooldowns",
+            "uwufy",
+            "guilds",
+            "ai_usage",
+            "mediaonly_stats",
+            "pending_welcomes",
+        ] {
+            assert!(export[key].as_array().unwrap().is_empty(), "{}", key);
+        }
+        // guild content the user authored stays
+        assert_eq!(
+            export["scheduled_messages_created"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // other users are untouched
+        assert!(
+            DashboardSession::get_active(db, &other_session.session_id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        let other = export_user_data(&app_state, "31").await.unwrap();
+        assert_eq!(other["settings"]["timezone"], "Asia/Tokyo");
+    }
+}
@@ -1,8 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use crate::tests::create_test_db;
+    use crate::tests::{create_test_app_state, create_test_db};
     use chrono::{Duration, Utc};
-    use clouder_core::database::selfroles::{SelfRoleConfig, SelfRoleCooldown, SelfRoleRole};
+    use clouder_core::database::selfroles::{
+        SelfRoleConfig, SelfRoleCooldown, SelfRoleEmojiPolicy, SelfRoleRole,
+    };
 
     #[tokio::test]
     async fn test_selfrole_config_creation() {
@@ -349,6 +351,33 @@ mod tests {
         assert_eq!(config_with_message.id, config1.id);
     }
 
+    #[tokio::test]
+    async fn test_selfrole_message_id_conflict_is_typed() {
+        use clouder_core::database::selfroles::MessageIdTaken;
+
+        let db = create_test_db().await;
+        let mut first = SelfRoleConfig::create(&db, "1", "10", "first", "", "multiple")
+            .await
+            .unwrap();
+        let mut second = SelfRoleConfig::create(&db, "1", "11", "second", "", "multiple")
+            .await
+            .unwrap();
+
+        first.update_message_id(&db, "500").await.unwrap();
+        // a retry setting the same id again is fine
+        first.update_message_id(&db, "500").await.unwrap();
+        assert_eq!(first.message_id.as_deref(), Some("500"));
+
+        let err = second.update_message_id(&db, "500").await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MessageIdTaken>(),
+            Some(&MessageIdTaken {
+                config_id: first.id
+            })
+        );
+        assert_eq!(second.message_id, None);
+    }
+
     #[tokio::test]
     async fn test_selfrole_role_deletion_cascade() {
         let db = create_test_db().await;
@@ -392,6 +421,74 @@ mod tests {
         // but we can't easily test this without re-creating the config and checking
     }
 
+    #[tokio::test]
+    async fn test_selfrole_cooldown_delete_for_user() {
+        let db = create_test_db().await;
+        let future_time = Utc::now() + Duration::hours(1);
+        for (user, role, guild) in [
+            ("user1", "role1", "guild1"),
+            ("user1", "role2", "guild1"),
+            ("user1", "role1", "guild2"),
+            ("user2", "role1", "guild1"),
+        ] {
+            SelfRoleCooldown::create(&db, user, role, guild, future_time)
+                .await
+                .unwrap();
+        }
+
+        // scoped to one role in one guild
+        let cleared =
+            SelfRoleCooldown::delete_for_user(&db, "user1", Some("guild1"), Some("role1"))
+                .await
+                .unwrap();
+        assert_eq!(cleared, 1);
+        assert!(
+            !SelfRoleCooldown::check_cooldown(&db, "user1", "role1", "guild1")
+                .await
+                .unwrap()
+        );
+        assert!(
+            SelfRoleCooldown::check_cooldown(&db, "user1", "role2", "guild1")
+                .await
+                .unwrap()
+        );
+
+        // the rest of the guild leaves other guilds and users alone
+        let cleared = SelfRoleCooldown::delete_for_user(&db, "user1", Some("guild1"), None)
+            .await
+            .unwrap();
+        assert_eq!(cleared, 1);
+        assert!(
+            SelfRoleCooldown::check_cooldown(&db, "user1", "role1", "guild2")
+                .await
+                .unwrap()
+        );
+        assert!(
+            SelfRoleCooldown::check_cooldown(&db, "user2", "role1", "guild1")
+                .await
+                .unwrap()
+        );
+
+        // unscoped clears everything left for the user
+        let cleared = SelfRoleCooldown::delete_for_user(&db, "user1", None, None)
+            .await
+            .unwrap();
+        assert_eq!(cleared, 1);
+        assert!(
+            SelfRoleCooldown::get_by_user(&db, "user1")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            SelfRoleCooldown::get_by_user(&db, "user2")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
     #[tokio::test]
     async fn test_selfrole_cooldown_cleanup() {
         let db = create_test_db().await;
@@ -454,6 +551,91 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_selfrole_config_move_to() {
+        let db = create_test_db().await;
+        let mut config = SelfRoleConfig::create(&db, "123", "100", "roles", "", "radio")
+            .await
+            .unwrap();
+        config.update_message_id(&db, "1000").await.unwrap();
+
+        config.move_to(&db, "200", "2000").await.unwrap();
+        assert_eq!(config.channel_id, "200");
+        assert_eq!(config.message_id.as_deref(), Some("2000"));
+
+        let stored = SelfRoleConfig::get_by_id(&db, config.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.channel_id, "200");
+        assert_eq!(stored.message_id.as_deref(), Some("2000"));
+        assert!(
+            SelfRoleConfig::get_by_message_id(&db, "1000")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_cooldown_cleanup_batched() {
+        let db = create_test_db().await;
+
+        let expired_time = Utc::now() - Duration::hours(1);
+        for i in 0..7 {
+            SelfRoleCooldown::create(&db, &format!("user{}", i), "role", "guild", expired_time)
+                .await
+                .unwrap();
+        }
+        let future_time = Utc::now() + Duration::hours(1);
+        SelfRoleCooldown::create(&db, "active", "role", "guild", future_time)
+            .await
+            .unwrap();
+
+        // 7 expired rows in batches of 3 takes three statements
+        let removed = SelfRoleCooldown::cleanup_expired_batched(&db, 3)
+            .await
+            .unwrap();
+        assert_eq!(removed, 7);
+
+        let remaining = SelfRoleCooldown::get_by_guild(&db, "guild").await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].user_id, "active");
+
+        let removed = SelfRoleCooldown::cleanup_expired_batched(&db, 3)
+            .await
+            .unwrap();
+        assert_eq!(removed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_cooldown_cleanup_batched_without_a_batch_size() {
+        let db = create_test_db().await;
+
+        let expired_time = Utc::now() - Duration::hours(1);
+        for i in 0..4 {
+            SelfRoleCooldown::create(&db, &format!("user{}", i), "role", "guild", expired_time)
+                .await
+                .unwrap();
+        }
+
+        // 0 and negative sizes fall back to one unbatched delete instead of looping
+        let removed = SelfRoleCooldown::cleanup_expired_batched(&db, 0)
+            .await
+            .unwrap();
+        assert_eq!(removed, 4);
+        let removed = SelfRoleCooldown::cleanup_expired_batched(&db, -1)
+            .await
+            .unwrap();
+        assert_eq!(removed, 0);
+        assert!(
+            SelfRoleCooldown::get_by_guild(&db, "guild")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[tokio::test]
     async fn test_selfrole_config_empty_fields() {
         let db = create_test_db().await;
@@ -779,4 +961,408 @@ mod tests {
             .unwrap();
         assert!(!not_deleted);
     }
+
+    #[tokio::test]
+    async fn test_selfrole_role_delete_orphaned() {
+        let db = create_test_db().await;
+
+        let config = SelfRoleConfig::create(
+            &db,
+            "123456789",
+            "987654321",
+            "Test Roles",
+            "Select your roles below:",
+            "multiple",
+        )
+        .await
+        .unwrap();
+        SelfRoleRole::create(&db, config.id, "111", "a")
+            .await
+            .unwrap();
+
+        // bypass the FK so the row survives, as it would on a pool without the pragma
+        let mut conn = db.acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO selfrole_roles (config_id, role_id, emoji) VALUES (9999, '222', 'b')",
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        let removed = SelfRoleRole::delete_orphaned(&db).await.unwrap();
+        assert_eq!(removed, 1);
+
+        let remaining: Vec<String> =
+            sqlx::query_scalar("SELECT role_id FROM selfrole_roles ORDER BY id")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(remaining, vec!["111".to_string()]);
+
+        assert_eq!(SelfRoleRole::delete_orphaned(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_emoji_policy_enforced() {
+        let app_state = create_test_app_state().await;
+        let guild_id = 123456789u64;
+
+        SelfRoleEmojiPolicy::upsert(
+            &app_state.db,
+            &guild_id.to_string(),
+            "deny",
+            &["🍆".to_string(), "<:blocked:4242>".to_string()],
+        )
+        .await
+        .unwrap();
+
+        let policy = SelfRoleEmojiPolicy::get(&app_state.db, &guild_id.to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(policy.emoji_list(), vec!["🍆", "4242"]);
+        assert!(!policy.allows("🍆"));
+        // custom emojis match by id, even after a rename
+        assert!(!policy.allows("<a:renamed:4242>"));
+        assert!(policy.allows("🎮"));
+        assert!(policy.allows(""));
+
+        let payload = serde_json::json!({
+            "title": "Roles",
+            "channel_id": "987654321",
+            "roles": [
+                { "role_id": "111", "emoji": "🎮" },
+                { "role_id": "222", "emoji": "<:blocked:4242>" },
+            ],
+        });
+        // rejected before any discord call, so the fake token never gets used
+        let err = clouder_core::shared::create_selfrole(&app_state, guild_id, 1, &payload)
+            .await
+            .unwrap_err();
+        assert!(err.contains("not allowed"), "{}", err);
+        let err = clouder_core::shared::update_selfrole(&app_state, guild_id, 1, 1, &payload)
+            .await
+            .unwrap_err();
+        assert!(err.contains("not allowed"), "{}", err);
+
+        SelfRoleEmojiPolicy::upsert(
+            &app_state.db,
+            &guild_id.to_string(),
+            "allow",
+            &["🎮".to_string()],
+        )
+        .await
+        .unwrap();
+        let policy = SelfRoleEmojiPolicy::get(&app_state.db, &guild_id.to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(policy.allows("🎮"));
+        assert!(!policy.allows("🍆"));
+
+        assert!(
+            SelfRoleEmojiPolicy::delete(&app_state.db, &guild_id.to_string())
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_max_panels_per_channel() {
+        let app_state = create_test_app_state().await;
+        let guild_id = 123456789u64;
+        let max = app_state.config.selfrole_max_panels_per_channel;
+
+        for i in 0..max {
+            SelfRoleConfig::create(
+                &app_state.db,
+                &guild_id.to_string(),
+                "555",
+                &format!("panel {}", i),
+                "",
+                "multiple",
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(
+            SelfRoleConfig::count_by_channel(&app_state.db, &guild_id.to_string(), "555")
+                .await
+                .unwrap(),
+            max
+        );
+        assert_eq!(
+            SelfRoleConfig::count_by_channel(&app_state.db, &guild_id.to_string(), "556")
+                .await
+                .unwrap(),
+            0
+        );
+
+        let payload = serde_json::json!({
+            "title": "One too many",
+            "channel_id": "555",
+            "roles": [{ "role_id": "111", "emoji": "" }],
+        });
+        let err = clouder_core::shared::create_selfrole(&app_state, guild_id, 1, &payload)
+            .await
+            .unwrap_err();
+        assert!(err.contains("per channel"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_ai_usage_summary_aggregates_per_guild() {
+        use clouder_core::database::ai_usage;
+
+        let app_state = create_test_app_state().await;
+        let db = app_state.db.as_ref();
+
+        ai_usage::record(db, "1", "10", 100, 50).await.unwrap();
+        ai_usage::record(db, "1", "10", 20, 10).await.unwrap();
+        ai_usage::record(db, "1", "11", 300, 100).await.unwrap();
+        ai_usage::record(db, "1", "12", 5, 5).await.unwrap();
+        ai_usage::record(db, "2", "10", 999, 999).await.unwrap();
+        // last month's usage doesn't count toward this period
+        sqlx::query(
+            "INSERT INTO ai_usage (guild_id, user_id, prompt_tokens, completion_tokens, created_at) VALUES ('1', '12', 5000, 5000, '2000-01-01 00:00:00')",
+        )
+        .execute(db)
+        .await
+        .unwrap();
+
+        let since = ai_usage::period_start(Utc::now());
+        let summary = ai_usage::summary(db, "1", since, 2).await.unwrap();
+        assert_eq!(summary.requests, 4);
+        assert_eq!(summary.prompt_tokens, 425);
+        assert_eq!(summary.completion_tokens, 165);
+        assert_eq!(summary.total_tokens(), 590);
+        assert_eq!(
+            summary.top_users,
+            vec![
+                ai_usage::AiUserUsage {
+                    user_id: "11".to_string(),
+                    requests: 1,
+                    tokens: 400,
+                },
+                ai_usage::AiUserUsage {
+                    user_id: "10".to_string(),
+                    requests: 2,
+                    tokens: 180,
+                },
+            ]
+        );
+        assert_eq!(ai_usage::tokens_since(db, "1", since).await.unwrap(), 590);
+
+        let hidden = ai_usage::summary(db, "1", since, 0).await.unwrap();
+        assert!(hidden.top_users.is_empty());
+        assert_eq!(hidden.requests, 4);
+
+        let empty = ai_usage::summary(db, "3", since, 10).await.unwrap();
+        assert_eq!(empty, ai_usage::AiUsageSummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_ai_usage_purge_keeps_the_budget_month() {
+        use chrono::TimeZone;
+        use clouder_core::database::ai_usage;
+
+        // 90 days back from mid-march lands in december; the cutoff stays there
+        let now = Utc.with_ymd_and_hms(2026, 3, 15, 12, 0, 0).unwrap();
+        assert_eq!(
+            ai_usage::retention_cutoff(now, 90),
+            now - Duration::days(90)
+        );
+        // a short retention would reach into march, so it stops at the 1st
+        assert_eq!(
+            ai_usage::retention_cutoff(now, 3),
+            Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap()
+        );
+
+        let app_state = create_test_app_state().await;
+        let db = app_state.db.as_ref();
+        ai_usage::record(db, "1", "10", 100, 50).await.unwrap();
+        sqlx::query(
+            "INSERT INTO ai_usage (guild_id, user_id, prompt_tokens, completion_tokens, created_at) VALUES ('1', '10', 5, 5, '2000-01-01 00:00:00')",
+        )
+        .execute(db)
+        .await
+        .unwrap();
+
+        let before = ai_usage::retention_cutoff(Utc::now(), 90);
+        assert_eq!(ai_usage::purge_before(db, before).await.unwrap(), 1);
+        let left = ai_usage::get_by_guild(db, "1").await.unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].prompt_tokens, 100);
+    }
+
+    #[tokio::test]
+    async fn test_ai_channel_allow_list() {
+        use clouder_core::database::ai_settings::GuildAiSettings;
+
+        let app_state = create_test_app_state().await;
+
+        // no settings row: every channel is allowed
+        let settings = GuildAiSettings::get_or_default(&app_state.db, "1")
+            .await
+            .unwrap();
+        assert!(settings.channel_list().is_empty());
+        assert!(settings.allows_channel("100"));
+
+        let saved = clouder_core::shared::update_ai_settings(
+            &app_state,
+            1,
+            &serde_json::json!({ "allowed_channel_ids": ["100", "200", "100"] }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            saved["allowed_channel_ids"],
+            serde_json::json!(["100", "200"])
+        );
+
+        let settings = GuildAiSettings::get_or_default(&app_state.db, "1")
+            .await
+            .unwrap();
+        assert!(settings.allows_channel("100"));
+        assert!(settings.allows_channel("200"));
+        assert!(!settings.allows_channel("300"));
+
+        // other guilds keep answering everywhere
+        let other = GuildAiSettings::get_or_default(&app_state.db, "2")
+            .await
+            .unwrap();
+        assert!(other.allows_channel("300"));
+
+        // clearing the list opens every channel again
+        clouder_core::shared::update_ai_settings(
+            &app_state,
+            1,
+            &serde_json::json!({ "allowed_channel_ids": [] }),
+        )
+        .await
+        .unwrap();
+        let settings = GuildAiSettings::get_or_default(&app_state.db, "1")
+            .await
+            .unwrap();
+        assert!(settings.allows_channel("300"));
+
+        assert!(
+            clouder_core::shared::update_ai_settings(
+                &app_state,
+                1,
+                &serde_json::json!({ "allowed_channel_ids": ["general"] }),
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ai_channel_cooldown_setting() {
+        use clouder_core::shared::update_ai_settings;
+
+        let app_state = create_test_app_state().await;
+
+        let saved = update_ai_settings(
+            &app_state,
+            1,
+            &serde_json::json!({ "allowed_channel_ids": ["100"], "channel_cooldown_secs": 30 }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(saved["channel_cooldown_secs"], 30);
+
+        // changing the allow-list alone keeps the spacing
+        let kept = update_ai_settings(
+            &app_state,
+            1,
+            &serde_json::json!({ "allowed_channel_ids": [] }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(kept["channel_cooldown_secs"], 30);
+
+        for bad in [
+            serde_json::json!(-1),
+            serde_json::json!(3601),
+            serde_json::json!("30"),
+        ] {
+            assert!(
+                update_ai_settings(
+                    &app_state,
+                    1,
+                    &serde_json::json!({ "channel_cooldown_secs": bad }),
+                )
+                .await
+                .is_err()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_replace_contents_rolls_back() {
+        let pool = create_test_db().await;
+        let mut config = SelfRoleConfig::create(&pool, "1", "2", "Colors", "pick one", "multiple")
+            .await
+            .unwrap();
+        SelfRoleRole::create(&pool, config.id, "100", "🔴")
+            .await
+            .unwrap();
+        SelfRoleRole::create(&pool, config.id, "200", "🔵")
+            .await
+            .unwrap();
+
+        // stand-in for an insert failing partway through the swap
+        sqlx::query(
+            "CREATE TRIGGER fail_role_insert BEFORE INSERT ON selfrole_roles
+             WHEN NEW.role_id = '999' BEGIN SELECT RAISE(ABORT, 'insert failed'); END",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let roles = vec![
+            ("300".to_string(), "🟢".to_string()),
+            ("999".to_string(), "⚫".to_string()),
+        ];
+        assert!(
+            config
+                .replace_contents(&pool, "Renamed", "new body", "radio", &roles)
+                .await
+                .is_err()
+        );
+
+        let stored = SelfRoleConfig::get_by_id(&pool, config.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.title, "Colors");
+        assert_eq!(stored.selection_type, "multiple");
+        let role_ids: Vec<String> = stored
+            .get_roles(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.role_id)
+            .collect();
+        assert_eq!(role_ids, vec!["100", "200"]);
+        assert_eq!(config.title, "Colors");
+
+        config
+            .replace_contents(&pool, "Renamed", "new body", "radio", &roles[..1])
+            .await
+            .unwrap();
+        let roles = config.get_roles(&pool).await.unwrap();
+        assert_eq!(roles.len(), 1);
+        assert_eq!(roles[0].role_id, "300");
+        assert_eq!(config.title, "Renamed");
+    }
 }
@@ -466,6 +466,77 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_removed_role_is_not_on_the_panel_anymore() {
+        use clouder::role_on_panel;
+
+        let app_state = create_test_app_state().await;
+        let config = SelfRoleConfig::create(
+            &app_state.db,
+            "12345",
+            "67890",
+            "DM panel",
+            "pick one",
+            "multiple",
+        )
+        .await
+        .unwrap();
+        SelfRoleRole::create(&app_state.db, config.id, "111", "a")
+            .await
+            .unwrap();
+        SelfRoleRole::create(&app_state.db, config.id, "222", "b")
+            .await
+            .unwrap();
+
+        let roles = config.get_roles(&app_state.db).await.unwrap();
+        assert!(role_on_panel(&roles, "111"));
+        assert!(role_on_panel(&roles, "222"));
+        assert!(!role_on_panel(&roles, "333"));
+
+        // an admin drops 222; an old DM copy still has its button
+        SelfRoleRole::delete_by_config_id(&app_state.db, config.id)
+            .await
+            .unwrap();
+        SelfRoleRole::create(&app_state.db, config.id, "111", "a")
+            .await
+            .unwrap();
+        let roles = config.get_roles(&app_state.db).await.unwrap();
+        assert!(role_on_panel(&roles, "111"));
+        assert!(!role_on_panel(&roles, "222"));
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_guild_resolves_from_config_in_dm() {
+        use clouder::selfrole_guild_id;
+        use poise::serenity_prelude::GuildId;
+
+        let app_state = create_test_app_state().await;
+        let config = SelfRoleConfig::create(
+            &app_state.db,
+            "12345",
+            "67890",
+            "DM panel",
+            "pick one",
+            "radio",
+        )
+        .await
+        .unwrap();
+
+        // a DM click has no guild on the interaction, so the panel's guild is used
+        assert_eq!(selfrole_guild_id(None, &config), Some(12345));
+        // in a server the interaction's guild wins
+        assert_eq!(
+            selfrole_guild_id(Some(GuildId::new(54321)), &config),
+            Some(54321)
+        );
+
+        let broken = SelfRoleConfig {
+            guild_id: "not-a-guild".to_string(),
+            ..config
+        };
+        assert_eq!(selfrole_guild_id(None, &broken), None);
+    }
+
     // Helper function to test custom ID parsing
     fn parse_selfrole_custom_id(custom_id: &str) -> Option<(i64, String)> {
         if let Some(suffix) = custom_id.strip_prefix("selfrole_") {
@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use crate::tests::create_test_db;
-    use clouder_core::database::mediaonly::MediaOnlyConfig;
+    use crate::tests::{create_test_app_state, create_test_db};
+    use chrono::NaiveDate;
+    use clouder_core::database::mediaonly::{
+        MediaOnlyChannelStats, MediaOnlyConfig, MediaOnlyStats,
+    };
 
     #[tokio::test]
     async fn test_get_by_channel_not_found() {
@@ -196,4 +199,201 @@ mod tests {
         assert_eq!(configs.len(), 1);
         assert_eq!(configs[0].channel_id, "channel2");
     }
+
+    #[tokio::test]
+    async fn test_stats_record_and_aggregate() {
+        let db = create_test_db().await;
+        let day = |d: u32| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+
+        for (channel, user, d) in [
+            ("channel1", "u1", 10),
+            ("channel1", "u1", 10),
+            ("channel1", "u2", 11),
+            ("channel1", "u1", 1),
+            ("channel2", "u3", 12),
+        ] {
+            MediaOnlyStats::record_deletion(&db, "guild1", channel, user, day(d))
+                .await
+                .unwrap();
+        }
+        MediaOnlyStats::record_deletion(&db, "guild2", "channel9", "u1", day(10))
+            .await
+            .unwrap();
+
+        // same user and day is one row with a bumped counter
+        let rows: i64 = sqlx::query_scalar(
+            "SELECT deletions FROM mediaonly_stats WHERE channel_id = 'channel1' AND user_id = 'u1' AND day = '2026-03-10'",
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(rows, 2);
+
+        let stats = MediaOnlyStats::get_by_guild(&db, "guild1", day(5))
+            .await
+            .unwrap();
+        assert_eq!(
+            stats,
+            vec![
+                MediaOnlyChannelStats {
+                    channel_id: "channel1".to_string(),
+                    deletions: 3,
+                    unique_offenders: 2,
+                    last_day: "2026-03-11".to_string(),
+                },
+                MediaOnlyChannelStats {
+                    channel_id: "channel2".to_string(),
+                    deletions: 1,
+                    unique_offenders: 1,
+                    last_day: "2026-03-12".to_string(),
+                },
+            ]
+        );
+
+        assert_eq!(MediaOnlyStats::purge_before(&db, day(5)).await.unwrap(), 1);
+        let all = MediaOnlyStats::get_by_guild(&db, "guild1", day(1))
+            .await
+            .unwrap();
+        assert_eq!(all[0].deletions, 3);
+    }
+
+    #[tokio::test]
+    async fn test_mediaonly_channel_cap_per_guild() {
+        let app_state = create_test_app_state().await;
+        let guild_id = 42u64;
+        let max = app_state.config.mediaonly_max_channels;
+        let owner = app_state.config.discord.bot_owner;
+        let payload = serde_json::json!({});
+
+        for i in 0..max {
+            MediaOnlyConfig::upsert(&app_state.db, "42", &format!("c{}", i), true)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            MediaOnlyConfig::count_by_guild(&app_state.db, "42")
+                .await
+                .unwrap(),
+            max
+        );
+
+        let err = clouder_core::shared::create_or_update_mediaonly_config(
+            &app_state, guild_id, 1, "extra", &payload,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.contains("media-only channels per server"), "{}", err);
+
+        // already-configured channels can still be updated
+        clouder_core::shared::create_or_update_mediaonly_config(
+            &app_state, guild_id, 1, "c0", &payload,
+        )
+        .await
+        .unwrap();
+
+        // other guilds have their own budget
+        clouder_core::shared::create_or_update_mediaonly_config(
+            &app_state, 43, 1, "extra", &payload,
+        )
+        .await
+        .unwrap();
+
+        // the bot owner is exempt
+        clouder_core::shared::create_or_update_mediaonly_config(
+            &app_state, guild_id, owner, "extra", &payload,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            MediaOnlyConfig::count_by_guild(&app_state.db, "42")
+                .await
+                .unwrap(),
+            max + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bulk_configure_skips_invalid_channels() {
+        use clouder_core::shared::apply_mediaonly_bulk;
+        use serde_json::json;
+
+        let app_state = create_test_app_state().await;
+        let guild_channels = vec!["100".to_string(), "200".to_string()];
+        let payload = json!({
+            "channels": [
+                { "channel_id": "100", "allow_links": false },
+                { "channel_id": "999" },
+                { "channel_id": "not-a-channel" },
+                { "channel_id": "100" },
+                { "channel_id": "200", "allow_gifs": false },
+            ]
+        });
+
+        let result = apply_mediaonly_bulk(&app_state, 1, 0, &payload, &guild_channels)
+            .await
+            .unwrap();
+        assert_eq!(result["configured"], 2);
+        let errors: Vec<&str> = result["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["error"].as_str().unwrap_or("ok"))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "ok",
+                "that channel isn't in this server",
+                "invalid channel id",
+                "channel listed more than once",
+                "ok",
+            ]
+        );
+
+        let saved = MediaOnlyConfig::get_by_guild(&app_state.db, "1")
+            .await
+            .unwrap();
+        assert_eq!(saved.len(), 2);
+        let first = saved.iter().find(|c| c.channel_id == "100").unwrap();
+        assert!(!first.allow_links && first.allow_gifs);
+        let second = saved.iter().find(|c| c.channel_id == "200").unwrap();
+        assert!(second.allow_links && !second.allow_gifs);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_configure_respects_channel_cap() {
+        use clouder_core::config::{AppState, Config};
+        use clouder_core::shared::apply_mediaonly_bulk;
+        use serde_json::json;
+        use std::sync::Arc;
+
+        let base = create_test_app_state().await;
+        let mut config = Config::test_config();
+        config.mediaonly_max_channels = 2;
+        let app_state = AppState::new(Arc::new(config), base.db.clone(), base.http.clone());
+        MediaOnlyConfig::upsert(&app_state.db, "1", "100", true)
+            .await
+            .unwrap();
+        let guild_channels: Vec<String> = ["100", "200", "300"].map(String::from).to_vec();
+        let payload = json!({
+            "channels": [{ "channel_id": "100" }, { "channel_id": "200" }, { "channel_id": "300" }]
+        });
+
+        let result = apply_mediaonly_bulk(&app_state, 1, 0, &payload, &guild_channels)
+            .await
+            .unwrap();
+        // 100 was already configured, so only 300 goes over the cap
+        assert_eq!(result["configured"], 2);
+        assert_eq!(
+            result["results"][2]["error"],
+            "maximum of 2 media-only channels per server reached"
+        );
+
+        // the bot owner is exempt
+        let owner = app_state.config.discord.bot_owner;
+        let result = apply_mediaonly_bulk(&app_state, 1, owner, &payload, &guild_channels)
+            .await
+            .unwrap();
+        assert_eq!(result["configured"], 3);
+    }
 }
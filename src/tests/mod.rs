@@ -1,7 +1,9 @@
 pub mod about_tests;
+mod alias_tests;
 mod channel_tests;
 pub mod commands_tests;
 pub mod config_tests;
+mod data_export_tests;
 pub mod database_tests;
 pub mod events_tests;
 mod github_tests;
@@ -10,6 +12,7 @@ mod huggingface_tests;
 mod mediaonly_tests;
 mod purge_tests;
 mod reminders_tests;
+mod session_tests;
 mod shared_tests;
 pub mod utils_tests;
 mod uwufy_tests;
@@ -35,7 +38,10 @@ pub async fn create_test_db() -> SqlitePool {
             body TEXT NOT NULL,
             selection_type TEXT NOT NULL CHECK(selection_type IN ('radio', 'multiple')),
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            attached BOOLEAN NOT NULL DEFAULT FALSE,
+            archived_at DATETIME,
+            cooldown_secs INTEGER NOT NULL DEFAULT 5
         );
     "#,
     )
@@ -285,6 +291,243 @@ pub async fn create_test_db() -> SqlitePool {
     .await
     .unwrap();
 
+    sqlx::query(
+        r#"
+        CREATE TABLE scheduled_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guild_id TEXT NOT NULL,
+            channel_id TEXT NOT NULL,
+            content TEXT NOT NULL,
+            cron_spec TEXT,
+            timezone TEXT NOT NULL DEFAULT 'UTC',
+            next_run_at INTEGER,
+            enabled BOOLEAN NOT NULL DEFAULT TRUE,
+            created_by TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE TABLE mediaonly_stats (
+            guild_id TEXT NOT NULL,
+            channel_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            day TEXT NOT NULL,
+            deletions INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (guild_id, channel_id, day, user_id)
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE TABLE guild_ai_settings (
+            guild_id TEXT PRIMARY KEY,
+            allowed_channel_ids TEXT NOT NULL DEFAULT '',
+            channel_cooldown_secs INTEGER NOT NULL DEFAULT 0,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE TABLE pending_welcomes (
+            guild_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            joined_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (guild_id, user_id)
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE TABLE ai_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guild_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            prompt_tokens INTEGER NOT NULL DEFAULT 0,
+            completion_tokens INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE TABLE command_aliases (
+            guild_id TEXT NOT NULL,
+            alias TEXT NOT NULL,
+            command TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (guild_id, alias)
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE TABLE selfrole_emoji_policies (
+            guild_id TEXT PRIMARY KEY,
+            mode TEXT NOT NULL CHECK (mode IN ('allow', 'deny')),
+            emojis TEXT NOT NULL DEFAULT '',
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE TABLE selfrole_settings (
+            guild_id TEXT PRIMARY KEY,
+            show_updated BOOLEAN NOT NULL DEFAULT FALSE,
+            show_cooldown BOOLEAN NOT NULL DEFAULT FALSE,
+            feedback_mode TEXT NOT NULL DEFAULT 'reply',
+            crosspost BOOLEAN NOT NULL DEFAULT FALSE,
+            default_cooldown_secs INTEGER NOT NULL DEFAULT 5,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE TABLE selfrole_labels (
+            guild_id TEXT NOT NULL,
+            role_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (guild_id, role_id)
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // mirror migrations 003_welcome_goodbye.sql, 020_welcome_rotation.sql,
+    // 022_welcome_after_screening.sql and 023_name_placeholder_source.sql
+    sqlx::query(
+        r#"
+        CREATE TABLE welcome_goodbye_configs (
+            guild_id TEXT PRIMARY KEY,
+            welcome_enabled BOOLEAN DEFAULT FALSE,
+            goodbye_enabled BOOLEAN DEFAULT FALSE,
+            welcome_channel_id TEXT,
+            goodbye_channel_id TEXT,
+            welcome_message_type TEXT DEFAULT 'embed',
+            goodbye_message_type TEXT DEFAULT 'embed',
+            welcome_message_content TEXT,
+            goodbye_message_content TEXT,
+            welcome_embed_title TEXT,
+            welcome_embed_description TEXT,
+            welcome_embed_color INTEGER,
+            welcome_embed_footer TEXT,
+            welcome_embed_thumbnail TEXT,
+            welcome_embed_image TEXT,
+            welcome_embed_timestamp BOOLEAN DEFAULT FALSE,
+            goodbye_embed_title TEXT,
+            goodbye_embed_description TEXT,
+            goodbye_embed_color INTEGER,
+            goodbye_embed_footer TEXT,
+            goodbye_embed_thumbnail TEXT,
+            goodbye_embed_image TEXT,
+            goodbye_embed_timestamp BOOLEAN DEFAULT FALSE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            welcome_rotation TEXT NOT NULL DEFAULT 'off',
+            goodbye_rotation TEXT NOT NULL DEFAULT 'off',
+            welcome_rotation_counter INTEGER NOT NULL DEFAULT 0,
+            goodbye_rotation_counter INTEGER NOT NULL DEFAULT 0,
+            welcome_after_screening BOOLEAN NOT NULL DEFAULT FALSE,
+            name_placeholder_source TEXT NOT NULL DEFAULT 'username'
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE TABLE welcome_goodbye_variants (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guild_id TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK (kind IN ('welcome', 'goodbye')),
+            content TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // dashboard tables (final shape after migrations 010-013)
+    sqlx::query(
+        r#"
+        CREATE TABLE dashboard_users (
+            user_id TEXT PRIMARY KEY NOT NULL,
+            api_key_hash TEXT,
+            api_key_ciphertext TEXT,
+            oauth_token TEXT,
+            oauth_token_updated_at INTEGER,
+            username TEXT,
+            avatar TEXT,
+            created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+            updated_at INTEGER NOT NULL DEFAULT (unixepoch())
+        );
+        CREATE TABLE dashboard_sessions (
+            session_id TEXT PRIMARY KEY NOT NULL,
+            user_id TEXT NOT NULL,
+            csrf_token TEXT NOT NULL,
+            expires_at INTEGER NOT NULL,
+            created_at INTEGER NOT NULL DEFAULT (unixepoch())
+        );
+        CREATE TABLE user_guild_cache (
+            user_id TEXT NOT NULL,
+            guild_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            icon TEXT,
+            updated_at INTEGER NOT NULL DEFAULT (unixepoch()),
+            permissions INTEGER NOT NULL DEFAULT 0,
+            expires_at INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (user_id, guild_id)
+        );
+    "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
     pool
 }
 
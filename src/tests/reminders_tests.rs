@@ -389,4 +389,141 @@ mod tests {
         assert!(schedule_days_match("3", 3));
         assert!(!schedule_days_match("3", 4));
     }
+
+    #[test]
+    fn test_cron_next_run_in_timezone() {
+        use chrono::{TimeZone, Utc};
+        use clouder_core::utils::cron::CronSpec;
+
+        let tz: chrono_tz::Tz = "Europe/Berlin".parse().unwrap();
+        // weekdays at 09:00 local
+        let spec = CronSpec::parse("0 9 * * 1-5").unwrap();
+
+        // friday 2024-01-05 10:00 UTC (11:00 berlin) -> monday 08:00 UTC
+        let after = Utc.with_ymd_and_hms(2024, 1, 5, 10, 0, 0).unwrap();
+        assert_eq!(
+            spec.next_after(after, tz),
+            Some(Utc.with_ymd_and_hms(2024, 1, 8, 8, 0, 0).unwrap())
+        );
+
+        // summer time shifts the UTC hour: 2024-07-01 is a monday
+        let after = Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            spec.next_after(after, tz),
+            Some(Utc.with_ymd_and_hms(2024, 7, 1, 7, 0, 0).unwrap())
+        );
+
+        // strictly after: a run exactly at `after` is skipped
+        let every_15 = CronSpec::parse("*/15 * * * *").unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 1, 1, 12, 15, 0).unwrap();
+        assert_eq!(
+            every_15.next_after(after, chrono_tz::UTC),
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 12, 30, 0).unwrap())
+        );
+
+        // leap day only
+        let leap = CronSpec::parse("0 0 29 2 *").unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            leap.next_after(after, chrono_tz::UTC),
+            Some(Utc.with_ymd_and_hms(2028, 2, 29, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_cron_skips_dst_gap() {
+        use chrono::{TimeZone, Utc};
+        use clouder_core::utils::cron::CronSpec;
+
+        // 02:30 doesn't exist in berlin on 2024-03-31; next is 2024-04-01 02:30 CEST
+        let tz: chrono_tz::Tz = "Europe/Berlin".parse().unwrap();
+        let spec = CronSpec::parse("30 2 * * *").unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 3, 30, 12, 0, 0).unwrap();
+        assert_eq!(
+            spec.next_after(after, tz),
+            Some(Utc.with_ymd_and_hms(2024, 4, 1, 0, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_cron_parse_rejects_invalid() {
+        use clouder_core::utils::cron::CronSpec;
+
+        assert!(CronSpec::parse("* * * *").is_err());
+        assert!(CronSpec::parse("60 * * * *").is_err());
+        assert!(CronSpec::parse("* 24 * * *").is_err());
+        assert!(CronSpec::parse("* * 0 * *").is_err());
+        assert!(CronSpec::parse("*/0 * * * *").is_err());
+        assert!(CronSpec::parse("5-1 * * * *").is_err());
+        assert!(CronSpec::parse("0 9 * * 7").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_messages_due_and_advance() {
+        use chrono::{TimeZone, Utc};
+        use clouder::scheduler::next_scheduled_run;
+        use clouder_core::database::scheduled_messages::ScheduledMessage;
+
+        let db = create_test_db().await;
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+
+        let once = ScheduledMessage::create(
+            &db,
+            "g1",
+            "c1",
+            "hello",
+            None,
+            "UTC",
+            now.timestamp() - 60,
+            "u1",
+        )
+        .await
+        .unwrap();
+        ScheduledMessage::create(
+            &db,
+            "g1",
+            "c1",
+            "later",
+            None,
+            "UTC",
+            now.timestamp() + 3600,
+            "u1",
+        )
+        .await
+        .unwrap();
+
+        let due = ScheduledMessage::get_due(&db, now.timestamp())
+            .await
+            .unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, once);
+
+        // one-offs have no next run and get disabled
+        assert_eq!(next_scheduled_run(&due[0], now), None);
+        ScheduledMessage::set_next_run(&db, once, None)
+            .await
+            .unwrap();
+        let fired = ScheduledMessage::get_by_id(&db, once)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!fired.enabled);
+        assert!(
+            ScheduledMessage::get_due(&db, now.timestamp())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut recurring = fired.clone();
+        recurring.cron_spec = Some("0 * * * *".to_string());
+        assert_eq!(
+            next_scheduled_run(&recurring, now),
+            Some(
+                Utc.with_ymd_and_hms(2024, 1, 1, 13, 0, 0)
+                    .unwrap()
+                    .timestamp()
+            )
+        );
+    }
 }
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::create_test_app_state;
+    use clouder_core::AppState;
+    use clouder_core::DashboardUser;
+    use clouder_core::database::dashboard_sessions::DashboardSession;
+    use clouder_core::shared::end_dashboard_session;
+    use sqlx::SqlitePool;
+
+    async fn seed_user(app_state: &AppState, user_id: &str) {
+        let db: &SqlitePool = &app_state.db;
+        sqlx::query("INSERT INTO dashboard_users (user_id, api_key_hash) VALUES (?, 'hash')")
+            .bind(user_id)
+            .execute(db)
+            .await
+            .unwrap();
+        let key = app_state.config.web.oauth_encryption_key_bytes;
+        let encrypted = clouder_core::crypto::encrypt(&key, b"discord-token").unwrap();
+        DashboardUser::store_oauth_token(db, user_id, &encrypted)
+            .await
+            .unwrap();
+    }
+
+    async fn stored_token(db: &SqlitePool, user_id: &str) -> Option<String> {
+        DashboardUser::get_by_user_id(db, user_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .oauth_token
+    }
+
+    #[tokio::test]
+    async fn test_logout_ends_session_whether_or_not_revoke_succeeds() {
+        let app_state = create_test_app_state().await;
+        let db: &SqlitePool = &app_state.db;
+
+        for (user_id, revoke_result) in [("40", Ok(())), ("41", Err("discord is down".to_string()))]
+        {
+            seed_user(&app_state, user_id).await;
+            let session = DashboardSession::create(db, user_id, 3600).await.unwrap();
+
+            let mut revoked = None;
+            end_dashboard_session(&app_state, &session.session_id, user_id, |token| {
+                revoked = Some(token);
+                async { revoke_result }
+            })
+            .await;
+
+            assert_eq!(revoked.as_deref(), Some("discord-token"));
+            assert!(
+                DashboardSession::get_active(db, &session.session_id)
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+            assert!(stored_token(db, user_id).await.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logout_keeps_the_grant_while_other_sessions_remain() {
+        let app_state = create_test_app_state().await;
+        let db: &SqlitePool = &app_state.db;
+        seed_user(&app_state, "42").await;
+        let laptop = DashboardSession::create(db, "42", 3600).await.unwrap();
+        let phone = DashboardSession::create(db, "42", 3600).await.unwrap();
+
+        let mut revoked = false;
+        end_dashboard_session(&app_state, &laptop.session_id, "42", |_| {
+            revoked = true;
+            async { Ok(()) }
+        })
+        .await;
+
+        assert!(!revoked);
+        assert!(stored_token(db, "42").await.is_some());
+        assert!(
+            DashboardSession::get_active(db, &laptop.session_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            DashboardSession::get_active(db, &phone.session_id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}
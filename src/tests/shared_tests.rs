@@ -2,8 +2,17 @@
 mod tests {
     use crate::tests::create_test_app_state;
     use clouder_core::shared::{
-        create_custom_reminder, update_custom_reminder, upsert_reminder_config,
+        BUTTON_LABEL_MAX_CHARS, archive_guild_selfroles, attach_selfrole, channel_guild_problem,
+        create_custom_reminder, describe_discord_error, discord_error_hint,
+        filter_selfrole_configs, format_selfrole_button_label, get_selfrole_settings,
+        list_selfrole_templates, list_selfroles, move_selfrole_channel,
+        patch_welcome_goodbye_config, restore_guild_selfroles, scheduled_channel_problem,
+        selfrole_attach_problem, selfrole_channel_problem, selfrole_cooldown_for,
+        selfrole_emoji_problem, selfrole_footer_text, selfrole_role_entry,
+        should_crosspost_selfrole, update_custom_reminder, update_selfrole_settings,
+        upsert_reminder_config, validate_message_config,
     };
+    use poise::serenity_prelude as serenity;
     use serde_json::json;
 
     #[tokio::test]
@@ -67,4 +76,853 @@ mod tests {
 
         assert_eq!(error, "embed_description exceeds 4096 characters");
     }
+
+    #[test]
+    fn test_discord_error_hint_maps_common_failures() {
+        let missing_perms = discord_error_hint(403, 50013).unwrap();
+        assert!(missing_perms.starts_with("missing permissions"));
+        assert!(
+            discord_error_hint(403, 50001)
+                .unwrap()
+                .starts_with("missing access")
+        );
+        assert!(
+            discord_error_hint(404, 10003)
+                .unwrap()
+                .starts_with("unknown channel")
+        );
+        // unlisted codes fall back to the status
+        assert!(discord_error_hint(429, 0).unwrap().contains("rate limited"));
+        assert!(discord_error_hint(502, 0).unwrap().contains("try again"));
+        assert_eq!(discord_error_hint(400, 12345), None);
+    }
+
+    #[test]
+    fn test_describe_discord_error_passes_through_other_errors() {
+        let error = serenity::Error::Other("boom");
+        assert_eq!(describe_discord_error(&error), "boom");
+    }
+
+    #[test]
+    fn test_selfrole_channel_problem() {
+        use serenity::{ChannelType, GuildId, Permissions};
+
+        let guild = GuildId::new(1);
+        let usable =
+            Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS;
+
+        assert_eq!(
+            selfrole_channel_problem(ChannelType::Text, guild, guild, usable),
+            None
+        );
+        assert_eq!(
+            selfrole_channel_problem(ChannelType::News, guild, guild, Permissions::ADMINISTRATOR),
+            None
+        );
+        assert!(
+            selfrole_channel_problem(ChannelType::Voice, guild, guild, usable)
+                .unwrap()
+                .contains("text or announcement")
+        );
+        assert!(
+            selfrole_channel_problem(ChannelType::Text, GuildId::new(2), guild, usable)
+                .unwrap()
+                .contains("isn't in this server")
+        );
+
+        let problem = selfrole_channel_problem(
+            ChannelType::Text,
+            guild,
+            guild,
+            Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES,
+        )
+        .unwrap();
+        assert!(problem.starts_with("missing permissions"), "{}", problem);
+        assert!(problem.contains("embed links"), "{}", problem);
+        assert!(!problem.contains("send messages"), "{}", problem);
+    }
+
+    #[test]
+    fn test_scheduled_channel_problem() {
+        use serenity::{ChannelType, GuildId, Permissions};
+
+        let guild = GuildId::new(1);
+        let usable = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+
+        assert_eq!(
+            scheduled_channel_problem(ChannelType::Text, guild, guild, usable),
+            None
+        );
+        assert_eq!(
+            scheduled_channel_problem(ChannelType::News, guild, guild, Permissions::ADMINISTRATOR),
+            None
+        );
+        // a channel from another server the bot happens to see
+        assert!(
+            scheduled_channel_problem(ChannelType::Text, GuildId::new(2), guild, usable)
+                .unwrap()
+                .contains("isn't in this server")
+        );
+        assert!(
+            scheduled_channel_problem(ChannelType::Category, guild, guild, usable)
+                .unwrap()
+                .contains("text or announcement")
+        );
+
+        let problem =
+            scheduled_channel_problem(ChannelType::Text, guild, guild, Permissions::VIEW_CHANNEL)
+                .unwrap();
+        assert!(problem.starts_with("missing permissions"), "{}", problem);
+        assert!(problem.contains("send messages"), "{}", problem);
+    }
+
+    #[test]
+    fn test_selfrole_attach_problem() {
+        use serenity::UserId;
+
+        let bot = UserId::new(1);
+        assert_eq!(selfrole_attach_problem(bot, bot, false), None);
+        assert!(
+            selfrole_attach_problem(UserId::new(2), bot, false)
+                .unwrap()
+                .contains("own messages")
+        );
+        assert!(
+            selfrole_attach_problem(bot, bot, true)
+                .unwrap()
+                .contains("already has a self-role panel")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_attach_selfrole_validates_payload() {
+        let app_state = create_test_app_state().await;
+        let roles = json!([{ "role_id": "10", "emoji": "" }]);
+
+        let missing = json!({ "title": "roles", "channel_id": "1", "roles": roles });
+        assert_eq!(
+            attach_selfrole(&app_state, 123, &missing)
+                .await
+                .unwrap_err(),
+            "Message ID is required"
+        );
+
+        let invalid =
+            json!({ "title": "roles", "channel_id": "1", "message_id": "abc", "roles": roles });
+        assert_eq!(
+            attach_selfrole(&app_state, 123, &invalid)
+                .await
+                .unwrap_err(),
+            "Invalid message ID"
+        );
+
+        let no_roles =
+            json!({ "title": "roles", "channel_id": "1", "message_id": "5", "roles": [] });
+        assert_eq!(
+            attach_selfrole(&app_state, 123, &no_roles)
+                .await
+                .unwrap_err(),
+            "Must have 1-25 roles"
+        );
+    }
+
+    #[test]
+    fn test_selfrole_emoji_problem() {
+        let guild_emojis = std::collections::HashSet::from([111u64]);
+
+        for ok in ["", "  ", "🎮", "1️⃣", "🏳️‍🌈", "<:cat:111>", "<a:dance:111>"] {
+            assert_eq!(selfrole_emoji_problem(ok, &guild_emojis), None, "{}", ok);
+        }
+
+        assert!(
+            selfrole_emoji_problem("<:cat:222>", &guild_emojis)
+                .unwrap()
+                .contains("isn't from this server")
+        );
+        assert!(
+            selfrole_emoji_problem("<:cat:nope>", &guild_emojis)
+                .unwrap()
+                .contains("isn't a valid custom emoji")
+        );
+        for bad in [":smile:", "abc", "🎮 🎮"] {
+            assert!(
+                selfrole_emoji_problem(bad, &guild_emojis)
+                    .unwrap()
+                    .contains("isn't an emoji"),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_selfrole_templates_are_valid() {
+        let result = list_selfrole_templates().unwrap();
+        assert_eq!(result["success"], true);
+        let templates = result["templates"].as_array().unwrap();
+        assert!(!templates.is_empty());
+
+        let mut ids = std::collections::HashSet::new();
+        for template in templates {
+            let id = template["id"].as_str().unwrap();
+            assert!(ids.insert(id), "duplicate template id {}", id);
+            assert!(!template["name"].as_str().unwrap().is_empty(), "{}", id);
+
+            let title = template["title"].as_str().unwrap();
+            assert!(!title.trim().is_empty() && title.len() <= 256, "{}", id);
+            assert!(template["description"].as_str().unwrap().len() <= 4096);
+            assert!(
+                matches!(
+                    template["selection_type"].as_str().unwrap(),
+                    "radio" | "multiple"
+                ),
+                "{}",
+                id
+            );
+
+            let roles = template["roles"].as_array().unwrap();
+            assert!((1..=25).contains(&roles.len()), "{}", id);
+            for role in roles {
+                let emoji = role["emoji"].as_str().unwrap();
+                assert_eq!(
+                    selfrole_emoji_problem(emoji, &Default::default()),
+                    None,
+                    "{}",
+                    id
+                );
+                let label = role["label"].as_str().unwrap();
+                assert!(!label.is_empty() && label.len() <= 80, "{}", id);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_rejects_duplicate_roles() {
+        let app_state = create_test_app_state().await;
+        let payload = json!({
+            "title": "roles",
+            "channel_id": "1",
+            "message_id": "5",
+            "roles": [
+                { "role_id": "10", "emoji": "🔴" },
+                { "role_id": "11", "emoji": "🔴" },
+                { "role_id": "10", "emoji": "🔵" },
+            ],
+        });
+
+        assert_eq!(
+            attach_selfrole(&app_state, 123, &payload)
+                .await
+                .unwrap_err(),
+            "role 10 is listed more than once"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_selfrole_channel_validates_before_redeploy() {
+        use clouder_core::database::selfroles::SelfRoleConfig;
+
+        let app_state = create_test_app_state().await;
+        let mut config =
+            SelfRoleConfig::create(&app_state.db, "123", "100", "roles", "", "multiple")
+                .await
+                .unwrap();
+        let move_to = |channel: &str| json!({ "channel_id": channel });
+
+        assert_eq!(
+            move_selfrole_channel(&app_state, 123, config.id, &json!({}))
+                .await
+                .unwrap_err(),
+            "Channel ID is required"
+        );
+        assert_eq!(
+            move_selfrole_channel(&app_state, 123, config.id, &move_to("abc"))
+                .await
+                .unwrap_err(),
+            "Invalid channel ID"
+        );
+        assert_eq!(
+            move_selfrole_channel(&app_state, 999, config.id, &move_to("200"))
+                .await
+                .unwrap_err(),
+            "Configuration not found"
+        );
+        assert_eq!(
+            move_selfrole_channel(&app_state, 123, config.id, &move_to("100"))
+                .await
+                .unwrap_err(),
+            "the panel is already in that channel"
+        );
+
+        config.mark_attached(&app_state.db, "5").await.unwrap();
+        assert_eq!(
+            move_selfrole_channel(&app_state, 123, config.id, &move_to("200"))
+                .await
+                .unwrap_err(),
+            "attached panels can't move to another channel"
+        );
+    }
+
+    #[test]
+    fn test_selfrole_footer_text() {
+        assert_eq!(
+            selfrole_footer_text("multiple", false, None),
+            "Multiple roles"
+        );
+        assert_eq!(selfrole_footer_text("radio", false, None), "Single role");
+        assert_eq!(
+            selfrole_footer_text("multiple", true, None),
+            "Multiple roles · last updated"
+        );
+        assert_eq!(
+            selfrole_footer_text("radio", true, None),
+            "Single role · last updated"
+        );
+        assert_eq!(selfrole_footer_text("other", true, None), "last updated");
+        // well within discord's 2048-char footer limit
+        assert!(selfrole_footer_text("multiple", true, None).len() < 2048);
+    }
+
+    #[test]
+    fn test_selfrole_footer_cooldown() {
+        assert_eq!(
+            selfrole_footer_text("multiple", false, Some(30)),
+            "Multiple roles · Cooldown: 30s"
+        );
+        assert_eq!(
+            selfrole_footer_text("radio", true, Some(90)),
+            "Single role · Cooldown: 1m 30s · last updated"
+        );
+        assert_eq!(
+            selfrole_footer_text("other", false, Some(5)),
+            "Cooldown: 5s"
+        );
+        // no cooldown set, nothing to show
+        assert_eq!(selfrole_footer_text("radio", false, Some(0)), "Single role");
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_settings_roundtrip() {
+        let app_state = create_test_app_state().await;
+
+        let on = update_selfrole_settings(&app_state, 123, &json!({ "show_updated": true }))
+            .await
+            .unwrap();
+        assert_eq!(on["show_updated"], true);
+
+        // omitted fields keep their value
+        let kept = update_selfrole_settings(&app_state, 123, &json!({ "show_cooldown": true }))
+            .await
+            .unwrap();
+        assert_eq!(kept["show_updated"], true);
+        assert_eq!(kept["show_cooldown"], true);
+
+        assert_eq!(
+            update_selfrole_settings(&app_state, 123, &json!({ "show_updated": "yes" }))
+                .await
+                .unwrap_err(),
+            "show_updated must be a boolean"
+        );
+    }
+
+    #[test]
+    fn test_selfrole_crosspost_needs_an_announcement_channel() {
+        use serenity::{ChannelType, Permissions};
+
+        let send = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+        assert!(should_crosspost_selfrole(true, ChannelType::News, send));
+        assert!(should_crosspost_selfrole(
+            true,
+            ChannelType::News,
+            Permissions::ADMINISTRATOR
+        ));
+
+        // opted out, or not an announcement channel: leave the message alone
+        assert!(!should_crosspost_selfrole(false, ChannelType::News, send));
+        for kind in [ChannelType::Text, ChannelType::Voice, ChannelType::Forum] {
+            assert!(!should_crosspost_selfrole(true, kind, send));
+        }
+        assert!(!should_crosspost_selfrole(
+            true,
+            ChannelType::News,
+            Permissions::VIEW_CHANNEL
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_crosspost_setting_roundtrip() {
+        let app_state = create_test_app_state().await;
+        let settings = get_selfrole_settings(&app_state, 123).await.unwrap();
+        assert_eq!(settings["crosspost"], false);
+
+        let saved = update_selfrole_settings(&app_state, 123, &json!({ "crosspost": true }))
+            .await
+            .unwrap();
+        assert_eq!(saved["crosspost"], true);
+        assert_eq!(
+            update_selfrole_settings(&app_state, 123, &json!({ "crosspost": 1 }))
+                .await
+                .unwrap_err(),
+            "crosspost must be a boolean"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_new_panel_cooldown_uses_guild_default() {
+        let app_state = create_test_app_state().await;
+        assert_eq!(
+            selfrole_cooldown_for(&app_state, "123", None)
+                .await
+                .unwrap(),
+            5
+        );
+
+        let saved =
+            update_selfrole_settings(&app_state, 123, &json!({ "default_cooldown_secs": 30 }))
+                .await
+                .unwrap();
+        assert_eq!(saved["default_cooldown_secs"], 30);
+        assert_eq!(
+            selfrole_cooldown_for(&app_state, "123", None)
+                .await
+                .unwrap(),
+            30
+        );
+        // an explicit cooldown on the panel wins, including none at all
+        assert_eq!(
+            selfrole_cooldown_for(&app_state, "123", Some(0))
+                .await
+                .unwrap(),
+            0
+        );
+
+        for bad in [json!(-1), json!(3601), json!("10")] {
+            assert_eq!(
+                update_selfrole_settings(&app_state, 123, &json!({ "default_cooldown_secs": bad }))
+                    .await
+                    .unwrap_err(),
+                "default_cooldown_secs must be between 0 and 3600 seconds"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_selfrole_feedback_mode_from_settings() {
+        use clouder_core::database::selfroles::{SelfRoleFeedback, SelfRoleSettings};
+
+        let app_state = create_test_app_state().await;
+        let feedback = || async {
+            SelfRoleSettings::get_or_default(&app_state.db, "123")
+                .await
+                .unwrap()
+                .feedback()
+        };
+        assert_eq!(feedback().await, SelfRoleFeedback::Reply);
+
+        let saved = update_selfrole_settings(&app_state, 123, &json!({ "feedback_mode": "react" }))
+            .await
+            .unwrap();
+        assert_eq!(saved["feedback_mode"], "react");
+        assert_eq!(feedback().await, SelfRoleFeedback::React);
+
+        assert_eq!(
+            update_selfrole_settings(&app_state, 123, &json!({ "feedback_mode": "wave" }))
+                .await
+                .unwrap_err(),
+            "feedback_mode must be reply or react"
+        );
+        assert_eq!(feedback().await, SelfRoleFeedback::React);
+
+        // a value the code doesn't know falls back to replying
+        let unknown = SelfRoleSettings {
+            feedback_mode: "confetti".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(unknown.feedback(), SelfRoleFeedback::Reply);
+    }
+
+    #[tokio::test]
+    async fn test_welcome_goodbye_patch_preserves_untouched_fields() {
+        let app_state = create_test_app_state().await;
+        // seeded directly: a channel id in the payload would be checked against discord
+        clouder_core::database::welcome_goodbye::WelcomeGoodbyeConfig::upsert_config(
+            &app_state.db,
+            &clouder_core::database::welcome_goodbye::WelcomeGoodbyeConfig {
+                guild_id: "123".to_string(),
+                welcome_channel_id: Some("555".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        patch_welcome_goodbye_config(
+            &app_state,
+            123,
+            &json!({
+                "welcome_enabled": true,
+                "welcome_message_type": "text",
+                "welcome_message_content": "hi {user}",
+                "goodbye_embed_title": "bye",
+                "welcome_variants": ["hey", "hello"]
+            }),
+        )
+        .await
+        .unwrap();
+
+        let patched =
+            patch_welcome_goodbye_config(&app_state, 123, &json!({ "goodbye_enabled": true }))
+                .await
+                .unwrap();
+        let config = &patched["config"];
+        assert_eq!(config["goodbye_enabled"], true);
+        assert_eq!(config["welcome_enabled"], true);
+        assert_eq!(config["welcome_channel_id"], "555");
+        assert_eq!(config["welcome_message_content"], "hi {user}");
+        assert_eq!(config["goodbye_embed_title"], "bye");
+
+        let variants = clouder_core::database::welcome_goodbye::WelcomeGoodbyeConfig::get_variants(
+            &app_state.db,
+            "123",
+            clouder_core::database::welcome_goodbye::MemberMessageKind::Welcome,
+        )
+        .await
+        .unwrap();
+        assert_eq!(variants, vec!["hey", "hello"]);
+
+        // a rejected patch leaves the stored config alone
+        assert!(
+            patch_welcome_goodbye_config(
+                &app_state,
+                123,
+                &json!({ "welcome_enabled": false, "welcome_rotation": "sometimes" }),
+            )
+            .await
+            .is_err()
+        );
+        let kept = patch_welcome_goodbye_config(&app_state, 123, &json!({}))
+            .await
+            .unwrap();
+        assert_eq!(kept["config"]["welcome_enabled"], true);
+    }
+
+    #[test]
+    fn test_validate_message_config_names_the_field() {
+        use clouder_core::database::welcome_goodbye::WelcomeGoodbyeConfig;
+
+        let base = WelcomeGoodbyeConfig::default();
+        assert!(validate_message_config(&base).is_ok());
+
+        let cases = [
+            (
+                WelcomeGoodbyeConfig {
+                    welcome_embed_title: Some("t".repeat(257)),
+                    ..base.clone()
+                },
+                "welcome_embed_title exceeds 256 characters",
+            ),
+            (
+                WelcomeGoodbyeConfig {
+                    goodbye_embed_description: Some("d".repeat(4097)),
+                    ..base.clone()
+                },
+                "goodbye_embed_description exceeds 4096 characters",
+            ),
+            (
+                WelcomeGoodbyeConfig {
+                    welcome_embed_footer: Some("f".repeat(2049)),
+                    ..base.clone()
+                },
+                "welcome_embed_footer exceeds 2048 characters",
+            ),
+            (
+                WelcomeGoodbyeConfig {
+                    goodbye_message_content: Some("c".repeat(2001)),
+                    ..base.clone()
+                },
+                "goodbye_message_content exceeds 2000 characters",
+            ),
+            (
+                WelcomeGoodbyeConfig {
+                    welcome_embed_image: Some("http://example.com/a.png".to_string()),
+                    ..base.clone()
+                },
+                "welcome_embed_image must be an https URL with a public host",
+            ),
+            (
+                WelcomeGoodbyeConfig {
+                    goodbye_enabled: true,
+                    goodbye_embed_title: Some("  ".to_string()),
+                    ..base.clone()
+                },
+                "goodbye_embed_title: an embed needs a title or a description",
+            ),
+        ];
+        for (config, expected) in cases {
+            assert_eq!(validate_message_config(&config).unwrap_err(), expected);
+        }
+
+        // limits count characters, not bytes
+        let emoji_title = WelcomeGoodbyeConfig {
+            welcome_embed_title: Some("🎉".repeat(256)),
+            ..base.clone()
+        };
+        assert!(validate_message_config(&emoji_title).is_ok());
+
+        // an enabled embed with a description, or one fed by variants, is fine
+        let described = WelcomeGoodbyeConfig {
+            welcome_enabled: true,
+            welcome_embed_description: Some("hi {user}".to_string()),
+            ..base.clone()
+        };
+        assert!(validate_message_config(&described).is_ok());
+        let rotating = WelcomeGoodbyeConfig {
+            welcome_enabled: true,
+            welcome_rotation: "random".to_string(),
+            ..base
+        };
+        assert!(validate_message_config(&rotating).is_ok());
+    }
+
+    #[test]
+    fn test_selfrole_button_label_fits_discord_limit() {
+        let short = format_selfrole_button_label("🎮", "gamer");
+        assert_eq!(short, "🎮 gamer");
+
+        let exact = "a".repeat(BUTTON_LABEL_MAX_CHARS - 2);
+        assert_eq!(
+            format_selfrole_button_label("🎮", &exact),
+            format!("🎮 {}", exact)
+        );
+
+        let long = "ロール".repeat(30);
+        let with_emoji = format_selfrole_button_label("🎮", &long);
+        assert_eq!(with_emoji.chars().count(), BUTTON_LABEL_MAX_CHARS);
+        assert!(with_emoji.starts_with("🎮 ロール"));
+        assert!(with_emoji.ends_with('…'));
+
+        let without_emoji = format_selfrole_button_label("  ", &long);
+        assert_eq!(without_emoji.chars().count(), BUTTON_LABEL_MAX_CHARS);
+        assert!(without_emoji.ends_with('…'));
+    }
+
+    #[test]
+    fn test_selfrole_role_entry_is_enriched() {
+        let mut role = serenity::Role::default();
+        role.name = "gamer".to_string();
+        role.colour = serenity::Colour(0x00ff00);
+        role.position = 3;
+
+        let entry = selfrole_role_entry("111", "🎮", "games", Some(&role), true, Some(&[5]));
+        assert_eq!(
+            entry,
+            json!({
+                "role_id": "111",
+                "emoji": "🎮",
+                "label": "games",
+                "role_name": "gamer",
+                "color": 0x00ff00,
+                "manageable": true,
+            })
+        );
+
+        // above the bot's highest role
+        let entry = selfrole_role_entry("111", "", "", Some(&role), true, Some(&[2]));
+        assert_eq!(entry["manageable"], false);
+
+        // deleted from the server
+        let entry = selfrole_role_entry("111", "", "", None, true, Some(&[5]));
+        assert!(entry["role_name"].is_null());
+        assert_eq!(entry["manageable"], false);
+
+        // discord couldn't be reached
+        let entry = selfrole_role_entry("111", "", "", None, false, None);
+        assert!(entry["role_name"].is_null());
+        assert!(entry["color"].is_null());
+        assert!(entry["manageable"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_selfroles_archived_on_leave_and_restored_on_rejoin() {
+        use clouder_core::database::selfroles::{SelfRoleConfig, SelfRoleRole};
+
+        let app_state = create_test_app_state().await;
+        let db = &app_state.db;
+        let config = SelfRoleConfig::create(db, "1", "2", "Colors", "pick one", "radio")
+            .await
+            .unwrap();
+        SelfRoleRole::create(db, config.id, "100", "🔴")
+            .await
+            .unwrap();
+
+        let left = archive_guild_selfroles(&app_state, 1).await.unwrap();
+        assert_eq!(left, json!({ "archived": 1, "deleted": 0 }));
+        assert!(
+            SelfRoleConfig::get_by_guild(db, "1")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            SelfRoleConfig::count_by_channel(db, "1", "2")
+                .await
+                .unwrap(),
+            0
+        );
+
+        let rejoined = restore_guild_selfroles(&app_state, 1).await.unwrap();
+        assert_eq!(rejoined["restored"], 1);
+        let restored = SelfRoleConfig::get_by_guild(db, "1").await.unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].get_roles(db).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_archived_selfroles_purged_after_retention() {
+        use clouder_core::database::selfroles::{SelfRoleConfig, SelfRoleRole};
+
+        let app_state = create_test_app_state().await;
+        let db = &app_state.db;
+        let archived = SelfRoleConfig::create(db, "1", "2", "Colors", "pick one", "radio")
+            .await
+            .unwrap();
+        SelfRoleRole::create(db, archived.id, "100", "🔴")
+            .await
+            .unwrap();
+        let kept = SelfRoleConfig::create(db, "3", "4", "Pings", "pick any", "multiple")
+            .await
+            .unwrap();
+        archive_guild_selfroles(&app_state, 1).await.unwrap();
+
+        let before = chrono::Utc::now() - chrono::Duration::days(1);
+        assert_eq!(
+            SelfRoleConfig::purge_archived_before(db, before)
+                .await
+                .unwrap(),
+            0
+        );
+        let before = chrono::Utc::now() + chrono::Duration::seconds(1);
+        assert_eq!(
+            SelfRoleConfig::purge_archived_before(db, before)
+                .await
+                .unwrap(),
+            1
+        );
+
+        assert!(
+            SelfRoleConfig::get_by_id(db, archived.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(archived.get_roles(db).await.unwrap().is_empty());
+        assert!(
+            SelfRoleConfig::get_by_id(db, kept.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        let rejoined = restore_guild_selfroles(&app_state, 1).await.unwrap();
+        assert_eq!(rejoined["restored"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_zero_retention_deletes_selfroles_on_leave() {
+        use clouder_core::config::{AppState, Config};
+        use clouder_core::database::selfroles::SelfRoleConfig;
+        use std::sync::Arc;
+
+        let base = create_test_app_state().await;
+        let mut config = Config::test_config();
+        config.selfrole_archive_retention_days = 0;
+        let app_state = AppState::new(Arc::new(config), base.db.clone(), base.http.clone());
+        let panel = SelfRoleConfig::create(&app_state.db, "1", "2", "Colors", "pick one", "radio")
+            .await
+            .unwrap();
+
+        let left = archive_guild_selfroles(&app_state, 1).await.unwrap();
+        assert_eq!(left, json!({ "archived": 0, "deleted": 1 }));
+        assert!(
+            SelfRoleConfig::get_by_id(&app_state.db, panel.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_channel_guild_problem_rejects_other_guilds() {
+        let guild = serenity::GuildId::new(1);
+        assert_eq!(channel_guild_problem(Some(guild), guild), None);
+        assert_eq!(
+            channel_guild_problem(Some(serenity::GuildId::new(2)), guild),
+            Some("that channel isn't in this server")
+        );
+        // DMs and group chats have no guild at all
+        assert!(channel_guild_problem(None, guild).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_welcome_goodbye_rejects_malformed_channel_before_saving() {
+        let app_state = create_test_app_state().await;
+
+        let error = patch_welcome_goodbye_config(
+            &app_state,
+            123,
+            &json!({ "welcome_enabled": true, "goodbye_channel_id": "general" }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, "goodbye_channel_id: invalid channel id");
+
+        let stored = clouder_core::database::welcome_goodbye::WelcomeGoodbyeConfig::get_config(
+            &app_state.db,
+            "123",
+        )
+        .await
+        .unwrap();
+        assert!(stored.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_filter_selfrole_configs_by_title_and_channel() {
+        use clouder_core::database::selfroles::SelfRoleConfig;
+
+        let app_state = create_test_app_state().await;
+        let db = &app_state.db;
+        for (channel, title) in [("10", "Colors"), ("10", "Pronouns"), ("20", "Color pings")] {
+            SelfRoleConfig::create(db, "1", channel, title, "", "radio")
+                .await
+                .unwrap();
+        }
+        async fn titles(
+            db: &sqlx::SqlitePool,
+            search: Option<&str>,
+            channel: Option<&str>,
+        ) -> Vec<String> {
+            let configs = SelfRoleConfig::get_by_guild(db, "1").await.unwrap();
+            let mut titles: Vec<String> = filter_selfrole_configs(configs, search, channel)
+                .into_iter()
+                .map(|c| c.title)
+                .collect();
+            titles.sort();
+            titles
+        }
+
+        assert_eq!(titles(db, None, None).await.len(), 3);
+        assert_eq!(titles(db, Some("  "), Some("")).await.len(), 3);
+        assert_eq!(
+            titles(db, Some("COLOR"), None).await,
+            ["Color pings", "Colors"]
+        );
+        assert_eq!(titles(db, None, Some("10")).await, ["Colors", "Pronouns"]);
+        assert_eq!(titles(db, Some("color"), Some("20")).await, ["Color pings"]);
+
+        // no match means no discord lookups; total still counts every panel
+        let listed = list_selfroles(&app_state, 1, Some("nothing"), None)
+            .await
+            .unwrap();
+        assert_eq!(listed["configs"], json!([]));
+        assert_eq!(listed["total"], 3);
+    }
 }
@@ -105,6 +105,21 @@ mod tests {
         assert!((bot_highest_position <= higher_position_role));
     }
 
+    #[test]
+    fn test_can_bot_manage_role_precheck() {
+        use clouder_core::utils::can_bot_manage_role;
+
+        // below the bot's highest role
+        assert!(can_bot_manage_role([10, 5, 2], 3, false));
+        // equal to or above it
+        assert!(!can_bot_manage_role([10, 5, 2], 10, false));
+        assert!(!can_bot_manage_role([10, 5, 2], 15, false));
+        // managed roles are never assignable
+        assert!(!can_bot_manage_role([10], 1, true));
+        // bot with only @everyone can't manage anything
+        assert!(!can_bot_manage_role([], 0, false));
+    }
+
     #[test]
     fn test_hierarchy_permissions() {
         // Test admin permissions bypass (bitwise)
@@ -386,4 +401,106 @@ mod tests {
         let text = make_message("just text", vec![], vec![]);
         assert!(!has_allowed_content(&text, true, true, true, true));
     }
+
+    #[test]
+    fn test_gif_detection_prefers_content_type() {
+        use clouder_core::utils::content_detection::{has_allowed_content, has_gif};
+
+        // content type wins over a misleading or missing extension
+        let renamed_png = make_message(
+            "",
+            vec![make_attachment("cat.gif", Some("image/png"))],
+            vec![],
+        );
+        assert!(!has_gif(&renamed_png));
+        assert!(has_allowed_content(&renamed_png, false, true, false, false));
+
+        let bare_gif = make_message("", vec![make_attachment("cat", Some("image/gif"))], vec![]);
+        assert!(has_gif(&bare_gif));
+        assert!(!has_allowed_content(&bare_gif, false, true, false, false));
+    }
+
+    #[test]
+    fn test_gif_detection_falls_back_to_extension() {
+        use clouder_core::utils::content_detection::{has_allowed_content, has_gif};
+
+        let gif = make_message("", vec![make_attachment("CAT.GIF", None)], vec![]);
+        assert!(has_gif(&gif));
+        assert!(!has_allowed_content(&gif, false, true, false, false));
+
+        let png = make_message("", vec![make_attachment("cat.png", None)], vec![]);
+        assert!(!has_gif(&png));
+        assert!(has_allowed_content(&png, false, true, false, false));
+    }
+
+    #[test]
+    fn test_classify_violation_reasons() {
+        use clouder_core::utils::content_detection::{ViolationReason, classify_violation};
+
+        // allowed content has no violation
+        let png = make_message(
+            "",
+            vec![make_attachment("cat.png", Some("image/png"))],
+            vec![],
+        );
+        assert_eq!(classify_violation(&png, true, true, true, true), None);
+
+        // plain text is never media
+        let text = make_message("just text", vec![], vec![]);
+        assert_eq!(
+            classify_violation(&text, true, true, true, true),
+            Some(ViolationReason::NoMedia)
+        );
+
+        // gif attachment in a channel that accepts attachments but not gifs
+        let gif = make_message(
+            "",
+            vec![make_attachment("cat.gif", Some("image/gif"))],
+            vec![],
+        );
+        assert_eq!(
+            classify_violation(&gif, false, true, false, false),
+            Some(ViolationReason::GifsNotAllowed)
+        );
+
+        // tenor link is reported as a gif, not a generic link
+        let tenor = make_message("https://tenor.com/view/cat-12345", vec![], vec![]);
+        assert_eq!(
+            classify_violation(&tenor, false, true, false, false),
+            Some(ViolationReason::GifsNotAllowed)
+        );
+
+        assert_eq!(
+            classify_violation(&png, true, false, true, true),
+            Some(ViolationReason::AttachmentsNotAllowed)
+        );
+
+        let link = make_message("check https://example.com", vec![], vec![]);
+        assert_eq!(
+            classify_violation(&link, false, true, true, true),
+            Some(ViolationReason::LinksNotAllowed)
+        );
+    }
+
+    #[test]
+    fn test_custom_id_joins_parts() {
+        assert_eq!(custom_id("gh_prev", &[&42u64]), "gh_prev_42");
+        assert_eq!(custom_id("selfrole", &[&7i64, &"123"]), "selfrole_7_123");
+    }
+
+    #[test]
+    fn test_selfrole_custom_id_fits_discord_limit() {
+        use clouder_core::shared::selfrole_custom_id;
+
+        let custom_id = selfrole_custom_id(i64::MAX, &u64::MAX.to_string());
+        assert!(custom_id.len() <= CUSTOM_ID_MAX_LEN);
+        assert_eq!(custom_id.split('_').count(), 3);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "custom_id too long")]
+    fn test_custom_id_rejects_overlong_ids() {
+        custom_id("x", &[&"y".repeat(CUSTOM_ID_MAX_LEN)]);
+    }
 }
@@ -1,8 +1,15 @@
 #[cfg(test)]
 mod tests {
-    use clouder_core::database::welcome_goodbye::WelcomeGoodbyeConfig;
-    use clouder_core::utils::welcome_goodbye::{EmbedConfig, build_embed, replace_placeholders};
-    use serenity::{model::id::UserId, model::user::User};
+    use crate::tests::create_test_db;
+    use clouder_core::database::welcome_goodbye::{
+        MemberMessageKind, PendingWelcome, UNKNOWN_PLACEHOLDER, WelcomeGoodbyeConfig,
+        get_member_placeholders,
+    };
+    use clouder_core::utils::welcome_goodbye::{
+        EmbedConfig, NamePlaceholderSource, RotationMode, build_embed, completed_screening,
+        replace_placeholders, select_variant_index,
+    };
+    use serenity::model::{Timestamp, guild::Member, id::UserId, user::User};
     use sqlx::SqlitePool;
     use std::collections::HashMap;
 
@@ -39,6 +46,9 @@ mod tests {
             include_str!("../../clouder-core/migrations/001_initial.sql"),
             include_str!("../../clouder-core/migrations/002_reminders.sql"),
             include_str!("../../clouder-core/migrations/003_welcome_goodbye.sql"),
+            include_str!("../../clouder-core/migrations/020_welcome_rotation.sql"),
+            include_str!("../../clouder-core/migrations/022_welcome_after_screening.sql"),
+            include_str!("../../clouder-core/migrations/023_name_placeholder_source.sql"),
         ];
 
         for migration_content in migrations.iter() {
@@ -115,10 +125,69 @@ mod tests {
         user.id = UserId::new(123456789);
         user.name = "TestUser".to_string();
 
-        // Create a mock guild
-        let mut guild = serenity::model::guild::Guild::default();
-        guild.name = "Test Server".to_string();
-        guild.member_count = 42;
+        let mut member = Member::default();
+        member.joined_at = Some(Timestamp::from_unix_timestamp(1_700_000_000).unwrap());
+
+        let with_member = get_member_placeholders(
+            &user,
+            Some("Test Server"),
+            Some(42),
+            Some(&member),
+            NamePlaceholderSource::Username,
+        );
+        assert_eq!(with_member["user"], "<@123456789>");
+        assert_eq!(with_member["username"], "TestUser");
+        assert_eq!(with_member["user_id"], "123456789");
+        assert_eq!(with_member["server"], "Test Server");
+        assert_eq!(with_member["member_count"], "42");
+        assert_eq!(with_member["join_date"], "2023-11-14");
+
+        // A departed member that was never cached, in a guild missing from the cache.
+        let without_member =
+            get_member_placeholders(&user, None, None, None, NamePlaceholderSource::Username);
+        assert_eq!(without_member["user"], "<@123456789>");
+        assert_eq!(without_member["username"], "TestUser");
+        assert_eq!(without_member["user_id"], "123456789");
+        assert_eq!(without_member["server"], UNKNOWN_PLACEHOLDER);
+        assert_eq!(without_member["member_count"], UNKNOWN_PLACEHOLDER);
+        assert_eq!(without_member["join_date"], UNKNOWN_PLACEHOLDER);
+    }
+
+    #[test]
+    fn test_name_placeholder_precedence() {
+        use NamePlaceholderSource::*;
+
+        assert_eq!(
+            Username.resolve("user", Some("Global"), Some("Nick")),
+            "user"
+        );
+        assert_eq!(
+            GlobalName.resolve("user", Some("Global"), Some("Nick")),
+            "Global"
+        );
+        assert_eq!(
+            Nickname.resolve("user", Some("Global"), Some("Nick")),
+            "Nick"
+        );
+
+        // missing or blank names fall through to the next source
+        assert_eq!(Nickname.resolve("user", Some("Global"), None), "Global");
+        assert_eq!(Nickname.resolve("user", None, Some("  ")), "user");
+        assert_eq!(GlobalName.resolve("user", None, Some("Nick")), "user");
+
+        assert_eq!(NamePlaceholderSource::parse("nickname"), Some(Nickname));
+        assert_eq!(NamePlaceholderSource::parse("display"), None);
+
+        let mut user = User::default();
+        user.name = "user".to_string();
+        user.global_name = Some("Global".to_string());
+        let mut member = Member::default();
+        member.nick = Some("Nick".to_string());
+        let placeholders = get_member_placeholders(&user, None, None, Some(&member), Nickname);
+        assert_eq!(placeholders["username"], "Nick");
+        // a departed member with no cached nickname falls back to the global name
+        let placeholders = get_member_placeholders(&user, None, None, None, Nickname);
+        assert_eq!(placeholders["username"], "Global");
     }
 
     #[tokio::test]
@@ -130,6 +199,9 @@ mod tests {
             include_str!("../../clouder-core/migrations/001_initial.sql"),
             include_str!("../../clouder-core/migrations/002_reminders.sql"),
             include_str!("../../clouder-core/migrations/003_welcome_goodbye.sql"),
+            include_str!("../../clouder-core/migrations/020_welcome_rotation.sql"),
+            include_str!("../../clouder-core/migrations/022_welcome_after_screening.sql"),
+            include_str!("../../clouder-core/migrations/023_name_placeholder_source.sql"),
         ];
 
         for migration_content in migrations.iter() {
@@ -474,4 +546,146 @@ mod tests {
         let embed = build_embed(&config, &placeholders);
         assert!(std::mem::size_of_val(&embed) > 0);
     }
+
+    #[test]
+    fn test_select_variant_index() {
+        // off or no variants falls back to the configured message
+        assert_eq!(select_variant_index(RotationMode::Off, 3, 5, 7), None);
+        assert_eq!(select_variant_index(RotationMode::Random, 0, 5, 7), None);
+        assert_eq!(
+            select_variant_index(RotationMode::RoundRobin, 0, 5, 7),
+            None
+        );
+
+        // round-robin walks the list and wraps, ignoring the roll
+        let picks: Vec<_> = (0..5)
+            .map(|counter| select_variant_index(RotationMode::RoundRobin, 3, counter, 99))
+            .collect();
+        assert_eq!(picks, [Some(0), Some(1), Some(2), Some(0), Some(1)]);
+
+        // random is fully determined by the roll, ignoring the counter
+        assert_eq!(select_variant_index(RotationMode::Random, 3, 0, 7), Some(1));
+        assert_eq!(select_variant_index(RotationMode::Random, 3, 1, 7), Some(1));
+        assert_eq!(
+            select_variant_index(RotationMode::Random, 3, 0, u64::MAX),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_completed_screening_transition() {
+        // pending -> not pending is the screening pass
+        assert!(completed_screening(false, Some(true), false));
+        // still pending, or an ordinary update for an already-screened member
+        assert!(!completed_screening(false, Some(true), true));
+        assert!(!completed_screening(false, Some(false), false));
+        assert!(!completed_screening(false, Some(false), true));
+        // without the cached old state or a recorded join there's nothing to go on
+        assert!(!completed_screening(false, None, false));
+        assert!(!completed_screening(false, None, true));
+        // a recorded join is enough on its own, cached or not
+        assert!(completed_screening(true, None, false));
+        assert!(completed_screening(true, Some(false), false));
+        assert!(!completed_screening(true, None, true));
+    }
+
+    #[tokio::test]
+    async fn test_pending_welcome_survives_uncached_member_state() {
+        let pool = create_test_db().await;
+
+        PendingWelcome::record(&pool, "42", "7").await.unwrap();
+        // joining again while still pending doesn't duplicate the entry
+        PendingWelcome::record(&pool, "42", "7").await.unwrap();
+        assert_eq!(
+            PendingWelcome::get_by_guild(&pool, "42")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // the update that passes screening arrives with no cached old state
+        let recorded = PendingWelcome::take(&pool, "42", "7").await.unwrap();
+        assert!(completed_screening(recorded, None, false));
+
+        // later updates find nothing, so the welcome goes out once
+        let recorded = PendingWelcome::take(&pool, "42", "7").await.unwrap();
+        assert!(!completed_screening(recorded, None, false));
+        assert!(!PendingWelcome::take(&pool, "43", "7").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_variants_and_round_robin_counter() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        for migration_content in [
+            include_str!("../../clouder-core/migrations/003_welcome_goodbye.sql"),
+            include_str!("../../clouder-core/migrations/020_welcome_rotation.sql"),
+            include_str!("../../clouder-core/migrations/022_welcome_after_screening.sql"),
+            include_str!("../../clouder-core/migrations/023_name_placeholder_source.sql"),
+        ] {
+            for statement in migration_content.split(';') {
+                let statement = statement.trim();
+                if !statement.is_empty() {
+                    sqlx::query(statement).execute(&pool).await.unwrap();
+                }
+            }
+        }
+
+        let guild_id = "42";
+        let config = WelcomeGoodbyeConfig {
+            guild_id: guild_id.to_string(),
+            welcome_rotation: "round_robin".to_string(),
+            ..Default::default()
+        };
+        WelcomeGoodbyeConfig::upsert_config(&pool, &config)
+            .await
+            .unwrap();
+
+        let variants = vec!["hi {user}".to_string(), "hey {user}".to_string()];
+        WelcomeGoodbyeConfig::set_variants(&pool, guild_id, MemberMessageKind::Welcome, &variants)
+            .await
+            .unwrap();
+        assert_eq!(
+            WelcomeGoodbyeConfig::get_variants(&pool, guild_id, MemberMessageKind::Welcome)
+                .await
+                .unwrap(),
+            variants
+        );
+        assert!(
+            WelcomeGoodbyeConfig::get_variants(&pool, guild_id, MemberMessageKind::Goodbye)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        for expected in 0..2 {
+            let counter = WelcomeGoodbyeConfig::next_rotation_counter(
+                &pool,
+                guild_id,
+                MemberMessageKind::Welcome,
+            )
+            .await
+            .unwrap();
+            assert_eq!(counter, expected);
+        }
+
+        // saving the config keeps the rotation going where it left off
+        let saved = WelcomeGoodbyeConfig::get_config(&pool, guild_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.welcome_rotation, "round_robin");
+        assert_eq!(saved.goodbye_rotation, "off");
+        WelcomeGoodbyeConfig::upsert_config(&pool, &saved)
+            .await
+            .unwrap();
+        let counter = WelcomeGoodbyeConfig::next_rotation_counter(
+            &pool,
+            guild_id,
+            MemberMessageKind::Welcome,
+        )
+        .await
+        .unwrap();
+        assert_eq!(counter, 2);
+    }
 }